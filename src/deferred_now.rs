@@ -1,13 +1,61 @@
-use chrono::{DateTime, Local};
+use chrono::format::{DelayedFormat, StrftimeItems};
+use chrono::{DateTime, FixedOffset, Local};
 
 /// Deferred timestamp creation.
 ///
 /// Is used to ensure that a log record that is sent to multiple outputs
 /// (in maybe different formats) always uses the same timestamp.
-pub struct DeferredNow(Option<DateTime<Local>>);
+pub struct DeferredNow(Option<DateTime<Local>>, Option<FixedOffset>);
 impl<'a> DeferredNow {
+    #[cfg(test)]
     pub(crate) fn new() -> DeferredNow {
-        DeferredNow(None)
+        DeferredNow(None, None)
+    }
+
+    // The timestamp is rendered with the offset, if one is given (see Logger::use_offset()).
+    pub(crate) fn with_offset(o_offset: Option<FixedOffset>) -> DeferredNow {
+        DeferredNow(None, o_offset)
+    }
+
+    // Provides a timestamp that was taken before, e.g. for records that are written late.
+    pub(crate) fn with_timestamp_and_offset(
+        timestamp: DateTime<Local>,
+        o_offset: Option<FixedOffset>,
+    ) -> DeferredNow {
+        DeferredNow(Some(timestamp), o_offset)
+    }
+
+    /// Constructs a `DeferredNow` that always provides the given timestamp,
+    /// rather than reading the clock.
+    ///
+    /// This allows testing format functions deterministically:
+    ///
+    /// ```rust
+    /// use chrono::{Local, TimeZone};
+    /// use flexi_logger::{opt_format, DeferredNow};
+    ///
+    /// let mut now = DeferredNow::with_fixed(Local.with_ymd_and_hms(2019, 10, 24, 8, 30, 0).unwrap());
+    /// let mut buf = Vec::<u8>::new();
+    /// opt_format(
+    ///     &mut buf,
+    ///     &mut now,
+    ///     &log::Record::builder()
+    ///         .args(format_args!("Task successfully read"))
+    ///         .level(log::Level::Info)
+    ///         .file(Some("src/foo/bar.rs"))
+    ///         .line(Some(26))
+    ///         .build(),
+    /// )
+    /// .unwrap();
+    /// assert!(String::from_utf8(buf)
+    ///     .unwrap()
+    ///     .starts_with("[2019-10-24 08:30:00.000000 "));
+    /// ```
+    ///
+    /// Only available with feature `testing`.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn with_fixed(timestamp: DateTime<Local>) -> DeferredNow {
+        DeferredNow(Some(timestamp), None)
     }
 
     /// Retrieve the timestamp.
@@ -19,4 +67,17 @@ impl<'a> DeferredNow {
         }
         self.0.as_ref().unwrap()
     }
+
+    /// Formats the timestamp with the given `strftime`-like format string
+    /// (see [`chrono::format::strftime`](https://docs.rs/chrono/latest/chrono/format/strftime/)).
+    ///
+    /// Unlike `now().format(fmt)`, this respects the offset that was configured with
+    /// [`Logger::use_offset()`](struct.Logger.html#method.use_offset);
+    /// the provided format functions use this method.
+    pub fn format(&'a mut self, fmt: &'a str) -> DelayedFormat<StrftimeItems<'a>> {
+        match self.1 {
+            Some(offset) => self.now().with_timezone(&offset).format(fmt),
+            None => self.now().format(fmt),
+        }
+    }
 }
@@ -19,7 +19,10 @@ pub enum FlexiLoggerError {
     /// Invalid level filter.
     LevelFilter(String),
     /// Some error occured during parsing.
-    Parse(Vec<String>, LogSpecification),
+    ///
+    /// Contains the individual errors, and the log specification that results from
+    /// ignoring the erroneous parts.
+    Parse(Vec<SpecParseError>, LogSpecification),
     /// Logger initialization failed.
     Log(log::SetLoggerError),
 }
@@ -35,8 +38,8 @@ impl fmt::Display for FlexiLoggerError {
             #[cfg(feature = "specfile")]
             FlexiLoggerError::Toml(ref err) => fmt::Display::fmt(err, f),
             FlexiLoggerError::Parse(ref vec, ref logspec) => {
-                for s in vec {
-                    f.write_str(&format!("parse error: \'{}\', ", s))?;
+                for e in vec {
+                    f.write_str(&format!("parse error: \'{}\', ", e))?;
                 }
                 f.write_str(&format!("resulting logspec: {:?}", logspec))?;
                 Ok(())
@@ -76,6 +79,37 @@ impl Error for FlexiLoggerError {
     }
 }
 
+/// Describes an individual error found while parsing a log specification.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SpecParseError {
+    /// The level filter (contained) is unknown.
+    UnknownLevel(String),
+    /// The text filter is not a valid regular expression; contains the error message
+    /// of the regex parser.
+    BadRegex(String),
+    /// The module name (contained) contains a dash or whitespace.
+    InvalidModuleName(String),
+    /// The part of the specification (contained) is malformed, e.g. `a=b=c`.
+    InvalidPart(String),
+}
+
+impl fmt::Display for SpecParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SpecParseError::UnknownLevel(ref s) => write!(f, "unknown level filter: {}", s),
+            SpecParseError::BadRegex(ref s) => write!(f, "invalid regex filter - {}", s),
+            SpecParseError::InvalidModuleName(ref s) => write!(
+                f,
+                "ignoring invalid part in log spec '{}' (contains a dash or whitespace)",
+                s
+            ),
+            SpecParseError::InvalidPart(ref s) => {
+                write!(f, "invalid part in log spec '{}', ignoring it", s)
+            }
+        }
+    }
+}
+
 impl From<log::SetLoggerError> for FlexiLoggerError {
     fn from(err: log::SetLoggerError) -> FlexiLoggerError {
         FlexiLoggerError::Log(err)
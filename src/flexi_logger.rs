@@ -1,11 +1,22 @@
+use crate::formats;
 use crate::primary_writer::PrimaryWriter;
 use crate::writers::LogWriter;
-use crate::LogSpecification;
+use crate::{LogSpecification, MapRecordFunction};
 
+use arc_swap::ArcSwap;
+use chrono::FixedOffset;
 use log;
 use regex::Regex;
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+// The target of the diagnostic messages of flexi_logger itself,
+// which are filtered with the internal log level rather than with the log specification.
+pub(crate) const INTERNAL_TARGET: &str = "flexi_logger::internal";
 
 // Implements log::Log to plug into the log crate.
 //
@@ -15,28 +26,309 @@ use std::sync::{Arc, RwLock};
 // and can additionally duplicate log lines to stderr.
 pub(crate) struct FlexiLogger {
     log_specification: Arc<RwLock<LogSpecification>>,
-    primary_writer: Arc<PrimaryWriter>,
-    other_writers: Arc<HashMap<String, Box<dyn LogWriter>>>,
+    primary_writer: Arc<ArcSwap<PrimaryWriter>>,
+    other_writers: Arc<RwLock<HashMap<String, Box<dyn LogWriter>>>>,
+    o_level_counts: Option<Arc<LevelCounts>>,
+    o_log_once: Option<LogOnce>,
+    #[cfg(feature = "kv")]
+    o_log_on_change: Option<LogOnChange>,
+    o_sampling: Option<Sampling>,
+    o_record_limit: Option<RecordLimit>,
+    o_sequence: Option<AtomicU64>,
+    o_buffer_until_error: Option<BufferUntilError>,
+    o_offset: Option<FixedOffset>,
+    internal_level: log::LevelFilter,
+    sanitize: bool,
+    o_redaction: Option<Redaction>,
+    o_map_record: Option<MapRecordFunction>,
+    // receives all records, independent of the log specification, see Logger::trace_file()
+    o_trace_writer: Option<Arc<dyn LogWriter>>,
+    paused: Arc<AtomicBool>,
+    // the number of records that were dropped by sampling or by the record limit
+    dropped: Arc<AtomicU64>,
 }
 
 impl FlexiLogger {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         log_specification: Arc<RwLock<LogSpecification>>,
-        primary_writer: Arc<PrimaryWriter>,
-        other_writers: Arc<HashMap<String, Box<dyn LogWriter>>>,
+        primary_writer: Arc<ArcSwap<PrimaryWriter>>,
+        other_writers: Arc<RwLock<HashMap<String, Box<dyn LogWriter>>>>,
+        o_level_counts: Option<Arc<LevelCounts>>,
+        o_log_once: Option<LogOnce>,
+        #[cfg(feature = "kv")] o_log_on_change: Option<LogOnChange>,
+        o_sampling: Option<Sampling>,
+        o_record_limit: Option<RecordLimit>,
+        o_sequence: Option<AtomicU64>,
+        o_buffer_until_error: Option<BufferUntilError>,
+        o_offset: Option<FixedOffset>,
+        internal_level: log::LevelFilter,
+        sanitize: bool,
+        o_redaction: Option<Redaction>,
+        o_map_record: Option<MapRecordFunction>,
+        o_trace_writer: Option<Arc<dyn LogWriter>>,
+        paused: Arc<AtomicBool>,
+        dropped: Arc<AtomicU64>,
     ) -> FlexiLogger {
         FlexiLogger {
             log_specification,
             primary_writer,
             other_writers,
+            o_level_counts,
+            o_log_once,
+            #[cfg(feature = "kv")]
+            o_log_on_change,
+            o_sampling,
+            o_record_limit,
+            o_sequence,
+            o_buffer_until_error,
+            o_offset,
+            internal_level,
+            sanitize,
+            o_redaction,
+            o_map_record,
+            o_trace_writer,
+            paused,
+            dropped,
+        }
+    }
+
+    // Is called once for each record that is about to be written;
+    // returns false if the record must not be written.
+    fn accept(&self, record: &log::Record) -> bool {
+        if let Some(ref sampling) = self.o_sampling {
+            if !sampling.is_sampled(record.level()) {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                return false;
+            }
+        }
+        if let Some(ref log_once) = self.o_log_once {
+            if !log_once.is_first_occurrence(record) {
+                return false;
+            }
+        }
+        #[cfg(feature = "kv")]
+        {
+            if let Some(ref log_on_change) = self.o_log_on_change {
+                if !log_on_change.is_change(record) {
+                    return false;
+                }
+            }
+        }
+        if let Some(ref record_limit) = self.o_record_limit {
+            match record_limit.check(record.level()) {
+                LimitCheck::Within => {}
+                LimitCheck::JustReached => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    self.write_limit_notice(record_limit.max);
+                    return false;
+                }
+                LimitCheck::Exceeded => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return false;
+                }
+            }
         }
+        if let Some(ref level_counts) = self.o_level_counts {
+            level_counts.increment(record.level());
+        }
+        self.assign_sequence_number();
+        true
+    }
+
+    // Provides the next sequence number to the format functions, if sequence numbers are used.
+    fn assign_sequence_number(&self) {
+        if let Some(ref sequence) = self.o_sequence {
+            formats::set_sequence_number(sequence.fetch_add(1, Ordering::Relaxed));
+        }
+    }
+
+    // Tells, in place of the first dropped record, that the limit was reached.
+    fn write_limit_notice(&self, max: u64) {
+        if log::Level::Warn > self.internal_level {
+            return;
+        }
+        self.assign_sequence_number();
+        self.primary_writer
+            .load()
+            .write(
+                &mut crate::DeferredNow::with_offset(self.o_offset),
+                &log::Record::builder()
+                    .args(format_args!(
+                        "the maximum of {} records was reached, further records are dropped",
+                        max
+                    ))
+                    .level(log::Level::Warn)
+                    .target(INTERNAL_TARGET)
+                    .module_path_static(Some(module_path!()))
+                    .build(),
+            )
+            .unwrap_or_else(|e| {
+                eprintln!("[flexi_logger] writing log line failed with {}", e);
+            });
     }
 
     fn primary_enabled(&self, level: log::Level, module: &str) -> bool {
+        if module == INTERNAL_TARGET {
+            return level <= self.internal_level;
+        }
         self.log_specification.read().as_ref()
                                 .unwrap(/* catch and expose error? */)
                                 .enabled(level, module)
     }
+
+    // Writes a copy of the record with the given message, keeping its key-value pairs.
+    fn write_record_with_message(&self, record: &log::Record, message: std::fmt::Arguments) {
+        #[cfg(feature = "kv")]
+        let mut builder = record.to_builder();
+        #[cfg(not(feature = "kv"))]
+        let mut builder = log::Record::builder();
+        #[cfg(not(feature = "kv"))]
+        builder
+            .level(record.level())
+            .target(record.target())
+            .module_path(record.module_path())
+            .file(record.file())
+            .line(record.line());
+        self.write_record(&builder.args(message).build());
+    }
+
+    fn trace_enabled(&self, level: log::Level) -> bool {
+        self.o_trace_writer
+            .as_ref()
+            .is_some_and(|trace_writer| level <= trace_writer.max_log_level())
+    }
+
+    // Checks the record against the log specification, or, for internal records,
+    // against the internal log level.
+    fn passes_specification(&self, record: &log::Record) -> bool {
+        if record.target() == INTERNAL_TARGET {
+            record.level() <= self.internal_level
+        } else {
+            let log_specification = self.log_specification.read().unwrap(/* expose this? */);
+            log_specification.enabled(record.level(), log_specification.filter_subject(record))
+                && log_specification.record_text_matches(record)
+        }
+    }
+
+    // Redacts and sanitizes the message, if required, and writes the record.
+    fn write_cleaned_record(&self, record: &log::Record) {
+        if !self.sanitize && self.o_redaction.is_none() {
+            self.write_record(record);
+            return;
+        }
+        let mut message = match record.args().as_str() {
+            Some(s) => Cow::Borrowed(s),
+            None => Cow::Owned(record.args().to_string()),
+        };
+        let mut changed = false;
+        if let Some(ref redaction) = self.o_redaction {
+            if let Some(redacted) = redaction.redact(&message) {
+                message = Cow::Owned(redacted);
+                changed = true;
+            }
+        }
+        if self.sanitize && message.contains(char::is_control) {
+            message = Cow::Owned(escape_control_chars(&message));
+            changed = true;
+        }
+        if changed {
+            self.write_record_with_message(record, format_args!("{}", message));
+        } else {
+            self.write_record(record);
+        }
+    }
+
+    // Writes the record to the trace writer, if there is one and it accepts the level.
+    fn write_trace(&self, now: &mut crate::DeferredNow, record: &log::Record) {
+        if let Some(ref trace_writer) = self.o_trace_writer {
+            if record.level() <= trace_writer.max_log_level() {
+                trace_writer.write(now, record).unwrap_or_else(|e| {
+                    eprintln!(
+                        "[flexi_logger] writing log line to trace file failed with {}",
+                        e
+                    );
+                });
+            }
+        }
+    }
+
+    // Writes the record to the addressed writers.
+    fn write_record(&self, record: &log::Record) {
+        if self.paused.load(Ordering::Relaxed) {
+            return;
+        }
+        let _guard = LoggingGuard::new();
+        let target = record.metadata().target();
+        let mut now = crate::DeferredNow::with_offset(self.o_offset);
+        let mut o_accepted = None;
+        if target.starts_with('{') {
+            let targets: Vec<&str> = target[1..(target.len() - 1)].split(',').collect();
+            let use_default = targets.contains(&"_Default");
+            if !use_default {
+                // the trace file gets also the records that address only custom writers
+                self.write_trace(&mut now, record);
+            }
+            let other_writers = self.other_writers.read().unwrap();
+            for t in targets {
+                if t != "_Default" {
+                    match other_writers.get(t) {
+                        None => eprintln!("[flexi_logger] found bad writer spec: {}", t),
+                        // log::max_level() is the maximum over all targets,
+                        // so each writer has to apply its own level
+                        Some(writer) if record.level() > writer.max_log_level() => {}
+                        Some(writer) => {
+                            if !*o_accepted.get_or_insert_with(|| self.accept(record)) {
+                                return;
+                            }
+                            writer.write(&mut now, record).unwrap_or_else(|e| {
+                                eprintln!(
+                                    "[flexi_logger] writing log line to custom writer \"{}\" \
+                                     failed with: \"{}\"",
+                                    t, e
+                                );
+                            });
+                        }
+                    }
+                }
+            }
+            if !use_default {
+                return;
+            }
+        }
+
+        let passes_specification = self.passes_specification(record);
+        if target != INTERNAL_TARGET || passes_specification {
+            self.write_trace(&mut now, record);
+        }
+        if !passes_specification {
+            return;
+        }
+
+        if !o_accepted.unwrap_or_else(|| self.accept(record)) {
+            return;
+        }
+
+        let primary_writer = self.primary_writer.load();
+        if let Some(ref buffer_until_error) = self.o_buffer_until_error {
+            match buffer_until_error.check(&mut now, record) {
+                Buffering::Buffered => return,
+                Buffering::Release(buffered_records) => {
+                    let o_sequence_number = formats::sequence_number();
+                    for buffered_record in buffered_records {
+                        buffered_record.write_to(&primary_writer, self.o_offset);
+                    }
+                    if let Some(number) = o_sequence_number {
+                        formats::set_sequence_number(number);
+                    }
+                }
+                Buffering::PassThrough => {}
+            }
+        }
+        primary_writer.write(&mut now, record).unwrap_or_else(|e| {
+            eprintln!("[flexi_logger] writing log line failed with {}", e);
+        });
+    }
 }
 
 impl log::Log for FlexiLogger {
@@ -58,19 +350,23 @@ impl log::Log for FlexiLogger {
         let level = metadata.level();
 
         // This is bad - we should have the module_path here :-(
-        if self.primary_enabled(level, target) {
+        if self.primary_enabled(level, target) || self.trace_enabled(level) {
             return true;
         };
 
-        if !self.other_writers.is_empty() && target.starts_with('{') {
+        if !target.starts_with('{') {
+            return false;
+        }
+        let other_writers = self.other_writers.read().unwrap();
+        if !other_writers.is_empty() {
             // at least one other writer is configured _and_ addressed
             let targets: Vec<&str> = target[1..(target.len() - 1)].split(',').collect();
             for t in targets {
                 if t != "_Default" {
-                    match self.other_writers.get(t) {
+                    match other_writers.get(t) {
                         None => eprintln!("[flexi_logger] bad writer spec: {}", t),
                         Some(writer) => {
-                            if level < writer.max_log_level() {
+                            if level <= writer.max_log_level() {
                                 return true;
                             }
                         }
@@ -82,68 +378,393 @@ impl log::Log for FlexiLogger {
     }
 
     fn log(&self, record: &log::Record) {
-        let target = record.metadata().target();
-        let mut now = crate::DeferredNow::new();
-        if target.starts_with('{') {
-            let mut use_default = false;
-            let targets: Vec<&str> = target[1..(target.len() - 1)].split(',').collect();
-            for t in targets {
-                if t == "_Default" {
-                    use_default = true;
+        match self.o_map_record {
+            None => self.write_cleaned_record(record),
+            Some(ref map_record) => {
+                // only records that would be written are handed to the function;
+                // records to custom writers are filtered by the writers' levels
+                let passes = if record.target().starts_with('{') {
+                    self.enabled(record.metadata())
                 } else {
-                    match self.other_writers.get(t) {
-                        None => eprintln!("[flexi_logger] found bad writer spec: {}", t),
-                        Some(writer) => {
-                            writer.write(&mut now, record).unwrap_or_else(|e| {
-                                eprintln!(
-                                    "[flexi_logger] writing log line to custom writer \"{}\" \
-                                     failed with: \"{}\"",
-                                    t, e
-                                );
-                            });
-                        }
-                    }
+                    self.trace_enabled(record.level()) || self.passes_specification(record)
+                };
+                if !passes {
+                    return;
+                }
+                if let Some(owned_record) = map_record(record) {
+                    owned_record.with_record(|record| self.write_cleaned_record(record));
                 }
             }
-            if !use_default {
-                return;
+        }
+    }
+
+    fn flush(&self) {
+        let _guard = LoggingGuard::new();
+        self.primary_writer.load().flush().unwrap_or_else(|e| {
+            eprintln!("[flexi_logger] flushing primary writer failed with {}", e);
+        });
+        for writer in self.other_writers.read().unwrap().values() {
+            writer.flush().unwrap_or_else(|e| {
+                eprintln!("[flexi_logger] flushing custom writer failed with {}", e);
+            });
+        }
+        if let Some(ref trace_writer) = self.o_trace_writer {
+            trace_writer.flush().unwrap_or_else(|e| {
+                eprintln!("[flexi_logger] flushing trace file failed with {}", e);
+            });
+        }
+    }
+}
+
+// Counts the written log lines per level.
+#[derive(Default)]
+pub(crate) struct LevelCounts([AtomicU64; 5]);
+impl LevelCounts {
+    fn increment(&self, level: log::Level) {
+        self.0[level as usize - 1].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> [u64; 5] {
+        let mut counts = [0; 5];
+        for (count, atomic) in counts.iter_mut().zip(self.0.iter()) {
+            *count = atomic.load(Ordering::Relaxed);
+        }
+        counts
+    }
+}
+
+// Replaces the matches of the patterns in the messages with `***`, see Logger::redact().
+pub(crate) struct Redaction {
+    patterns: Vec<Regex>,
+}
+impl Redaction {
+    pub fn new(patterns: Vec<Regex>) -> Redaction {
+        Redaction { patterns }
+    }
+
+    // Returns the redacted message, or None if no pattern matches.
+    // Overlapping or adjacent matches are merged, so the result does not depend
+    // on the order of the patterns.
+    fn redact(&self, message: &str) -> Option<String> {
+        let mut ranges: Vec<(usize, usize)> = self
+            .patterns
+            .iter()
+            .flat_map(|pattern| pattern.find_iter(message))
+            .filter(|m| !m.as_str().is_empty())
+            .map(|m| (m.start(), m.end()))
+            .collect();
+        if ranges.is_empty() {
+            return None;
+        }
+        ranges.sort_unstable();
+
+        let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = std::cmp::max(last.1, end),
+                _ => merged.push((start, end)),
             }
         }
 
-        if !self.primary_enabled(record.level(), record.module_path().unwrap_or_default()) {
-            return;
+        let mut redacted = String::with_capacity(message.len());
+        let mut pos = 0;
+        for (start, end) in merged {
+            redacted.push_str(&message[pos..start]);
+            redacted.push_str("***");
+            pos = end;
         }
+        redacted.push_str(&message[pos..]);
+        Some(redacted)
+    }
+}
 
-        // closure that we need below
-        let check_text_filter = |text_filter: &Option<Regex>| {
-            if let Some(filter) = text_filter.as_ref() {
-                filter.is_match(&*record.args().to_string())
-            } else {
-                true
+// Replaces the control characters of the message, like line breaks or the escape character
+// that starts ANSI escape sequences, with their escaped representation, like `\n` or `\u{1b}`.
+fn escape_control_chars(message: &str) -> String {
+    let mut escaped = String::with_capacity(message.len() + 16);
+    for c in message.chars() {
+        if c.is_control() {
+            escaped.extend(c.escape_default());
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+// Remembers hashes of target and text of the written log lines,
+// up to a maximum number of distinct messages.
+pub(crate) struct LogOnce {
+    max_messages: usize,
+    seen: Mutex<HashSet<u64>>,
+}
+impl LogOnce {
+    pub fn new(max_messages: usize) -> LogOnce {
+        LogOnce {
+            max_messages,
+            seen: Mutex::new(HashSet::new()),
+        }
+    }
+
+    fn is_first_occurrence(&self, record: &log::Record) -> bool {
+        let mut hasher = DefaultHasher::new();
+        record.target().hash(&mut hasher);
+        record.args().to_string().hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let mut seen = self.seen.lock().unwrap();
+        if seen.contains(&hash) {
+            false
+        } else {
+            if seen.len() < self.max_messages {
+                seen.insert(hash);
             }
+            true
+        }
+    }
+}
+
+// Remembers, for each value of the tracked key-value field, a hash of the text
+// of the last written log line, for up to a maximum number of distinct values.
+#[cfg(feature = "kv")]
+pub(crate) struct LogOnChange {
+    key: String,
+    max_keys: usize,
+    last: Mutex<HashMap<String, u64>>,
+}
+#[cfg(feature = "kv")]
+impl LogOnChange {
+    pub fn new(key: String, max_keys: usize) -> LogOnChange {
+        LogOnChange {
+            key,
+            max_keys,
+            last: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn is_change(&self, record: &log::Record) -> bool {
+        let tracked = match record.key_values().get(log::kv::Key::from_str(&self.key)) {
+            Some(value) => value.to_string(),
+            None => return true,
         };
+        let mut hasher = DefaultHasher::new();
+        record.target().hash(&mut hasher);
+        record.args().to_string().hash(&mut hasher);
+        let hash = hasher.finish();
 
-        if !check_text_filter(
-            self.log_specification.read().as_ref().unwrap(/* expose this? */).text_filter(),
-        ) {
-            return;
+        let mut last = self.last.lock().unwrap();
+        match last.get_mut(&tracked) {
+            Some(last_hash) if *last_hash == hash => false,
+            Some(last_hash) => {
+                *last_hash = hash;
+                true
+            }
+            None => {
+                if last.len() < self.max_keys {
+                    last.insert(tracked, hash);
+                }
+                true
+            }
         }
+    }
+}
 
-        self.primary_writer
-            .write(&mut now, record)
+// Lets only every n-th record pass that has the threshold level or a less severe level.
+pub(crate) struct Sampling {
+    threshold: log::Level,
+    rate: u64,
+    counter: AtomicU64,
+}
+impl Sampling {
+    pub fn new(threshold: log::Level, rate: u64) -> Sampling {
+        Sampling {
+            threshold,
+            rate: std::cmp::max(1, rate),
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    fn is_sampled(&self, level: log::Level) -> bool {
+        level < self.threshold || self.counter.fetch_add(1, Ordering::Relaxed) % self.rate == 0
+    }
+}
+
+// Lets only the first `max` records pass, except for the exempt levels.
+pub(crate) struct RecordLimit {
+    max: u64,
+    // this level and the more severe levels are not limited
+    o_exempt: Option<log::Level>,
+    counter: AtomicU64,
+}
+pub(crate) enum LimitCheck {
+    Within,
+    JustReached,
+    Exceeded,
+}
+impl RecordLimit {
+    pub fn new(max: u64, o_exempt: Option<log::Level>) -> RecordLimit {
+        RecordLimit {
+            max,
+            o_exempt,
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    fn check(&self, level: log::Level) -> LimitCheck {
+        if let Some(exempt) = self.o_exempt {
+            if level <= exempt {
+                return LimitCheck::Within;
+            }
+        }
+        let count = self.counter.fetch_add(1, Ordering::Relaxed);
+        if count < self.max {
+            LimitCheck::Within
+        } else if count == self.max {
+            LimitCheck::JustReached
+        } else {
+            // avoid an overflow of the counter in endless runs
+            self.counter.store(self.max + 1, Ordering::Relaxed);
+            LimitCheck::Exceeded
+        }
+    }
+}
+
+// Holds back the records, up to a maximum number, until the first error is logged.
+pub(crate) struct BufferUntilError {
+    capacity: usize,
+    // is None once an error was logged
+    o_records: Mutex<Option<VecDeque<BufferedRecord>>>,
+}
+pub(crate) enum Buffering {
+    Buffered,
+    // the first error came, the held back records have to be written before it
+    Release(VecDeque<BufferedRecord>),
+    PassThrough,
+}
+impl BufferUntilError {
+    pub fn new(capacity: usize) -> BufferUntilError {
+        BufferUntilError {
+            capacity,
+            o_records: Mutex::new(Some(VecDeque::with_capacity(capacity))),
+        }
+    }
+
+    fn check(&self, now: &mut crate::DeferredNow, record: &log::Record) -> Buffering {
+        let mut o_records = self.o_records.lock().unwrap();
+        match o_records.as_mut() {
+            None => Buffering::PassThrough,
+            Some(_) if record.level() == log::Level::Error => {
+                Buffering::Release(o_records.take().unwrap())
+            }
+            Some(records) => {
+                if self.capacity > 0 {
+                    if records.len() == self.capacity {
+                        records.pop_front();
+                    }
+                    records.push_back(BufferedRecord::new(now, record));
+                }
+                Buffering::Buffered
+            }
+        }
+    }
+}
+
+// An owned copy of a record, with its timestamp and its sequence number.
+pub(crate) struct BufferedRecord {
+    timestamp: chrono::DateTime<chrono::Local>,
+    o_sequence_number: Option<u64>,
+    level: log::Level,
+    target: String,
+    o_module_path: Option<String>,
+    o_file: Option<String>,
+    o_line: Option<u32>,
+    message: String,
+    #[cfg(feature = "kv")]
+    key_values: Vec<(String, String)>,
+}
+impl BufferedRecord {
+    fn new(now: &mut crate::DeferredNow, record: &log::Record) -> BufferedRecord {
+        BufferedRecord {
+            timestamp: *now.now(),
+            o_sequence_number: formats::sequence_number(),
+            level: record.level(),
+            target: record.target().to_owned(),
+            o_module_path: record.module_path().map(ToOwned::to_owned),
+            o_file: record.file().map(ToOwned::to_owned),
+            o_line: record.line(),
+            message: record.args().to_string(),
+            #[cfg(feature = "kv")]
+            key_values: collect_key_values(record),
+        }
+    }
+
+    fn write_to(&self, primary_writer: &PrimaryWriter, o_offset: Option<FixedOffset>) {
+        if let Some(number) = self.o_sequence_number {
+            formats::set_sequence_number(number);
+        }
+        let mut builder = log::Record::builder();
+        builder
+            .level(self.level)
+            .target(&self.target)
+            .module_path(self.o_module_path.as_deref())
+            .file(self.o_file.as_deref())
+            .line(self.o_line);
+        #[cfg(feature = "kv")]
+        builder.key_values(&self.key_values);
+        primary_writer
+            .write(
+                &mut crate::DeferredNow::with_timestamp_and_offset(self.timestamp, o_offset),
+                &builder.args(format_args!("{}", self.message)).build(),
+            )
             .unwrap_or_else(|e| {
                 eprintln!("[flexi_logger] writing log line failed with {}", e);
             });
     }
+}
 
-    fn flush(&self) {
-        self.primary_writer.flush().unwrap_or_else(|e| {
-            eprintln!("[flexi_logger] flushing primary writer failed with {}", e);
-        });
-        for writer in self.other_writers.values() {
-            writer.flush().unwrap_or_else(|e| {
-                eprintln!("[flexi_logger] flushing custom writer failed with {}", e);
-            });
-        }
+// Returns the key-value pairs of a record, rendered as strings.
+#[cfg(feature = "kv")]
+pub(crate) fn collect_key_values(record: &log::Record) -> Vec<(String, String)> {
+    let mut collector = KeyValueCollector(Vec::new());
+    record.key_values().visit(&mut collector).ok();
+    collector.0
+}
+
+// Collects the key-value pairs of a record, rendered as strings.
+#[cfg(feature = "kv")]
+struct KeyValueCollector(Vec<(String, String)>);
+#[cfg(feature = "kv")]
+impl<'kvs> log::kv::VisitSource<'kvs> for KeyValueCollector {
+    fn visit_pair(
+        &mut self,
+        key: log::kv::Key<'kvs>,
+        value: log::kv::Value<'kvs>,
+    ) -> Result<(), log::kv::Error> {
+        self.0.push((key.to_string(), value.to_string()));
+        Ok(())
+    }
+}
+
+thread_local! {
+    static LOGGING: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+// Returns true if the current thread is executing flexi_logger code that may hold locks,
+// e.g. when a panic occurs within a format function.
+pub(crate) fn is_logging() -> bool {
+    LOGGING.with(std::cell::Cell::get)
+}
+
+// Marks the current thread as executing flexi_logger code, until it is dropped.
+pub(crate) struct LoggingGuard(bool);
+impl LoggingGuard {
+    pub fn new() -> LoggingGuard {
+        LoggingGuard(LOGGING.with(|flag| flag.replace(true)))
+    }
+}
+impl Drop for LoggingGuard {
+    fn drop(&mut self) {
+        let was_logging = self.0;
+        LOGGING.with(|flag| flag.set(was_logging));
     }
 }
@@ -1,9 +1,84 @@
+use crate::primary_writer::ConsoleStream;
 use crate::DeferredNow;
+#[cfg(feature = "colors")]
+use arc_swap::ArcSwapOption;
 use log::Record;
+#[cfg(feature = "colors")]
+use regex::Regex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::thread;
 
+mod format_builder;
+pub use self::format_builder::{FormatBuilder, LevelStyle, TimestampPrecision};
+#[cfg(feature = "terminal_size")]
+mod terminal_wrap;
+#[cfg(feature = "terminal_size")]
+pub use self::terminal_wrap::terminal_wrapped_format;
+
+/// A format for the log lines, as it is used by the writers.
+///
+/// The methods that take a format, like [`Logger::format()`](struct.Logger.html#method.format),
+/// accept a [`FormatFunction`](type.FormatFunction.html), a closure with the same signature,
+/// or a [`FormatBuilder`](struct.FormatBuilder.html).
+///
+/// ```rust
+/// use flexi_logger::{DeferredNow, FormatBuilder, Logger, Record};
+///
+/// Logger::with_str("info").format(FormatBuilder::new().thread(true));
+///
+/// let prefix = String::from("my_service");
+/// Logger::with_str("info").format(
+///     move |w: &mut dyn std::io::Write, _now: &mut DeferredNow, record: &Record| {
+///         write!(w, "{}: {}", prefix, record.args())
+///     },
+/// );
+/// ```
+#[derive(Clone)]
+pub struct Format(Arc<FormatClosure>);
+
+type FormatClosure = dyn Fn(&mut dyn std::io::Write, &mut DeferredNow, &Record) -> Result<(), std::io::Error>
+    + Send
+    + Sync;
+
+impl Format {
+    /// Writes the record in this format.
+    pub fn write(
+        &self,
+        w: &mut dyn std::io::Write,
+        now: &mut DeferredNow,
+        record: &Record,
+    ) -> Result<(), std::io::Error> {
+        (self.0)(w, now, record)
+    }
+}
+impl<F> From<F> for Format
+where
+    F: Fn(&mut dyn std::io::Write, &mut DeferredNow, &Record) -> Result<(), std::io::Error>
+        + Send
+        + Sync
+        + 'static,
+{
+    fn from(format: F) -> Format {
+        Format(Arc::new(format))
+    }
+}
+impl From<FormatBuilder> for Format {
+    fn from(builder: FormatBuilder) -> Format {
+        builder.build()
+    }
+}
+impl std::fmt::Debug for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("Format")
+    }
+}
+
 /// A logline-formatter that produces log lines like <br>
 /// ```INFO [my_prog::some_submodule] Task successfully read from conf.json```
+///
+/// With feature `kv`, the key-value pairs of the record are appended, like in <br>
+/// ```INFO [my_prog::some_submodule] Task successfully read {file=conf.json, tasks=3}```
 pub fn default_format(
     w: &mut dyn std::io::Write,
     _now: &mut DeferredNow,
@@ -12,16 +87,19 @@ pub fn default_format(
     write!(
         w,
         "{} [{}] {}",
-        record.level(),
+        LevelField(record.level()),
         record.module_path().unwrap_or("<unnamed>"),
         record.args()
-    )
+    )?;
+    write_key_values(w, record)
 }
 
 /// A colored version of the logline-formatter `default_format`
 /// that produces log lines like <br>
 /// <code><span style="color:red">ERROR</span> &#91;my_prog::some_submodule&#93; <span style="color:red">File not found</span></code>
 ///
+/// With feature `kv`, the key-value pairs of the record are appended.
+///
 /// Only available with feature `colors`.
 #[cfg(feature = "colors")]
 pub fn colored_default_format(
@@ -33,9 +111,50 @@ pub fn colored_default_format(
     write!(
         w,
         "{} [{}] {}",
-        style(level, level),
+        style(level, LevelField(level)),
         record.module_path().unwrap_or("<unnamed>"),
-        style(level, record.args())
+        style_message(level, record.args())
+    )?;
+    write_key_values(w, record)
+}
+
+/// A logline-formatter that produces log lines like <br>
+/// ```INFO [metrics] Task successfully read from conf.json```
+/// <br>
+/// i.e. like `default_format`, but with the target of the record rather than its module path.
+///
+/// The target is by default the module path, too, but can be set explicitly,
+/// like in `info!(target: "metrics", ...)`.
+pub fn target_only_format(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    write!(
+        w,
+        "{} [{}] {}",
+        LevelField(record.level()),
+        record.target(),
+        record.args()
+    )
+}
+
+/// A colored version of the logline-formatter `target_only_format`.
+///
+/// Only available with feature `colors`.
+#[cfg(feature = "colors")]
+pub fn colored_target_only_format(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    let level = record.level();
+    write!(
+        w,
+        "{} [{}] {}",
+        style(level, LevelField(level)),
+        record.target(),
+        style_message(level, record.args())
     )
 }
 
@@ -52,8 +171,8 @@ pub fn opt_format(
     write!(
         w,
         "[{}] {} [{}:{}] {}",
-        now.now().format("%Y-%m-%d %H:%M:%S%.6f %:z"),
-        record.level(),
+        now.format("%Y-%m-%d %H:%M:%S%.6f %:z"),
+        LevelField(record.level()),
         record.file().unwrap_or("<unnamed>"),
         record.line().unwrap_or(0),
         &record.args()
@@ -73,11 +192,11 @@ pub fn colored_opt_format(
     write!(
         w,
         "[{}] {} [{}:{}] {}",
-        style(level, now.now().format("%Y-%m-%d %H:%M:%S%.6f %:z")),
-        style(level, level),
+        style(level, now.format("%Y-%m-%d %H:%M:%S%.6f %:z")),
+        style(level, LevelField(level)),
         record.file().unwrap_or("<unnamed>"),
         record.line().unwrap_or(0),
-        style(level, &record.args())
+        style_message(level, record.args())
     )
 }
 
@@ -94,8 +213,8 @@ pub fn detailed_format(
     write!(
         w,
         "[{}] {} [{}] {}:{}: {}",
-        now.now().format("%Y-%m-%d %H:%M:%S%.6f %:z"),
-        record.level(),
+        now.format("%Y-%m-%d %H:%M:%S%.6f %:z"),
+        LevelField(record.level()),
         record.module_path().unwrap_or("<unnamed>"),
         record.file().unwrap_or("<unnamed>"),
         record.line().unwrap_or(0),
@@ -116,15 +235,127 @@ pub fn colored_detailed_format(
     write!(
         w,
         "[{}] {} [{}] {}:{}: {}",
-        style(level, now.now().format("%Y-%m-%d %H:%M:%S%.6f %:z")),
-        style(level, record.level()),
+        style(level, now.format("%Y-%m-%d %H:%M:%S%.6f %:z")),
+        style(level, LevelField(level)),
         record.module_path().unwrap_or("<unnamed>"),
         record.file().unwrap_or("<unnamed>"),
         record.line().unwrap_or(0),
-        style(level, &record.args())
+        style_message(level, record.args())
     )
 }
 
+/// A logline-formatter that produces compact log lines like
+/// <br>
+/// ```I 15:25:01.640 Task successfully read from conf.json```
+/// <br>
+/// i.e. with a one-letter level indicator (`E`, `W`, `I`, `D`, `T`) and a short timestamp.
+pub fn compact_format(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    write!(
+        w,
+        "{} {} {}",
+        level_char(record.level()),
+        now.format("%H:%M:%S%.3f"),
+        &record.args()
+    )
+}
+
+fn level_char(level: log::Level) -> char {
+    match level {
+        log::Level::Error => 'E',
+        log::Level::Warn => 'W',
+        log::Level::Info => 'I',
+        log::Level::Debug => 'D',
+        log::Level::Trace => 'T',
+    }
+}
+
+/// A logline-formatter that produces log lines like <br>
+/// ```[2016-01-13 15:25:01] INFO [my_prog::some_submodule] Task successfully read from conf.json```
+/// <br>
+/// i.e. like `default_format`, with a timestamp that is only printed if it differs from the
+/// timestamp of the previous line; otherwise the timestamp is replaced with spaces.
+///
+/// The previous timestamp is kept per output, e.g. the log file and stderr, and per thread,
+/// so with several logging threads a line without timestamp shares the timestamp of the
+/// previous line of the same thread, which is not necessarily the line above it.
+pub fn default_format_dedup_timestamp(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    thread_local! {
+        // the last timestamp per output
+        static LAST_TIMESTAMPS: std::cell::RefCell<Vec<(usize, String)>> =
+            const { std::cell::RefCell::new(Vec::new()) };
+    }
+    let (output, _) = CURRENT_OUTPUT.with(std::cell::Cell::get);
+    let timestamp = now.format("%Y-%m-%d %H:%M:%S").to_string();
+    let repeated = LAST_TIMESTAMPS.with(|last_timestamps| {
+        let mut last_timestamps = last_timestamps.borrow_mut();
+        match last_timestamps.iter_mut().find(|(o, _)| *o == output) {
+            Some((_, last)) if *last == timestamp => true,
+            Some((_, last)) => {
+                *last = timestamp.clone();
+                false
+            }
+            None => {
+                last_timestamps.push((output, timestamp.clone()));
+                false
+            }
+        }
+    });
+    if repeated {
+        write!(w, "{:1$} ", "", timestamp.len() + 2)?;
+    } else {
+        write!(w, "[{}] ", timestamp)?;
+    }
+    default_format(w, now, record)
+}
+
+/// A logline-formatter that produces log lines like <br>
+/// ```#000123 INFO [my_prog::some_submodule] Task successfully read from conf.json```
+/// <br>
+/// i.e. like `default_format`, prefixed with the sequence number of the record.
+///
+/// The sequence numbers are only assigned if they are switched on with
+/// [`Logger::sequence_numbers()`](struct.Logger.html#method.sequence_numbers);
+/// otherwise the lines are prefixed with `#------`.
+pub fn with_sequence_number(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    match sequence_number() {
+        Some(number) => write!(w, "#{:06} ", number)?,
+        None => write!(w, "#------ ")?,
+    }
+    default_format(w, now, record)
+}
+
+/// A logline-formatter that produces log lines like <br>
+/// ```myhost INFO [my_prog::some_submodule] Task successfully read from conf.json```
+/// <br>
+/// i.e. like `default_format`, prefixed with the hostname.
+///
+/// The hostname is only provided if it is switched on with
+/// [`Logger::with_hostname()`](struct.Logger.html#method.with_hostname);
+/// otherwise the lines are prefixed with `-`.
+///
+/// Only available with feature `hostname`.
+#[cfg(feature = "hostname")]
+pub fn with_hostname(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    write!(w, "{} ", hostname().unwrap_or("-"))?;
+    default_format(w, now, record)
+}
+
 /// A logline-formatter that produces log lines like
 /// <br>
 /// ```[2016-01-13 15:25:01.640870 +01:00] T[taskreader] INFO [src/foo/bar:26] Task successfully read from conf.json```
@@ -138,9 +369,9 @@ pub fn with_thread(
     write!(
         w,
         "[{}] T[{:?}] {} [{}:{}] {}",
-        now.now().format("%Y-%m-%d %H:%M:%S%.6f %:z"),
+        now.format("%Y-%m-%d %H:%M:%S%.6f %:z"),
         thread::current().name().unwrap_or("<unnamed>"),
-        record.level(),
+        LevelField(record.level()),
         record.file().unwrap_or("<unnamed>"),
         record.line().unwrap_or(0),
         &record.args()
@@ -160,15 +391,707 @@ pub fn colored_with_thread(
     write!(
         w,
         "[{}] T[{:?}] {} [{}:{}] {}",
-        style(level, now.now().format("%Y-%m-%d %H:%M:%S%.6f %:z")),
+        style(level, now.format("%Y-%m-%d %H:%M:%S%.6f %:z")),
         style(level, thread::current().name().unwrap_or("<unnamed>")),
-        style(level, level),
+        style(level, LevelField(level)),
         record.file().unwrap_or("<unnamed>"),
         record.line().unwrap_or(0),
-        style(level, &record.args())
+        style_message(level, record.args())
     )
 }
 
+/// A logline-formatter that produces log lines in
+/// [NDJSON](http://ndjson.org/) format, like
+/// <br>
+/// ```{"ts":"2016-01-13T15:25:01.640870+01:00","level":"INFO","module":"foo::bar","msg":"Task successfully read","fields":{"file":"conf.json","tasks":3}}```
+/// <br>
+/// i.e. with the key-value pairs of the record in a nested `fields` object, so that they
+/// cannot collide with the standard fields.
+///
+/// With [`Logger::with_hostname()`](struct.Logger.html#method.with_hostname),
+/// a `host` field follows the `module` field.
+///
+/// Numbers and booleans are written as JSON numbers and booleans, all other values
+/// as JSON strings.
+///
+/// See [`json_format_with()`](fn.json_format_with.html) for other field names.
+///
+/// Only available with feature `kv`.
+#[cfg(feature = "kv")]
+pub fn ndjson_nested_format(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    json_format_with(w, now, record, &JsonFieldNames::NDJSON)
+}
+
+/// Provides a logline-formatter that produces log lines like
+/// [`default_format()`](fn.default_format.html), but shows the module path without the
+/// given leading prefix, e.g. the name of the crate:
+/// <br>
+/// ```INFO [http::handler] Request handled```
+/// <br>
+/// rather than ```INFO [my_service::http::handler] Request handled```.
+///
+/// Module paths that do not start with the prefix are shown unchanged,
+/// see [`FormatBuilder::strip_prefix()`](struct.FormatBuilder.html#method.strip_prefix).
+///
+/// ```rust
+/// use flexi_logger::{format_strip_prefix, Logger};
+///
+/// Logger::with_str("info").format(format_strip_prefix("my_service"));
+/// ```
+pub fn format_strip_prefix(prefix: &str) -> Format {
+    FormatBuilder::new().strip_prefix(prefix).build()
+}
+
+/// A logline-formatter that produces log lines like
+/// [`default_format()`](fn.default_format.html), but with the key-value pairs
+/// of the record appended as a compact JSON object, like <br>
+/// ```INFO [my_prog::some_submodule] Request handled {"req_id":"abc","ms":12}```
+///
+/// This keeps the log lines readable, while the key-value pairs can be extracted by tools.
+/// If the record has no key-value pairs, nothing is appended.
+/// Numbers and booleans are written as JSON numbers and booleans, all other values
+/// as JSON strings.
+///
+/// Only available with feature `kv`.
+#[cfg(feature = "kv")]
+pub fn default_format_json_kv(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    write!(
+        w,
+        "{} [{}] {}",
+        LevelField(record.level()),
+        record.module_path().unwrap_or("<unnamed>"),
+        record.args()
+    )?;
+    if record.key_values().count() == 0 {
+        return Ok(());
+    }
+    w.write_all(b" {")?;
+    let mut visitor = kv::JsonFields {
+        w,
+        key_prefix: "",
+        first: true,
+        result: Ok(()),
+    };
+    if record.key_values().visit(&mut visitor).is_err() {
+        visitor.result?;
+    }
+    w.write_all(b"}")
+}
+
+/// A logline-formatter that produces log lines in JSON format with the field names of the
+/// [Elastic Common Schema](https://www.elastic.co/guide/en/ecs/current/index.html), like
+/// <br>
+/// ```{"@timestamp":"2016-01-13T15:25:01.640870+01:00","log.level":"info","log.logger":"foo::bar","message":"Task successfully read","labels":{"file":"conf.json","tasks":3}}```
+/// <br>
+/// i.e. like [`ndjson_nested_format()`](fn.ndjson_nested_format.html), but with the
+/// [`JsonFieldNames::ECS`](struct.JsonFieldNames.html#associatedconstant.ECS).
+///
+/// Only available with feature `kv`.
+#[cfg(feature = "kv")]
+pub fn json_format_ecs(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    json_format_with(w, now, record, &JsonFieldNames::ECS)
+}
+
+/// The fields of a log line in JSON format, see [`JsonFieldNames`](struct.JsonFieldNames.html).
+///
+/// Only available with feature `kv`.
+#[cfg(feature = "kv")]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum JsonField {
+    /// The timestamp, in RFC 3339 format with microseconds.
+    Timestamp,
+    /// The level of the record, like `INFO`.
+    Level,
+    /// The level of the record in lowercase, like `info`, as expected by the
+    /// Elastic Common Schema.
+    LowercaseLevel,
+    /// The module path of the record.
+    Module,
+    /// The hostname; is only written with
+    /// [`Logger::with_hostname()`](struct.Logger.html#method.with_hostname).
+    Host,
+    /// The message of the record.
+    Message,
+    /// The key-value pairs of the record, as nested object.
+    KeyValues,
+}
+
+/// Names and order of the fields of a log line in JSON format,
+/// for [`json_format_with()`](fn.json_format_with.html).
+///
+/// The fields are written in the given order; fields that are not listed are not written.
+///
+/// ## Example
+///
+/// ```rust
+/// use flexi_logger::{json_format_with, DeferredNow, JsonField, JsonFieldNames, Record};
+///
+/// const MY_FIELD_NAMES: JsonFieldNames = JsonFieldNames(&[
+///     (JsonField::Timestamp, "timestamp"),
+///     (JsonField::Level, "severity"),
+///     (JsonField::Message, "message"),
+/// ]);
+///
+/// fn my_json_format(
+///     w: &mut dyn std::io::Write,
+///     now: &mut DeferredNow,
+///     record: &Record,
+/// ) -> Result<(), std::io::Error> {
+///     json_format_with(w, now, record, &MY_FIELD_NAMES)
+/// }
+/// ```
+///
+/// Only available with feature `kv`.
+#[cfg(feature = "kv")]
+#[derive(Copy, Clone, Debug)]
+pub struct JsonFieldNames(pub &'static [(JsonField, &'static str)]);
+#[cfg(feature = "kv")]
+impl JsonFieldNames {
+    /// The field names of [`ndjson_nested_format()`](fn.ndjson_nested_format.html):
+    /// `ts`, `level`, `module`, `host`, `msg`, `fields`.
+    pub const NDJSON: JsonFieldNames = JsonFieldNames(&[
+        (JsonField::Timestamp, "ts"),
+        (JsonField::Level, "level"),
+        (JsonField::Module, "module"),
+        (JsonField::Host, "host"),
+        (JsonField::Message, "msg"),
+        (JsonField::KeyValues, "fields"),
+    ]);
+
+    /// The field names of the Elastic Common Schema, as used by
+    /// [`json_format_ecs()`](fn.json_format_ecs.html):
+    /// `@timestamp`, `log.level`, `log.logger`, `host.hostname`, `message`, `labels`.
+    pub const ECS: JsonFieldNames = JsonFieldNames(&[
+        (JsonField::Timestamp, "@timestamp"),
+        (JsonField::LowercaseLevel, "log.level"),
+        (JsonField::Module, "log.logger"),
+        (JsonField::Host, "host.hostname"),
+        (JsonField::Message, "message"),
+        (JsonField::KeyValues, "labels"),
+    ]);
+}
+#[cfg(feature = "kv")]
+impl Default for JsonFieldNames {
+    fn default() -> JsonFieldNames {
+        JsonFieldNames::NDJSON
+    }
+}
+
+/// Writes the record as JSON object with the given field names.
+///
+/// Can be used to implement a format function with other field names than
+/// [`ndjson_nested_format()`](fn.ndjson_nested_format.html), see
+/// [`JsonFieldNames`](struct.JsonFieldNames.html).
+///
+/// Only available with feature `kv`.
+#[cfg(feature = "kv")]
+pub fn json_format_with(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+    field_names: &JsonFieldNames,
+) -> Result<(), std::io::Error> {
+    let mut first = true;
+    for (field, name) in field_names.0 {
+        match field {
+            JsonField::Timestamp => {
+                write_json_key(w, &mut first, name)?;
+                write!(w, "\"{}\"", now.format("%Y-%m-%dT%H:%M:%S%.6f%:z"))?;
+            }
+            JsonField::Level => {
+                write_json_key(w, &mut first, name)?;
+                write_json_str(w, record.level().as_str())?;
+            }
+            JsonField::LowercaseLevel => {
+                write_json_key(w, &mut first, name)?;
+                write_json_str(w, lowercase_level(record.level()))?;
+            }
+            JsonField::Module => {
+                write_json_key(w, &mut first, name)?;
+                write_json_str(w, record.module_path().unwrap_or("<unnamed>"))?;
+            }
+            #[cfg(feature = "hostname")]
+            JsonField::Host => {
+                if let Some(host) = hostname() {
+                    write_json_key(w, &mut first, name)?;
+                    write_json_str(w, host)?;
+                }
+            }
+            #[cfg(not(feature = "hostname"))]
+            JsonField::Host => {}
+            JsonField::Message => {
+                write_json_key(w, &mut first, name)?;
+                match record.args().as_str() {
+                    Some(message) => write_json_str(w, message)?,
+                    None => write_json_display(w, record.args())?,
+                }
+            }
+            JsonField::KeyValues => {
+                write_json_key(w, &mut first, name)?;
+                w.write_all(b"{")?;
+                let mut visitor = kv::JsonFields {
+                    w,
+                    key_prefix: "",
+                    first: true,
+                    result: Ok(()),
+                };
+                if record.key_values().visit(&mut visitor).is_err() {
+                    visitor.result?;
+                }
+                w.write_all(b"}")?;
+            }
+        }
+    }
+    w.write_all(if first { b"{}" } else { b"}" })
+}
+
+#[cfg(feature = "kv")]
+fn lowercase_level(level: log::Level) -> &'static str {
+    match level {
+        log::Level::Error => "error",
+        log::Level::Warn => "warn",
+        log::Level::Info => "info",
+        log::Level::Debug => "debug",
+        log::Level::Trace => "trace",
+    }
+}
+
+/// A logline-formatter that produces log lines in
+/// [GELF](https://go2docs.graylog.org/current/getting_in_log_data/gelf.html) format
+/// for the ingestion by Graylog, like
+/// <br>
+/// ```{"version":"1.1","host":"myhost","short_message":"Task successfully read","timestamp":1452695101.640,"level":6,"_module":"foo::bar","_file":"conf.json","_tasks":3}```
+/// <br>
+/// i.e. with the syslog severity as `level`, the unix timestamp in seconds with milliseconds,
+/// and the key-value pairs of the record as additional fields, whose names are prefixed with `_`.
+///
+/// Only available with feature `gelf`.
+#[cfg(feature = "gelf")]
+pub fn gelf_format(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    w.write_all(b"{\"version\":\"1.1\",\"host\":")?;
+    write_json_str(w, cached_hostname())?;
+    w.write_all(b",\"short_message\":")?;
+    write_json_str(w, &record.args().to_string())?;
+    let timestamp = now.now();
+    write!(
+        w,
+        ",\"timestamp\":{}.{:03},\"level\":{},\"_module\":",
+        timestamp.timestamp(),
+        // during a leap second, the milliseconds can exceed 999
+        timestamp.timestamp_subsec_millis() % 1000,
+        syslog_severity(record.level()),
+    )?;
+    write_json_str(w, record.module_path().unwrap_or("<unnamed>"))?;
+    let mut visitor = kv::JsonFields {
+        w,
+        key_prefix: "_",
+        first: false,
+        result: Ok(()),
+    };
+    if record.key_values().visit(&mut visitor).is_err() {
+        visitor.result?;
+    }
+    w.write_all(b"}")
+}
+
+#[cfg(feature = "gelf")]
+fn syslog_severity(level: log::Level) -> u8 {
+    match level {
+        log::Level::Error => 3,
+        log::Level::Warn => 4,
+        log::Level::Info => 6,
+        log::Level::Debug | log::Level::Trace => 7,
+    }
+}
+
+/// A logline-formatter for access logs that produces log lines in the
+/// [Common Log Format](https://httpd.apache.org/docs/current/logs.html#common), like
+/// <br>
+/// ```127.0.0.1 - - [13/Jan/2016:15:25:01 +0100] "GET /index.html" 200 2326```
+/// <br>
+/// from the key-value pairs `remote_addr`, `method`, `path`, `status`, and `bytes`
+/// of the record, e.g. of
+/// `info!(remote_addr = "127.0.0.1", method = "GET", path = "/index.html", status = 200, bytes = 2326; "")`.
+///
+/// Absent fields are written as `-`, the message of the record is not written.
+///
+/// Only available with feature `kv`.
+#[cfg(feature = "kv")]
+pub fn clf_format(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    use log::kv::Key;
+    let key_values = record.key_values();
+    let field = |key: &str| key_values.get(Key::from_str(key)).map(|v| v.to_string());
+    let request = match (field("method"), field("path")) {
+        (Some(method), Some(path)) => format!("{} {}", method, path),
+        (Some(s), None) | (None, Some(s)) => s,
+        (None, None) => "-".to_string(),
+    };
+    write!(
+        w,
+        "{} - - [{}] \"{}\" {} {}",
+        field("remote_addr").unwrap_or_else(|| "-".to_string()),
+        now.format("%d/%b/%Y:%H:%M:%S %z"),
+        request.replace('"', "\\\""),
+        field("status").unwrap_or_else(|| "-".to_string()),
+        field("bytes").unwrap_or_else(|| "-".to_string()),
+    )
+}
+
+// Appends the key-value pairs of the record, if it has some, like ` {file=conf.json, tasks=3}`.
+#[cfg(feature = "kv")]
+fn write_key_values(w: &mut dyn std::io::Write, record: &Record) -> Result<(), std::io::Error> {
+    let mut visitor = kv::PlainFields {
+        w,
+        first: true,
+        result: Ok(()),
+    };
+    let visited = record.key_values().visit(&mut visitor);
+    let (first, result) = (visitor.first, visitor.result);
+    if visited.is_err() {
+        result?;
+    }
+    if first {
+        Ok(())
+    } else {
+        w.write_all(b"}")
+    }
+}
+
+#[cfg(not(feature = "kv"))]
+#[inline]
+fn write_key_values(_w: &mut dyn std::io::Write, _record: &Record) -> Result<(), std::io::Error> {
+    Ok(())
+}
+
+// Writes the opening brace or the separating comma, and the key.
+#[cfg(feature = "kv")]
+fn write_json_key(
+    w: &mut dyn std::io::Write,
+    first: &mut bool,
+    key: &str,
+) -> Result<(), std::io::Error> {
+    w.write_all(if *first { b"{" } else { b"," })?;
+    *first = false;
+    write_json_str(w, key)?;
+    w.write_all(b":")
+}
+
+#[cfg(feature = "kv")]
+fn write_json_str(w: &mut dyn std::io::Write, s: &str) -> Result<(), std::io::Error> {
+    w.write_all(b"\"")?;
+    write_json_chars(w, s)?;
+    w.write_all(b"\"")
+}
+
+// Writes the value as JSON string, without formatting it into a String first.
+#[cfg(feature = "kv")]
+fn write_json_display(
+    w: &mut dyn std::io::Write,
+    value: &dyn std::fmt::Display,
+) -> Result<(), std::io::Error> {
+    struct JsonChars<'a> {
+        w: &'a mut dyn std::io::Write,
+        // fmt::Write cannot transport io errors, so we keep them here
+        result: Result<(), std::io::Error>,
+    }
+    impl std::fmt::Write for JsonChars<'_> {
+        fn write_str(&mut self, s: &str) -> std::fmt::Result {
+            write_json_chars(self.w, s).map_err(|e| {
+                self.result = Err(e);
+                std::fmt::Error
+            })
+        }
+    }
+
+    w.write_all(b"\"")?;
+    let mut json_chars = JsonChars {
+        w: &mut *w,
+        result: Ok(()),
+    };
+    if std::fmt::write(&mut json_chars, format_args!("{}", value)).is_err() {
+        json_chars.result?;
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "formatting the value failed",
+        ));
+    }
+    w.write_all(b"\"")
+}
+
+#[cfg(feature = "kv")]
+fn write_json_chars(w: &mut dyn std::io::Write, s: &str) -> Result<(), std::io::Error> {
+    for c in s.chars() {
+        match c {
+            '"' => w.write_all(b"\\\"")?,
+            '\\' => w.write_all(b"\\\\")?,
+            '\n' => w.write_all(b"\\n")?,
+            '\r' => w.write_all(b"\\r")?,
+            '\t' => w.write_all(b"\\t")?,
+            c if c.is_control() => write!(w, "\\u{:04x}", c as u32)?,
+            c => write!(w, "{}", c)?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "kv")]
+mod kv {
+    use super::write_json_str;
+    use log::kv::{Error, Key, Value, VisitSource, VisitValue};
+
+    // Writes the key-value pairs of a record as `key=value`, separated by commas,
+    // and opens the enclosing braces.
+    pub(super) struct PlainFields<'a> {
+        pub w: &'a mut dyn std::io::Write,
+        pub first: bool,
+        // the kv API cannot transport io errors, so we keep them here
+        pub result: Result<(), std::io::Error>,
+    }
+
+    impl<'a, 'kvs> VisitSource<'kvs> for PlainFields<'a> {
+        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+            let separator = if self.first { " {" } else { ", " };
+            self.first = false;
+            write!(self.w, "{}{}={}", separator, key, value).map_err(|e| {
+                self.result = Err(e);
+                Error::msg("writing failed")
+            })
+        }
+    }
+
+    // Writes the key-value pairs of a record as members of a JSON object.
+    pub(super) struct JsonFields<'a> {
+        pub w: &'a mut dyn std::io::Write,
+        // is prepended to the keys
+        pub key_prefix: &'static str,
+        pub first: bool,
+        // the kv API cannot transport io errors, so we keep them here
+        pub result: Result<(), std::io::Error>,
+    }
+    impl<'a> JsonFields<'a> {
+        fn track(&mut self, result: Result<(), std::io::Error>) -> Result<(), Error> {
+            result.map_err(|e| {
+                self.result = Err(e);
+                Error::msg("writing failed")
+            })
+        }
+    }
+
+    impl<'a, 'kvs> VisitSource<'kvs> for JsonFields<'a> {
+        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+            let result = if self.first {
+                Ok(())
+            } else {
+                self.w.write_all(b",")
+            };
+            self.first = false;
+            self.track(result)?;
+            let result = if self.key_prefix.is_empty() {
+                write_json_str(self.w, key.as_str())
+            } else {
+                write_json_str(self.w, &[self.key_prefix, key.as_str()].concat())
+            };
+            self.track(result)?;
+            let result = self.w.write_all(b":");
+            self.track(result)?;
+            value.visit(self)
+        }
+    }
+
+    impl<'a, 'v> VisitValue<'v> for JsonFields<'a> {
+        fn visit_any(&mut self, value: Value) -> Result<(), Error> {
+            let result = write_json_str(self.w, &value.to_string());
+            self.track(result)
+        }
+
+        fn visit_null(&mut self) -> Result<(), Error> {
+            let result = self.w.write_all(b"null");
+            self.track(result)
+        }
+
+        fn visit_u64(&mut self, value: u64) -> Result<(), Error> {
+            let result = write!(self.w, "{}", value);
+            self.track(result)
+        }
+
+        fn visit_i64(&mut self, value: i64) -> Result<(), Error> {
+            let result = write!(self.w, "{}", value);
+            self.track(result)
+        }
+
+        fn visit_u128(&mut self, value: u128) -> Result<(), Error> {
+            let result = write!(self.w, "{}", value);
+            self.track(result)
+        }
+
+        fn visit_i128(&mut self, value: i128) -> Result<(), Error> {
+            let result = write!(self.w, "{}", value);
+            self.track(result)
+        }
+
+        fn visit_f64(&mut self, value: f64) -> Result<(), Error> {
+            // JSON has no representation for NaN and infinity
+            let result = if value.is_finite() {
+                write!(self.w, "{}", value)
+            } else {
+                self.w.write_all(b"null")
+            };
+            self.track(result)
+        }
+
+        fn visit_bool(&mut self, value: bool) -> Result<(), Error> {
+            let result = write!(self.w, "{}", value);
+            self.track(result)
+        }
+
+        fn visit_str(&mut self, value: &str) -> Result<(), Error> {
+            let result = write_json_str(self.w, value);
+            self.track(result)
+        }
+    }
+}
+
+thread_local! {
+    // Identifies the output for which the current thread formats a record,
+    // for the formats that depend on the previous line of the output,
+    // and tells if the output is stdout or stderr, for the formats that depend on the terminal.
+    static CURRENT_OUTPUT: std::cell::Cell<(usize, Option<ConsoleStream>)> =
+        const { std::cell::Cell::new((0, None)) };
+}
+
+// Is called by the writers before they format a record.
+pub(crate) fn set_current_output(output: usize, o_console: Option<ConsoleStream>) {
+    CURRENT_OUTPUT.with(|current| current.set((output, o_console)));
+}
+
+// The console for which the current thread formats a record, if it formats for one.
+#[cfg(feature = "terminal_size")]
+pub(crate) fn current_console() -> Option<ConsoleStream> {
+    CURRENT_OUTPUT.with(std::cell::Cell::get).1
+}
+
+// Whether the provided formats pad the level, see Logger::pad_levels().
+static PAD_LEVELS: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn set_pad_levels(pad_levels: bool) {
+    PAD_LEVELS.store(pad_levels, Ordering::Relaxed);
+}
+
+// Whether the hostname is provided to the format functions, see Logger::with_hostname().
+#[cfg(feature = "hostname")]
+static WITH_HOSTNAME: AtomicBool = AtomicBool::new(false);
+
+#[cfg(feature = "hostname")]
+pub(crate) fn set_with_hostname(with_hostname: bool) {
+    if with_hostname {
+        // the hostname is determined only once, at initialization
+        cached_hostname();
+    }
+    WITH_HOSTNAME.store(with_hostname, Ordering::Relaxed);
+}
+
+#[cfg(feature = "hostname")]
+fn cached_hostname() -> &'static str {
+    static HOST: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+    HOST.get_or_init(|| hostname::get_hostname().unwrap_or_else(|| "<unknown_hostname>".to_owned()))
+}
+
+/// Returns the hostname of the machine, if it is switched on with
+/// [`Logger::with_hostname()`](struct.Logger.html#method.with_hostname).
+///
+/// This allows own format functions to render the hostname.
+///
+/// Only available with feature `hostname`.
+#[cfg(feature = "hostname")]
+pub fn hostname() -> Option<&'static str> {
+    if WITH_HOSTNAME.load(Ordering::Relaxed) {
+        Some(cached_hostname())
+    } else {
+        None
+    }
+}
+
+thread_local! {
+    // The sequence number of the record that is currently written by this thread,
+    // see Logger::sequence_numbers().
+    static SEQUENCE_NUMBER: std::cell::Cell<Option<u64>> = const { std::cell::Cell::new(None) };
+}
+
+pub(crate) fn set_sequence_number(number: u64) {
+    SEQUENCE_NUMBER.with(|current| current.set(Some(number)));
+}
+
+/// Returns the sequence number of the record that is currently being formatted,
+/// if sequence numbers are switched on with
+/// [`Logger::sequence_numbers()`](struct.Logger.html#method.sequence_numbers).
+///
+/// This allows own format functions to render the sequence number.
+pub fn sequence_number() -> Option<u64> {
+    SEQUENCE_NUMBER.with(std::cell::Cell::get)
+}
+
+// Displays the level, padded to the width of the longest level name if PAD_LEVELS is set.
+struct LevelField(log::Level);
+impl std::fmt::Display for LevelField {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if PAD_LEVELS.load(Ordering::Relaxed) {
+            write!(f, "{:<5}", self.0)
+        } else {
+            write!(f, "{}", self.0)
+        }
+    }
+}
+
+// The highlighting rules, see Logger::highlight(); they are set when the logger is started
+// and read without locking when a record is formatted.
+#[cfg(feature = "colors")]
+static HIGHLIGHTS: ArcSwapOption<Vec<(Regex, yansi::Style)>> = ArcSwapOption::const_empty();
+
+#[cfg(feature = "colors")]
+pub(crate) fn set_highlights(highlights: Vec<(Regex, yansi::Style)>) {
+    HIGHLIGHTS.store(if highlights.is_empty() {
+        None
+    } else {
+        Some(Arc::new(highlights))
+    });
+}
+
+// Styles the message with the first matching highlighting rule, or else according to the level.
+// The message is only formatted if highlighting rules exist.
+#[cfg(feature = "colors")]
+fn style_message<'a, 'b>(
+    level: log::Level,
+    args: &'a std::fmt::Arguments<'b>,
+) -> yansi::Paint<&'a std::fmt::Arguments<'b>> {
+    if let Some(ref highlights) = *HIGHLIGHTS.load() {
+        let text = args.to_string();
+        if let Some((_, style)) = highlights.iter().find(|(regex, _)| regex.is_match(&text)) {
+            return style.paint(args);
+        }
+    }
+    style(level, args)
+}
+
 /// Helper function that is used in the provided colored format functions.
 ///
 /// Only available with feature `colors`.
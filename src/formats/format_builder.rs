@@ -0,0 +1,374 @@
+use super::{level_char, write_key_values, LevelField};
+use crate::{DeferredNow, Format};
+use log::Record;
+use std::thread;
+
+/// Precision of the timestamp in formats that are built with
+/// [`FormatBuilder`](struct.FormatBuilder.html).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TimestampPrecision {
+    /// Whole seconds, like in `2016-01-13 15:25:01 +01:00`.
+    Seconds,
+    /// Milliseconds, like in `2016-01-13 15:25:01.640 +01:00`.
+    Millis,
+    /// Microseconds, like in `2016-01-13 15:25:01.640870 +01:00`.
+    #[default]
+    Micros,
+}
+impl TimestampPrecision {
+    fn strftime(self) -> &'static str {
+        match self {
+            TimestampPrecision::Seconds => "%Y-%m-%d %H:%M:%S %:z",
+            TimestampPrecision::Millis => "%Y-%m-%d %H:%M:%S%.3f %:z",
+            TimestampPrecision::Micros => "%Y-%m-%d %H:%M:%S%.6f %:z",
+        }
+    }
+}
+
+/// How the level is shown in formats that are built with
+/// [`FormatBuilder`](struct.FormatBuilder.html).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LevelStyle {
+    /// The level name, like `INFO`.
+    #[default]
+    Full,
+    /// A one-letter level indicator, like `I`.
+    Char,
+    /// The level is not shown.
+    Off,
+}
+
+/// Builder for format functions that are composed from a set of components.
+///
+/// The components are written in a fixed order, each of them only if it is switched on:
+///
+/// ```text
+/// [timestamp] P[pid] T[thread] LEVEL [target] [module_path] file:line: message {key=value}
+/// ```
+///
+/// Without any further configuration, `FormatBuilder::new().build()` produces the same
+/// log lines as [`default_format`](fn.default_format.html).
+///
+/// ## Example
+///
+/// ```rust
+/// use flexi_logger::{FormatBuilder, LevelStyle, Logger, TimestampPrecision};
+///
+/// let format = FormatBuilder::new()
+///     .timestamp(true)
+///     .timestamp_precision(TimestampPrecision::Millis)
+///     .level_style(LevelStyle::Char)
+///     .thread(true)
+///     .build();
+/// Logger::with_str("info").format(format);
+/// ```
+///
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FormatBuilder {
+    timestamp: bool,
+    timestamp_precision: TimestampPrecision,
+    pid: bool,
+    thread: bool,
+    level_style: LevelStyle,
+    target: bool,
+    module_path: bool,
+    // without trailing `::`
+    o_strip_prefix: Option<String>,
+    file: bool,
+    line: bool,
+    key_values: bool,
+}
+impl Default for FormatBuilder {
+    fn default() -> FormatBuilder {
+        FormatBuilder::new()
+    }
+}
+impl FormatBuilder {
+    /// Creates a `FormatBuilder` with level, module path, message,
+    /// and the key-value pairs of the record (with feature `kv`).
+    pub fn new() -> FormatBuilder {
+        FormatBuilder {
+            timestamp: false,
+            timestamp_precision: TimestampPrecision::default(),
+            pid: false,
+            thread: false,
+            level_style: LevelStyle::default(),
+            target: false,
+            module_path: true,
+            o_strip_prefix: None,
+            file: false,
+            line: false,
+            key_values: true,
+        }
+    }
+
+    /// Switches the timestamp on or off; by default, it is off.
+    pub fn timestamp(mut self, timestamp: bool) -> FormatBuilder {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Sets the precision of the timestamp; the default is `TimestampPrecision::Micros`.
+    pub fn timestamp_precision(mut self, precision: TimestampPrecision) -> FormatBuilder {
+        self.timestamp_precision = precision;
+        self
+    }
+
+    /// Switches the process id on or off; by default, it is off.
+    pub fn pid(mut self, pid: bool) -> FormatBuilder {
+        self.pid = pid;
+        self
+    }
+
+    /// Switches the name of the current thread on or off; by default, it is off.
+    pub fn thread(mut self, thread: bool) -> FormatBuilder {
+        self.thread = thread;
+        self
+    }
+
+    /// Sets how the level is shown; the default is `LevelStyle::Full`.
+    pub fn level_style(mut self, level_style: LevelStyle) -> FormatBuilder {
+        self.level_style = level_style;
+        self
+    }
+
+    /// Switches the target of the record, like `[metrics]`, on or off;
+    /// by default, it is off.
+    pub fn target(mut self, target: bool) -> FormatBuilder {
+        self.target = target;
+        self
+    }
+
+    /// Switches the module path of the record, like `[foo::bar]`, on or off;
+    /// by default, it is on.
+    pub fn module_path(mut self, module_path: bool) -> FormatBuilder {
+        self.module_path = module_path;
+        self
+    }
+
+    /// Makes the format show the module path or the target of the record without the given
+    /// leading prefix, e.g. the name of the crate, so that `my_service::http::handler`
+    /// is shown as `http::handler`.
+    ///
+    /// Only whole path segments are stripped, so `my_service_ext::db` stays unchanged
+    /// with prefix `my_service`; the crate root itself, `my_service`, is shown unchanged.
+    /// A trailing `::` of the prefix is ignored.
+    pub fn strip_prefix<S: Into<String>>(mut self, prefix: S) -> FormatBuilder {
+        let prefix = prefix.into();
+        self.o_strip_prefix = Some(prefix.trim_end_matches("::").to_string());
+        self
+    }
+
+    /// Switches the source file on or off; by default, it is off.
+    pub fn file(mut self, file: bool) -> FormatBuilder {
+        self.file = file;
+        self
+    }
+
+    /// Switches the source line on or off; by default, it is off.
+    pub fn line(mut self, line: bool) -> FormatBuilder {
+        self.line = line;
+        self
+    }
+
+    /// Switches the key-value pairs of the record on or off; by default, they are on.
+    ///
+    /// Only available with feature `kv`.
+    #[cfg(feature = "kv")]
+    pub fn key_values(mut self, key_values: bool) -> FormatBuilder {
+        self.key_values = key_values;
+        self
+    }
+
+    /// Produces the format.
+    pub fn build(self) -> Format {
+        Format::from(
+            move |w: &mut dyn std::io::Write, now: &mut DeferredNow, record: &Record| {
+                self.write(w, now, record)
+            },
+        )
+    }
+
+    fn write(
+        &self,
+        w: &mut dyn std::io::Write,
+        now: &mut DeferredNow,
+        record: &Record,
+    ) -> Result<(), std::io::Error> {
+        if self.timestamp {
+            write!(w, "[{}] ", now.format(self.timestamp_precision.strftime()))?;
+        }
+        if self.pid {
+            write!(w, "P[{}] ", std::process::id())?;
+        }
+        if self.thread {
+            write!(w, "T[{}] ", thread::current().name().unwrap_or("<unnamed>"))?;
+        }
+        match self.level_style {
+            LevelStyle::Full => write!(w, "{} ", LevelField(record.level()))?,
+            LevelStyle::Char => write!(w, "{} ", level_char(record.level()))?,
+            LevelStyle::Off => {}
+        }
+        if self.target {
+            write!(w, "[{}] ", self.stripped(record.target()))?;
+        }
+        if self.module_path {
+            let module_path = record.module_path().unwrap_or("<unnamed>");
+            write!(w, "[{}] ", self.stripped(module_path))?;
+        }
+        match (self.file, self.line) {
+            (true, true) => write!(
+                w,
+                "{}:{}: ",
+                record.file().unwrap_or("<unnamed>"),
+                record.line().unwrap_or(0)
+            )?,
+            (true, false) => write!(w, "{}: ", record.file().unwrap_or("<unnamed>"))?,
+            (false, true) => write!(w, "line {}: ", record.line().unwrap_or(0))?,
+            (false, false) => {}
+        }
+        write!(w, "{}", record.args())?;
+        if self.key_values {
+            write_key_values(w, record)?;
+        }
+        Ok(())
+    }
+
+    // Removes the prefix of strip_prefix(), if the path starts with it.
+    fn stripped<'a>(&self, path: &'a str) -> &'a str {
+        self.o_strip_prefix
+            .as_ref()
+            .and_then(|prefix| path.strip_prefix(prefix.as_str()))
+            .and_then(|rest| rest.strip_prefix("::"))
+            .unwrap_or(path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FormatBuilder, LevelStyle, TimestampPrecision};
+    use crate::{default_format, DeferredNow, Format};
+
+    fn format(format: Format, record: &log::Record) -> String {
+        let mut buf = Vec::<u8>::new();
+        format
+            .write(&mut buf, &mut DeferredNow::new(), record)
+            .unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    fn check(builder: FormatBuilder, expected: &str) {
+        let record = log::Record::builder()
+            .args(format_args!("Task successfully read"))
+            .level(log::Level::Info)
+            .target("metrics")
+            .module_path(Some("foo::bar"))
+            .file(Some("src/foo/bar.rs"))
+            .line(Some(26))
+            .build();
+        assert_eq!(format(builder.build(), &record), expected);
+    }
+
+    #[test]
+    fn test_format_builder() {
+        check(
+            FormatBuilder::new(),
+            "INFO [foo::bar] Task successfully read",
+        );
+        check(
+            FormatBuilder::new()
+                .level_style(LevelStyle::Char)
+                .target(true)
+                .module_path(false),
+            "I [metrics] Task successfully read",
+        );
+        check(
+            FormatBuilder::new().target(true),
+            "INFO [metrics] [foo::bar] Task successfully read",
+        );
+        check(
+            FormatBuilder::new()
+                .level_style(LevelStyle::Off)
+                .module_path(false)
+                .file(true)
+                .line(true),
+            "src/foo/bar.rs:26: Task successfully read",
+        );
+        check(
+            FormatBuilder::new().file(true),
+            "INFO [foo::bar] src/foo/bar.rs: Task successfully read",
+        );
+        check(
+            FormatBuilder::new().module_path(false).line(true),
+            "INFO line 26: Task successfully read",
+        );
+        check(
+            FormatBuilder::new().strip_prefix("foo"),
+            "INFO [bar] Task successfully read",
+        );
+        check(
+            FormatBuilder::new().strip_prefix("fo"),
+            "INFO [foo::bar] Task successfully read",
+        );
+        check(
+            FormatBuilder::new()
+                .target(true)
+                .module_path(false)
+                .strip_prefix("foo::"),
+            "INFO [metrics] Task successfully read",
+        );
+        check(
+            FormatBuilder::new().module_path(false).pid(true),
+            &format!("P[{}] INFO Task successfully read", std::process::id()),
+        );
+    }
+
+    #[test]
+    fn test_format_builder_default() {
+        let record = log::Record::builder()
+            .args(format_args!("Task successfully read"))
+            .level(log::Level::Info)
+            .module_path(Some("foo::bar"))
+            .build();
+        let mut buf = Vec::<u8>::new();
+        default_format(&mut buf, &mut DeferredNow::new(), &record).unwrap();
+        assert_eq!(
+            format(FormatBuilder::new().build(), &record),
+            String::from_utf8(buf).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_format_builder_timestamp() {
+        let record = log::Record::builder()
+            .args(format_args!("Task successfully read"))
+            .level(log::Level::Warn)
+            .build();
+        let line = format(
+            FormatBuilder::new()
+                .timestamp(true)
+                .timestamp_precision(TimestampPrecision::Millis)
+                .level_style(LevelStyle::Char)
+                .module_path(false)
+                .build(),
+            &record,
+        );
+        // like "[2016-01-13 15:25:01.640 +01:00] W Task successfully read"
+        assert_eq!(line.len(), 57, "unexpected line {}", line);
+        assert!(line.starts_with('['));
+        assert_eq!(&line[20..21], ".");
+        assert!(line.ends_with("] W Task successfully read"));
+
+        let line = format(
+            FormatBuilder::new()
+                .timestamp(true)
+                .timestamp_precision(TimestampPrecision::Seconds)
+                .thread(true)
+                .build(),
+            &record,
+        );
+        assert!(line.contains(" +") || line.contains(" -"));
+        assert!(line.contains("] T["));
+        assert!(line.ends_with("] WARN [<unnamed>] Task successfully read"));
+    }
+}
@@ -0,0 +1,152 @@
+use super::{current_console, write_key_values, LevelField};
+use crate::primary_writer::ConsoleStream;
+use crate::DeferredNow;
+use log::Record;
+use std::io::Write;
+
+// The message is not wrapped if fewer columns than this remain for it.
+const MIN_COLUMNS: usize = 20;
+
+/// A logline-formatter for the output to a terminal that produces log lines like
+/// [`default_format()`](fn.default_format.html), but wraps long messages at the width of
+/// the terminal, and indents the continuation lines to the start of the message, like
+/// <br>
+/// ```INFO [my_prog::some_submodule] Task successfully read from conf.json, continuing with```
+/// <br>
+/// ```                               the next task```
+///
+/// The width is taken from the terminal that the output, stderr or stdout, is attached to,
+/// so use this format with
+/// [`Logger::format_for_stderr()`](struct.Logger.html#method.format_for_stderr).
+/// The message is not wrapped in the output to files and other writers,
+/// or if the output is not a terminal, or its width is unknown.
+///
+/// Only available with feature `terminal_size`.
+pub fn terminal_wrapped_format(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    let prefix = format!(
+        "{} [{}] ",
+        LevelField(record.level()),
+        record.module_path().unwrap_or("<unnamed>")
+    );
+    let mut message = Vec::<u8>::new();
+    write!(message, "{}", record.args())?;
+    write_key_values(&mut message, record)?;
+    let message = String::from_utf8_lossy(&message);
+
+    w.write_all(prefix.as_bytes())?;
+    match terminal_width() {
+        Some(width) => w.write_all(wrap(&message, prefix.chars().count(), width).as_bytes()),
+        None => w.write_all(message.as_bytes()),
+    }
+}
+
+// The width of the terminal, or None if the output does not go to a terminal.
+fn terminal_width() -> Option<usize> {
+    let console = current_console()?;
+    #[cfg(unix)]
+    let o_size = {
+        use std::os::unix::io::AsRawFd;
+        let fd = match console {
+            ConsoleStream::StdOut => std::io::stdout().as_raw_fd(),
+            ConsoleStream::StdErr => std::io::stderr().as_raw_fd(),
+        };
+        terminal_size::terminal_size_using_fd(fd)
+    };
+    #[cfg(windows)]
+    let o_size = {
+        use std::os::windows::io::AsRawHandle;
+        let handle = match console {
+            ConsoleStream::StdOut => std::io::stdout().as_raw_handle(),
+            ConsoleStream::StdErr => std::io::stderr().as_raw_handle(),
+        };
+        terminal_size::terminal_size_using_handle(handle)
+    };
+    #[cfg(not(any(unix, windows)))]
+    let o_size = {
+        let _ = console;
+        terminal_size::terminal_size()
+    };
+    o_size.map(|(terminal_size::Width(width), _)| width as usize)
+}
+
+// Wraps the message so that no line exceeds the width, if the first line starts at column
+// `indent`; continuation lines are indented by `indent` spaces.
+// Lines are broken at spaces, words that are longer than a line are split.
+fn wrap(message: &str, indent: usize, width: usize) -> String {
+    if width < indent + MIN_COLUMNS {
+        return message.to_string();
+    }
+    let columns = width - indent;
+    let mut result = String::with_capacity(message.len());
+    for (line_idx, line) in message.split('\n').enumerate() {
+        if line_idx > 0 {
+            new_line(&mut result, indent);
+        }
+        let mut column = 0;
+        for (word_idx, mut word) in line.split(' ').enumerate() {
+            let mut word_len = word.chars().count();
+            if word_idx > 0 {
+                if column + 1 + word_len <= columns {
+                    result.push(' ');
+                    column += 1;
+                } else {
+                    new_line(&mut result, indent);
+                    column = 0;
+                }
+            }
+            while column + word_len > columns {
+                let (split, _) = word.char_indices().nth(columns - column).unwrap();
+                result.push_str(&word[..split]);
+                new_line(&mut result, indent);
+                word_len -= columns - column;
+                column = 0;
+                word = &word[split..];
+            }
+            result.push_str(word);
+            column += word_len;
+        }
+    }
+    result
+}
+
+fn new_line(result: &mut String, indent: usize) {
+    result.push('\n');
+    result.push_str(&" ".repeat(indent));
+}
+
+#[cfg(test)]
+mod test {
+    use super::{terminal_width, wrap};
+    use crate::formats::set_current_output;
+
+    #[test]
+    fn test_terminal_width_of_other_outputs() {
+        // files and other writers are never wrapped, even if stderr is a terminal
+        set_current_output(1, None);
+        assert_eq!(terminal_width(), None);
+    }
+
+    #[test]
+    fn test_wrap() {
+        // fits
+        assert_eq!(wrap("short message", 5, 40), "short message");
+        // too narrow for wrapping
+        assert_eq!(wrap("a b c d e f g h", 30, 40), "a b c d e f g h");
+        // wrapped at spaces, with indentation
+        assert_eq!(
+            wrap("the quick brown fox jumps over the lazy dog", 4, 24),
+            "the quick brown fox\n    jumps over the lazy\n    dog"
+        );
+        // explicit line breaks are indented, too
+        assert_eq!(wrap("first\nsecond", 2, 30), "first\n  second");
+        // long words are split
+        assert_eq!(
+            wrap("x abcdefghijklmnopqrstuvwxyz", 0, 20),
+            "x\nabcdefghijklmnopqrst\nuvwxyz"
+        );
+    }
+}
@@ -34,20 +34,35 @@ mod flexi_logger;
 mod formats;
 mod log_specification;
 mod logger;
+mod owned_record;
 mod primary_writer;
 mod reconfiguration_handle;
+mod spec_source;
+#[cfg(feature = "specfile")]
+mod specfile_output;
 
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod writers;
 
 /// Re-exports from log crate
 pub use log::{Level, LevelFilter, Record};
 
 pub use crate::deferred_now::DeferredNow;
-pub use crate::flexi_error::FlexiLoggerError;
+pub use crate::flexi_error::{FlexiLoggerError, SpecParseError};
 pub use crate::formats::*;
-pub use crate::log_specification::{LogSpecBuilder, LogSpecification, ModuleFilter};
-pub use crate::logger::{Age, Cleanup, Criterion, Duplicate, LogTarget, Logger, Naming};
-pub use crate::reconfiguration_handle::ReconfigurationHandle;
+pub use crate::log_specification::{
+    LogSpecBuilder, LogSpecification, ModuleFilter, TextFilterScope,
+};
+pub use crate::logger::{
+    Age, Cleanup, ConsoleBuffering, Criterion, Duplicate, DuplicateFormat, DuplicateTarget,
+    FormatErrorPolicy, LogTarget, Logger, Naming, RecursionPolicy, StartupInfo, WriteErrorPolicy,
+};
+pub use crate::owned_record::{MapRecordFunction, OwnedRecord};
+pub use crate::reconfiguration_handle::{LoggerGuard, ReconfigurationHandle};
+#[cfg(feature = "specfile")]
+pub use crate::spec_source::SpecFileSource;
+pub use crate::spec_source::{PollingSpecSource, SpecSource};
 
 /// Function type for Format functions.
 ///
@@ -1,12 +1,12 @@
-use crate::flexi_error::FlexiLoggerError;
+use crate::flexi_error::{FlexiLoggerError, SpecParseError};
 use crate::LevelFilter;
 
+use aho_corasick::AhoCorasick;
 use regex::Regex;
 #[cfg(feature = "specfile")]
 use serde_derive::Deserialize;
 #[cfg(feature = "specfile")]
 use std::collections::BTreeMap;
-use std::collections::HashMap;
 use std::env;
 #[cfg(feature = "specfile")]
 use std::io::{Read, Write};
@@ -41,6 +41,8 @@ use std::io::{Read, Write};
 ///   explicit log level assigment.
 ///   (You see that for modules named error, warn, info, debug or trace,
 ///   it is necessary to specify their loglevel explicitly).
+/// * Besides the level names, `all` can be used as alias for `trace`,
+///   and `none` as alias for `off`.
 /// * The module names are compared as Strings, with the side effect that a specified module filter
 ///   affects all modules whose name starts with this String.<br>
 ///   Example: ```"foo"``` affects e.g.
@@ -49,8 +51,24 @@ use std::io::{Read, Write};
 ///   * `foo::bar`
 ///   * `foobaz` (!)
 ///   * `foobaz::bar` (!)
+/// * The module filters are matched against the module path of the log records
+///   (or against their target, if they have no module path), so records with a custom target,
+///   like from `info!(target: "metrics", ...)`, are filtered like the other records
+///   of the module that writes them.
+///   Log specifications that are created with a [`LogSpecBuilder`](struct.LogSpecBuilder.html)
+///   can instead match the module filters against the target of the records
+///   (see [`LogSpecBuilder::match_target()`](struct.LogSpecBuilder.html#method.match_target)).
 ///
 /// The optional text filter is applied for all modules.
+/// Everything after the first `/` is taken verbatim as the regex of the text filter,
+/// so the regex can itself contain `/` (e.g. `"info/GET /api/v1"`).
+/// The text filter is by default matched against the message; log specifications
+/// that are created with a [`LogSpecBuilder`](struct.LogSpecBuilder.html) can match it
+/// against the module path instead (see
+/// [`LogSpecBuilder::text_filter_scope()`](struct.LogSpecBuilder.html#method.text_filter_scope)).
+/// Log specifications that are created with a [`LogSpecBuilder`](struct.LogSpecBuilder.html)
+/// can additionally have a set of literal filters
+/// (see [`LogSpecBuilder::literal_filters()`](struct.LogSpecBuilder.html#method.literal_filters)).
 ///
 /// Note that external module names are to be specified like in ```"extern crate ..."```, i.e.,
 /// for crates with a dash in their name this means: the dash is to be replaced with
@@ -58,7 +76,32 @@ use std::io::{Read, Write};
 #[derive(Clone, Debug, Default)]
 pub struct LogSpecification {
     module_filters: Vec<ModuleFilter>,
+    // the module filters are in the order of LogSpecBuilder::first_match_wins()
+    first_match_wins: bool,
     textfilter: Option<Regex>,
+    literal_filter: Option<AhoCorasick>,
+    match_target: bool,
+    text_filter_scope: TextFilterScope,
+}
+
+/// Defines against what the text filter of a [`LogSpecification`](struct.LogSpecification.html)
+/// is matched.
+///
+/// The literal filters are always matched against the message.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TextFilterScope {
+    /// The text filter is matched against the message of the records (default).
+    #[default]
+    Message,
+    /// The text filter is matched against the module path of the records,
+    /// or against their target, if they have no module path or if the log specification
+    /// matches the module filters against the target
+    /// (see [`LogSpecBuilder::match_target()`](struct.LogSpecBuilder.html#method.match_target)).
+    ///
+    /// This allows e.g. selecting the modules with a regex like `::http::`.
+    Module,
+    /// A record passes if the text filter matches either its message or its module path.
+    MessageOrModule,
 }
 
 /// Defines which loglevel filter to use for the specified module.
@@ -86,7 +129,20 @@ impl LogSpecification {
 
     pub(crate) fn update_from(&mut self, other: LogSpecification) {
         self.module_filters = other.module_filters;
+        self.first_match_wins = other.first_match_wins;
         self.textfilter = other.textfilter;
+        self.literal_filter = other.literal_filter;
+        self.match_target = other.match_target;
+        self.text_filter_scope = other.text_filter_scope;
+    }
+
+    // Returns the part of the record that the module filters are matched against.
+    pub(crate) fn filter_subject<'a>(&self, record: &'a log::Record) -> &'a str {
+        if self.match_target {
+            record.target()
+        } else {
+            record.module_path().unwrap_or_else(|| record.target())
+        }
     }
 
     pub(crate) fn max_level(&self) -> log::LevelFilter {
@@ -99,7 +155,11 @@ impl LogSpecification {
 
     /// Returns true if messages on the specified level from the writing module should be written
     pub fn enabled(&self, level: log::Level, writing_module: &str) -> bool {
-        // Search for the longest match, the vector is assumed to be pre-sorted.
+        // Use the first match; the vector is assumed to be pre-sorted
+        // (usually such that the longest match comes first, see LevelSort).
+        // A spec with only a default level has a single filter without module name,
+        // so the loop returns on its first iteration without a prefix comparison,
+        // and log::max_level() already gates the records before they get here.
         for module_filter in &self.module_filters {
             match module_filter.module_name {
                 Some(ref module_name) => {
@@ -113,6 +173,138 @@ impl LogSpecification {
         false
     }
 
+    // Returns true if the text of the log message passes the text filter
+    // and the literal filters; the message is only formatted if a filter is set.
+    pub(crate) fn text_matches(&self, args: &std::fmt::Arguments) -> bool {
+        if self.textfilter.is_none() && self.literal_filter.is_none() {
+            return true;
+        }
+        let text = args.to_string();
+        if let Some(ref textfilter) = self.textfilter {
+            if !textfilter.is_match(&text) {
+                return false;
+            }
+        }
+        if let Some(ref literal_filter) = self.literal_filter {
+            if !literal_filter.is_match(&text) {
+                return false;
+            }
+        }
+        true
+    }
+
+    // Returns true if the record passes the text filter, depending on its scope,
+    // and the literal filters.
+    pub(crate) fn record_text_matches(&self, record: &log::Record) -> bool {
+        let textfilter = match self.textfilter {
+            Some(ref textfilter) if self.text_filter_scope != TextFilterScope::Message => {
+                textfilter
+            }
+            _ => return self.text_matches(record.args()),
+        };
+        if textfilter.is_match(self.filter_subject(record)) {
+            match self.literal_filter {
+                Some(ref literal_filter) => literal_filter.is_match(&record.args().to_string()),
+                None => true,
+            }
+        } else {
+            self.text_filter_scope == TextFilterScope::MessageOrModule
+                && self.text_matches(record.args())
+        }
+    }
+
+    /// Combines this log specification with another one, which takes precedence.
+    ///
+    /// * The module filters of `overriding` replace the ones of this specification
+    ///   for the same module; this applies also to the default log level filter.
+    ///   Module filters that exist only in one of the specifications are kept.
+    ///   If this specification was built with
+    ///   [`LogSpecBuilder::first_match_wins()`](struct.LogSpecBuilder.html#method.first_match_wins),
+    ///   its order is kept: the replacing filters take the position of the replaced ones,
+    ///   and the additional filters of `overriding` are inserted before the default filter.
+    /// * The text filter and the literal filters of `overriding` replace the ones of this
+    ///   specification, if they are present.
+    ///
+    /// ```rust
+    /// use flexi_logger::LogSpecification;
+    /// use log::Level;
+    ///
+    /// let base = LogSpecification::parse("info, hyper=warn").unwrap();
+    /// let overriding = LogSpecification::parse("hyper=debug, karl=trace").unwrap();
+    /// let spec = base.merge(&overriding);
+    ///
+    /// assert!(spec.enabled(Level::Info, "egon"));
+    /// assert!(spec.enabled(Level::Debug, "hyper"));
+    /// assert!(spec.enabled(Level::Trace, "karl"));
+    /// ```
+    pub fn merge(&self, overriding: &LogSpecification) -> LogSpecification {
+        let module_filters = if self.first_match_wins {
+            let (mut module_filters, default): (Vec<ModuleFilter>, Vec<ModuleFilter>) = self
+                .module_filters
+                .iter()
+                .map(|mf| {
+                    overriding
+                        .module_filters
+                        .iter()
+                        .find(|omf| omf.module_name == mf.module_name)
+                        .unwrap_or(mf)
+                        .clone()
+                })
+                .partition(|mf| mf.module_name.is_some());
+            let (additional, additional_default): (Vec<ModuleFilter>, Vec<ModuleFilter>) =
+                overriding
+                    .module_filters
+                    .iter()
+                    .filter(|omf| {
+                        !self
+                            .module_filters
+                            .iter()
+                            .any(|mf| mf.module_name == omf.module_name)
+                    })
+                    .cloned()
+                    .partition(|mf| mf.module_name.is_some());
+            module_filters.extend(additional);
+            module_filters.extend(default);
+            module_filters.extend(additional_default);
+            module_filters
+        } else {
+            let mut module_filters: Vec<ModuleFilter> = self
+                .module_filters
+                .iter()
+                .filter(|mf| {
+                    !overriding
+                        .module_filters
+                        .iter()
+                        .any(|omf| omf.module_name == mf.module_name)
+                })
+                .cloned()
+                .collect();
+            module_filters.extend(overriding.module_filters.iter().cloned());
+            module_filters.level_sort()
+        };
+
+        LogSpecification {
+            module_filters,
+            first_match_wins: self.first_match_wins,
+            textfilter: overriding
+                .textfilter
+                .as_ref()
+                .or(self.textfilter.as_ref())
+                .cloned(),
+            literal_filter: overriding
+                .literal_filter
+                .as_ref()
+                .or(self.literal_filter.as_ref())
+                .cloned(),
+            match_target: self.match_target || overriding.match_target,
+            text_filter_scope: if overriding.textfilter.is_some() {
+                overriding.text_filter_scope
+            } else {
+                self.text_filter_scope
+            },
+        }
+    }
+
     /// Returns a `LogSpecification` where all traces are switched off.
     pub fn off() -> LogSpecification {
         Default::default()
@@ -120,19 +312,13 @@ impl LogSpecification {
 
     /// Returns a log specification from a String.
     pub fn parse(spec: &str) -> Result<LogSpecification, FlexiLoggerError> {
-        let mut parse_errs = Vec::<String>::new();
+        let mut parse_errs = Vec::<SpecParseError>::new();
         let mut dirs = Vec::<ModuleFilter>::new();
 
-        let mut parts = spec.split('/');
+        // everything after the first '/' is the text filter, so it can contain '/' itself
+        let mut parts = spec.splitn(2, '/');
         let mods = parts.next();
         let filter = parts.next();
-        if parts.next().is_some() {
-            push_err(
-                format!("invalid log spec '{}' (too many '/'s), ignoring it", spec),
-                &mut parse_errs,
-            );
-            return parse_err(parse_errs, LogSpecification::off());
-        }
         if let Some(m) = mods {
             for s in m.split(',') {
                 let s = s.trim();
@@ -170,17 +356,17 @@ impl LogSpecification {
                         }
                         match parse_level_filter(part1.trim()) {
                             Ok(num) => (num, Some(part0.trim())),
-                            Err(e) => {
-                                push_err(e.to_string(), &mut parse_errs);
+                            Err(_) => {
+                                push_err(
+                                    SpecParseError::UnknownLevel(part1.to_string()),
+                                    &mut parse_errs,
+                                );
                                 continue;
                             }
                         }
                     }
                     _ => {
-                        push_err(
-                            format!("invalid part in log spec '{}', ignoring it", s),
-                            &mut parse_errs,
-                        );
+                        push_err(SpecParseError::InvalidPart(s.to_string()), &mut parse_errs);
                         continue;
                     }
                 };
@@ -194,14 +380,18 @@ impl LogSpecification {
         let textfilter = filter.and_then(|filter| match Regex::new(filter) {
             Ok(re) => Some(re),
             Err(e) => {
-                push_err(format!("invalid regex filter - {}", e), &mut parse_errs);
+                push_err(SpecParseError::BadRegex(e.to_string()), &mut parse_errs);
                 None
             }
         });
 
         let logspec = LogSpecification {
             module_filters: dirs.level_sort(),
+            first_match_wins: false,
             textfilter,
+            literal_filter: None,
+            match_target: false,
+            text_filter_scope: TextFilterScope::Message,
         };
 
         if parse_errs.is_empty() {
@@ -244,7 +434,7 @@ impl LogSpecification {
         }
 
         let logspec_ff: LogSpecFileFormat = toml::from_str(s)?;
-        let mut parse_errs = Vec::<String>::new();
+        let mut parse_errs = Vec::<SpecParseError>::new();
         let mut module_filters = Vec::<ModuleFilter>::new();
 
         if let Some(s) = logspec_ff.global_level {
@@ -266,7 +456,7 @@ impl LogSpecification {
             Some(s) => match Regex::new(&s) {
                 Ok(re) => Some(re),
                 Err(e) => {
-                    push_err(format!("invalid regex filter - {}", e), &mut parse_errs);
+                    push_err(SpecParseError::BadRegex(e.to_string()), &mut parse_errs);
                     None
                 }
             },
@@ -274,7 +464,11 @@ impl LogSpecification {
 
         let logspec = LogSpecification {
             module_filters: module_filters.level_sort(),
+            first_match_wins: false,
             textfilter,
+            literal_filter: None,
+            match_target: false,
+            text_filter_scope: TextFilterScope::Message,
         };
         if parse_errs.is_empty() {
             Ok(logspec)
@@ -285,9 +479,30 @@ impl LogSpecification {
 
     /// Serializes itself in toml format.
     ///
+    /// The global level, the text filter, and the module filters are written.
+    /// The order of a specification with
+    /// [`LogSpecBuilder::first_match_wins()`](struct.LogSpecBuilder.html#method.first_match_wins),
+    /// the literal filters, matching the target, and the text filter scope
+    /// cannot be expressed in toml and are lost.
+    ///
     /// This method is only avaible with feature `specfile`.
     #[cfg(feature = "specfile")]
     pub fn to_toml(&self, w: &mut dyn Write) -> Result<(), FlexiLoggerError> {
+        self.spec_to_toml(w)?;
+        w.write_all(
+            b"\n### Optional: define where and how the log lines are written\n\
+            ### (target = 'stderr', 'stdout', 'file', or 'devnull')\n",
+        )?;
+        w.write_all(b"#[output]\n")?;
+        w.write_all(b"#target = 'file'\n")?;
+        w.write_all(b"#directory = 'log_files'\n")?;
+        w.write_all(b"#format = 'detailed'\n")?;
+        Ok(())
+    }
+
+    // Serializes everything but the output section.
+    #[cfg(feature = "specfile")]
+    pub(crate) fn spec_to_toml(&self, w: &mut dyn Write) -> Result<(), FlexiLoggerError> {
         w.write_all(b"### Optional: Default log level\n")?;
         let last = self.module_filters.last();
         if last.is_some() && last.as_ref().unwrap().module_name.is_none() {
@@ -309,7 +524,14 @@ impl LogSpecification {
         w.write_all(
             b"\n### Optional: specify a regular expression to suppress all messages that don't match\n",
         )?;
-        w.write_all(b"#global_pattern = 'foo'\n")?;
+        match self.textfilter {
+            Some(ref textfilter) => writeln!(
+                w,
+                "global_pattern = {}",
+                toml::Value::String(textfilter.as_str().to_string())
+            )?,
+            None => w.write_all(b"#global_pattern = 'foo'\n")?,
+        }
 
         w.write_all(
             b"\n### Specific log levels per module are optionally defined in this section\n",
@@ -352,29 +574,62 @@ impl LogSpecification {
     pub fn text_filter(&self) -> &Option<Regex> {
         &(self.textfilter)
     }
-}
 
-fn push_err(s: String, parse_errs: &mut Vec<String>) {
-    println!("flexi_logger warning: {}", s);
-    parse_errs.push(s);
+    /// Describes the effective module filters, in the order in which they are evaluated
+    /// (the first filter whose name is a prefix of the module path decides),
+    /// as pairs of module name and level filter.
+    ///
+    /// The last pair, with the name `<default>`, applies to all other modules;
+    /// it is also present if the specification has no default level.
+    /// The text filter, if one is set, can be obtained as
+    /// `spec.text_filter().as_ref().map(Regex::as_str)`.
+    ///
+    /// ```rust
+    /// use flexi_logger::{LevelFilter, LogSpecification};
+    ///
+    /// let spec = LogSpecification::parse("info, hyper=warn, hyper::client=debug").unwrap();
+    /// assert_eq!(
+    ///     spec.describe(),
+    ///     vec![
+    ///         ("hyper::client".to_string(), LevelFilter::Debug),
+    ///         ("hyper".to_string(), LevelFilter::Warn),
+    ///         ("<default>".to_string(), LevelFilter::Info),
+    ///     ]
+    /// );
+    /// ```
+    pub fn describe(&self) -> Vec<(String, LevelFilter)> {
+        let mut description = Vec::with_capacity(self.module_filters.len() + 1);
+        for module_filter in &self.module_filters {
+            match module_filter.module_name {
+                Some(ref module_name) => {
+                    description.push((module_name.clone(), module_filter.level_filter));
+                }
+                None => {
+                    // filters after the default are never evaluated
+                    description.push(("<default>".to_string(), module_filter.level_filter));
+                    return description;
+                }
+            }
+        }
+        description.push(("<default>".to_string(), LevelFilter::Off));
+        description
+    }
 }
 
-fn parse_err(
-    errors: Vec<String>,
-    logspec: LogSpecification,
-) -> Result<LogSpecification, FlexiLoggerError> {
-    Err(FlexiLoggerError::Parse(errors, logspec))
+fn push_err(e: SpecParseError, parse_errs: &mut Vec<SpecParseError>) {
+    println!("flexi_logger warning: {}", e);
+    parse_errs.push(e);
 }
 
 // #[cfg(feature = "specfile")]
 fn parse_level_filter<S: AsRef<str>>(s: S) -> Result<LevelFilter, FlexiLoggerError> {
     match s.as_ref().to_lowercase().as_ref() {
-        "off" => Ok(LevelFilter::Off),
+        "off" | "none" => Ok(LevelFilter::Off),
         "error" => Ok(LevelFilter::Error),
         "warn" => Ok(LevelFilter::Warn),
         "info" => Ok(LevelFilter::Info),
         "debug" => Ok(LevelFilter::Debug),
-        "trace" => Ok(LevelFilter::Trace),
+        "trace" | "all" => Ok(LevelFilter::Trace),
         _ => Err(FlexiLoggerError::LevelFilter(format!(
             "unknown level filter: {}",
             s.as_ref()
@@ -382,16 +637,10 @@ fn parse_level_filter<S: AsRef<str>>(s: S) -> Result<LevelFilter, FlexiLoggerErr
     }
 }
 
-fn contains_dash_or_whitespace(s: &str, parse_errs: &mut Vec<String>) -> bool {
+fn contains_dash_or_whitespace(s: &str, parse_errs: &mut Vec<SpecParseError>) -> bool {
     let result = s.find('-').is_some() || s.find(' ').is_some() || s.find('\t').is_some();
     if result {
-        push_err(
-            format!(
-                "ignoring invalid part in log spec '{}' (contains a dash or whitespace)",
-                s
-            ),
-            parse_errs,
-        );
+        push_err(SpecParseError::InvalidModuleName(s.to_string()), parse_errs);
     }
     result
 }
@@ -432,33 +681,41 @@ fn contains_dash_or_whitespace(s: &str, parse_errs: &mut Vec<String>) -> bool {
 /// ```
 #[derive(Clone, Default)]
 pub struct LogSpecBuilder {
-    module_filters: HashMap<Option<String>, LevelFilter>,
+    // in declaration order
+    module_filters: Vec<ModuleFilter>,
+    first_match_wins: bool,
+    literal_filters: Vec<String>,
+    match_target: bool,
+    text_filter_scope: TextFilterScope,
 }
 
 impl LogSpecBuilder {
     /// Creates a LogSpecBuilder with all logging turned off.
     pub fn new() -> LogSpecBuilder {
-        let mut modfilmap = HashMap::new();
-        modfilmap.insert(None, LevelFilter::Off);
         LogSpecBuilder {
-            module_filters: modfilmap,
+            module_filters: vec![ModuleFilter {
+                module_name: None,
+                level_filter: LevelFilter::Off,
+            }],
+            first_match_wins: false,
+            literal_filters: Vec::new(),
+            match_target: false,
+            text_filter_scope: TextFilterScope::Message,
         }
     }
 
     /// Creates a LogSpecBuilder from given module filters.
     pub fn from_module_filters(module_filters: &[ModuleFilter]) -> LogSpecBuilder {
-        let mut modfilmap = HashMap::new();
+        let mut builder: LogSpecBuilder = Default::default();
         for mf in module_filters {
-            modfilmap.insert(mf.module_name.clone(), mf.level_filter);
-        }
-        LogSpecBuilder {
-            module_filters: modfilmap,
+            builder.insert(mf.module_name.clone(), mf.level_filter);
         }
+        builder
     }
 
     /// Adds a default log level filter, or updates the default log level filter.
     pub fn default(&mut self, lf: LevelFilter) -> &mut LogSpecBuilder {
-        self.module_filters.insert(None, lf);
+        self.insert(None, lf);
         self
     }
 
@@ -468,64 +725,156 @@ impl LogSpecBuilder {
         module_name: M,
         lf: LevelFilter,
     ) -> &mut LogSpecBuilder {
-        self.module_filters
-            .insert(Some(module_name.as_ref().to_owned()), lf);
+        self.insert(Some(module_name.as_ref().to_owned()), lf);
         self
     }
 
     /// Adds a log level filter, or updates the log level filter, for a module.
     pub fn remove<M: AsRef<str>>(&mut self, module_name: M) -> &mut LogSpecBuilder {
+        let module_name = Some(module_name.as_ref().to_owned());
         self.module_filters
-            .remove(&Some(module_name.as_ref().to_owned()));
+            .retain(|mf| mf.module_name != module_name);
+        self
+    }
+
+    /// Makes the resulting log specifications evaluate the module filters in the order
+    /// in which they were added to the builder, and use the first one that matches.
+    ///
+    /// By default, the module filter with the longest matching module name is used.
+    /// The default log level filter is in both cases only used if no module filter matches.
+    ///
+    /// Note that updating the log level filter of a module keeps its position.
+    ///
+    /// ```rust
+    /// use flexi_logger::LogSpecBuilder;
+    /// use log::{Level, LevelFilter};
+    ///
+    /// let mut builder = LogSpecBuilder::new();
+    /// builder
+    ///     .first_match_wins()
+    ///     .module("karl", LevelFilter::Debug)
+    ///     .module("karl::heinz", LevelFilter::Error);
+    /// let spec = builder.build();
+    ///
+    /// // "karl::heinz" is shadowed by "karl"
+    /// assert!(spec.enabled(Level::Debug, "karl::heinz"));
+    /// ```
+    pub fn first_match_wins(&mut self) -> &mut LogSpecBuilder {
+        self.first_match_wins = true;
+        self
+    }
+
+    /// Sets a list of literal substrings; only log lines that contain at least one of them
+    /// are written.
+    ///
+    /// All literals are matched together in a single pass over the log message, which is
+    /// much cheaper than a text filter regex with a large alternation, e.g. when you
+    /// look for any of many request ids.
+    /// If a text filter is set as well, a log line has to pass both filters.
+    /// An empty list removes the literal filters.
+    ///
+    /// ```rust
+    /// use flexi_logger::LogSpecBuilder;
+    /// use log::LevelFilter;
+    ///
+    /// let mut builder = LogSpecBuilder::new();
+    /// builder
+    ///     .default(LevelFilter::Info)
+    ///     .literal_filters(vec!["req-4711".to_string(), "req-4712".to_string()]);
+    /// let spec = builder.build();
+    /// ```
+    pub fn literal_filters(&mut self, literals: Vec<String>) -> &mut LogSpecBuilder {
+        self.literal_filters = literals;
+        self
+    }
+
+    /// Makes the resulting log specifications match the module filters against the target
+    /// of the log records, rather than against their module path.
+    ///
+    /// The target is by default the module path, too, but can be set explicitly,
+    /// like in `info!(target: "metrics", ...)`.
+    /// With this option, such records are filtered by their target, i.e., a module filter
+    /// `metrics` controls them, while the filter for the writing module has no effect on them.
+    pub fn match_target(&mut self) -> &mut LogSpecBuilder {
+        self.match_target = true;
+        self
+    }
+
+    /// Defines against what the text filter of the resulting log specifications is matched;
+    /// by default, it is matched against the message of the records.
+    pub fn text_filter_scope(&mut self, scope: TextFilterScope) -> &mut LogSpecBuilder {
+        self.text_filter_scope = scope;
         self
     }
 
     /// Creates a log specification without text filter.
     pub fn finalize(self) -> LogSpecification {
-        LogSpecification {
-            module_filters: self.module_filters.into_vec_module_filter(),
-            textfilter: None,
-        }
+        self.build_with_textfilter(None)
     }
 
     /// Creates a log specification with text filter.
     pub fn finalize_with_textfilter(self, tf: Regex) -> LogSpecification {
-        LogSpecification {
-            module_filters: self.module_filters.into_vec_module_filter(),
-            textfilter: Some(tf),
-        }
+        self.build_with_textfilter(Some(tf))
     }
 
     /// Creates a log specification without being consumed.
     pub fn build(&self) -> LogSpecification {
-        LogSpecification {
-            module_filters: self.module_filters.clone().into_vec_module_filter(),
-            textfilter: None,
-        }
+        self.build_with_textfilter(None)
     }
 
     /// Creates a log specification without being consumed, optionally with a text filter.
     pub fn build_with_textfilter(&self, tf: Option<Regex>) -> LogSpecification {
         LogSpecification {
-            module_filters: self.module_filters.clone().into_vec_module_filter(),
+            module_filters: self.ordered_module_filters(),
+            first_match_wins: self.first_match_wins,
             textfilter: tf,
+            literal_filter: self.literal_filter(),
+            match_target: self.match_target,
+            text_filter_scope: self.text_filter_scope,
         }
     }
-}
 
-trait IntoVecModuleFilter {
-    fn into_vec_module_filter(self) -> Vec<ModuleFilter>;
-}
-impl IntoVecModuleFilter for HashMap<Option<String>, LevelFilter> {
-    fn into_vec_module_filter(self) -> Vec<ModuleFilter> {
-        let mf: Vec<ModuleFilter> = self
-            .into_iter()
-            .map(|(k, v)| ModuleFilter {
-                module_name: k,
-                level_filter: v,
-            })
-            .collect();
-        mf.level_sort()
+    fn literal_filter(&self) -> Option<AhoCorasick> {
+        if self.literal_filters.is_empty() {
+            return None;
+        }
+        match AhoCorasick::new(&self.literal_filters) {
+            Ok(literal_filter) => Some(literal_filter),
+            Err(e) => {
+                eprintln!("[flexi_logger] ignoring literal filters: {}", e);
+                None
+            }
+        }
+    }
+
+    fn insert(&mut self, module_name: Option<String>, lf: LevelFilter) {
+        match self
+            .module_filters
+            .iter_mut()
+            .find(|mf| mf.module_name == module_name)
+        {
+            Some(mf) => mf.level_filter = lf,
+            None => self.module_filters.push(ModuleFilter {
+                module_name,
+                level_filter: lf,
+            }),
+        }
+    }
+
+    // Brings the module filters into the order in which LogSpecification::enabled()
+    // has to evaluate them.
+    fn ordered_module_filters(&self) -> Vec<ModuleFilter> {
+        if self.first_match_wins {
+            let (mut module_filters, default): (Vec<ModuleFilter>, Vec<ModuleFilter>) = self
+                .module_filters
+                .iter()
+                .cloned()
+                .partition(|mf| mf.module_name.is_some());
+            module_filters.extend(default);
+            module_filters
+        } else {
+            self.module_filters.clone().level_sort()
+        }
     }
 }
 
@@ -575,6 +924,38 @@ mod tests {
         assert!(spec.text_filter().is_none());
     }
 
+    #[test]
+    fn describe() {
+        let spec = LogSpecification::parse("crate1=warn, crate1::mod1=error, crate2").unwrap();
+        let description = spec.describe();
+        assert_eq!(
+            description,
+            vec![
+                ("crate1::mod1".to_string(), LevelFilter::Error),
+                ("crate1".to_string(), LevelFilter::Warn),
+                ("crate2".to_string(), LevelFilter::max()),
+                ("<default>".to_string(), LevelFilter::Off),
+            ]
+        );
+        // the description matches the evaluation
+        for (module, level) in &[("crate1::mod1::a", Level::Warn), ("crate1::b", Level::Warn)] {
+            let first_match = description
+                .iter()
+                .find(|(name, _)| module.starts_with(name.as_str()))
+                .unwrap();
+            assert_eq!(spec.enabled(*level, module), *level <= first_match.1);
+        }
+
+        let spec = LogSpecification::parse("debug, crate1=info").unwrap();
+        assert_eq!(
+            spec.describe(),
+            vec![
+                ("crate1".to_string(), LevelFilter::Info),
+                ("<default>".to_string(), LevelFilter::Debug),
+            ]
+        );
+    }
+
     #[test]
     fn parse_logging_spec_invalid_crate() {
         // test parse_logging_spec with multiple = in specification
@@ -662,6 +1043,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_logging_spec_with_slash_in_filter() {
+        let spec = LogSpecification::parse("crate1=info/GET /api/v1").unwrap();
+        assert_eq!(spec.module_filters().len(), 1);
+        assert_eq!(spec.module_filters()[0].level_filter, LevelFilter::Info);
+        let filter = spec.text_filter().as_ref().unwrap();
+        assert_eq!(filter.as_str(), "GET /api/v1");
+        assert!(filter.is_match("GET /api/v1/users"));
+        assert!(!filter.is_match("GET /api/v2/users"));
+    }
+
     #[test]
     fn reuse_logspec_builder() {
         let mut builder = crate::LogSpecBuilder::new();
@@ -709,6 +1101,198 @@ mod tests {
         assert_eq!(spec2.module_filters()[1].level_filter, LevelFilter::Warn);
     }
 
+    #[test]
+    fn literal_filters() {
+        let mut builder = crate::LogSpecBuilder::new();
+        builder
+            .default(LevelFilter::Info)
+            .literal_filters(vec!["req-4711".to_string(), "req-0815".to_string()]);
+        let spec = builder.build();
+        assert!(spec.text_matches(&format_args!("handling req-0815 now")));
+        assert!(spec.text_matches(&format_args!("req-4711")));
+        assert!(!spec.text_matches(&format_args!("handling req-4712 now")));
+
+        let spec = builder.build_with_textfilter(Some(regex::Regex::new("^handling").unwrap()));
+        assert!(spec.text_matches(&format_args!("handling req-0815 now")));
+        assert!(!spec.text_matches(&format_args!("req-4711")));
+
+        builder.literal_filters(Vec::new());
+        assert!(builder.build().text_matches(&format_args!("anything")));
+    }
+
+    #[test]
+    fn merge() {
+        let base = LogSpecification::parse("info, karl=debug, hyper=warn/a+b").unwrap();
+        let overriding = LogSpecification::parse("hyper=trace, egon=error").unwrap();
+
+        // precedence
+        let merged = base.merge(&overriding);
+        assert_eq!(
+            merged.module_filters(),
+            LogSpecification::parse("info, karl=debug, hyper=trace, egon=error")
+                .unwrap()
+                .module_filters()
+        );
+        assert_eq!(merged.text_filter().as_ref().unwrap().as_str(), "a+b");
+
+        let merged = base.merge(&LogSpecification::parse("warn/x").unwrap());
+        assert_eq!(
+            merged.module_filters(),
+            LogSpecification::parse("warn, karl=debug, hyper=warn")
+                .unwrap()
+                .module_filters()
+        );
+        assert_eq!(merged.text_filter().as_ref().unwrap().as_str(), "x");
+
+        // round trip
+        for spec in &[&base, &overriding] {
+            for merged in &[
+                spec.merge(&LogSpecification::off()),
+                LogSpecification::off().merge(spec),
+            ] {
+                assert_eq!(merged.module_filters(), spec.module_filters());
+                assert_eq!(
+                    merged.text_filter().as_ref().map(regex::Regex::as_str),
+                    spec.text_filter().as_ref().map(regex::Regex::as_str)
+                );
+            }
+            assert_eq!(spec.merge(spec).module_filters(), spec.module_filters());
+        }
+    }
+
+    #[test]
+    fn match_target() {
+        let record = log::Record::builder()
+            .args(format_args!("hello"))
+            .level(Level::Info)
+            .target("metrics")
+            .module_path(Some("server::db"))
+            .build();
+
+        let mut builder = crate::LogSpecBuilder::new();
+        builder
+            .module("server", LevelFilter::Info)
+            .module("metrics", LevelFilter::Off);
+        let spec = builder.build();
+        assert_eq!(spec.filter_subject(&record), "server::db");
+        assert!(spec.enabled(Level::Info, spec.filter_subject(&record)));
+
+        builder.match_target();
+        let spec = builder.build();
+        assert_eq!(spec.filter_subject(&record), "metrics");
+        assert!(!spec.enabled(Level::Info, spec.filter_subject(&record)));
+
+        let record = log::Record::builder()
+            .args(format_args!("hello"))
+            .target("server::web")
+            .build();
+        let spec = LogSpecification::parse("info").unwrap();
+        assert_eq!(spec.filter_subject(&record), "server::web");
+    }
+
+    #[test]
+    fn text_filter_scope() {
+        use crate::TextFilterScope;
+        // (module path, message)
+        let records = [
+            ("server::http::client", "GET /index.html"),
+            ("server::db", "querying http_log"),
+            ("server::db", "connected"),
+        ];
+        let matches = |scope: TextFilterScope| -> Vec<bool> {
+            let mut builder = crate::LogSpecBuilder::new();
+            builder.default(LevelFilter::Info).text_filter_scope(scope);
+            let spec = builder.build_with_textfilter(Some(regex::Regex::new("http").unwrap()));
+            records
+                .iter()
+                .map(|(module, text)| {
+                    spec.record_text_matches(
+                        &log::Record::builder()
+                            .args(format_args!("{}", text))
+                            .level(Level::Info)
+                            .module_path(Some(module))
+                            .build(),
+                    )
+                })
+                .collect()
+        };
+
+        assert_eq!(matches(TextFilterScope::Message), vec![false, true, false]);
+        assert_eq!(matches(TextFilterScope::Module), vec![true, false, false]);
+        assert_eq!(
+            matches(TextFilterScope::MessageOrModule),
+            vec![true, true, false]
+        );
+    }
+
+    #[test]
+    fn first_match_wins() {
+        let mut builder = crate::LogSpecBuilder::new();
+        builder
+            .first_match_wins()
+            .default(LevelFilter::Warn)
+            .module("abcd", LevelFilter::Info)
+            .module("abcd::mod1", LevelFilter::Trace)
+            .module("klmn::mod1", LevelFilter::Error)
+            .module("klmn", LevelFilter::Debug);
+        let spec = builder.build();
+
+        assert_eq!(spec.module_filters().len(), 5);
+        assert_eq!(spec.module_filters()[4].module_name, None);
+
+        // "abcd" shadows "abcd::mod1"
+        assert!(spec.enabled(Level::Info, "abcd::mod1"));
+        assert!(!spec.enabled(Level::Debug, "abcd::mod1"));
+        // "klmn::mod1" comes first
+        assert!(spec.enabled(Level::Error, "klmn::mod1"));
+        assert!(!spec.enabled(Level::Warn, "klmn::mod1"));
+        assert!(spec.enabled(Level::Debug, "klmn::mod2"));
+        // the default is only used if nothing else matches
+        assert!(spec.enabled(Level::Warn, "xyz"));
+        assert!(!spec.enabled(Level::Info, "xyz"));
+
+        // updating a module keeps its position
+        builder.module("abcd", LevelFilter::Error);
+        let spec = builder.build();
+        assert!(!spec.enabled(Level::Trace, "abcd::mod1"));
+    }
+
+    #[test]
+    fn merge_first_match_wins() {
+        let base = crate::LogSpecBuilder::new()
+            .first_match_wins()
+            .default(LevelFilter::Warn)
+            .module("abcd", LevelFilter::Info)
+            .module("abcd::mod1", LevelFilter::Trace)
+            .build();
+        let overriding =
+            LogSpecification::parse("abcd=error, abcd::mod1=debug, xyz=trace").unwrap();
+        let spec = base.merge(&overriding);
+
+        assert_eq!(
+            spec.module_filters()
+                .iter()
+                .map(|mf| (mf.module_name.as_deref(), mf.level_filter))
+                .collect::<Vec<_>>(),
+            vec![
+                (Some("abcd"), LevelFilter::Error),
+                (Some("abcd::mod1"), LevelFilter::Debug),
+                (Some("xyz"), LevelFilter::Trace),
+                (None, LevelFilter::Warn),
+            ]
+        );
+        // "abcd" still shadows "abcd::mod1"
+        assert!(spec.enabled(Level::Error, "abcd::mod1"));
+        assert!(!spec.enabled(Level::Warn, "abcd::mod1"));
+        assert!(spec.enabled(Level::Trace, "xyz"));
+        assert!(!spec.enabled(Level::Info, "klmn"));
+
+        // the order is also kept in further merges
+        let spec = spec.merge(&LogSpecification::parse("info").unwrap());
+        assert_eq!(spec.module_filters()[3].level_filter, LevelFilter::Info);
+        assert!(!spec.enabled(Level::Warn, "abcd::mod1"));
+    }
+
     ///////////////////////////////////////////////////////
     ///////////////////////////////////////////////////////
     #[test]
@@ -773,6 +1357,23 @@ mod tests {
         assert!(!spec.enabled(Level::Info, "rocket::rocket"));
     }
 
+    #[test]
+    fn level_aliases() {
+        let spec = LogSpecification::parse("all").unwrap();
+        assert_eq!(spec.module_filters()[0].module_name, None);
+        assert_eq!(spec.module_filters()[0].level_filter, LevelFilter::Trace);
+
+        let spec = LogSpecification::parse("NONE").unwrap();
+        assert_eq!(spec.module_filters()[0].module_name, None);
+        assert_eq!(spec.module_filters()[0].level_filter, LevelFilter::Off);
+
+        let spec = LogSpecification::parse("info, crate1=all, crate2=none").unwrap();
+        assert!(spec.enabled(Level::Trace, "crate1::mod1"));
+        assert!(!spec.enabled(Level::Error, "crate2::mod2"));
+        assert!(spec.enabled(Level::Info, "crate3"));
+        assert!(!spec.enabled(Level::Debug, "crate3"));
+    }
+
     #[test]
     fn zero_level() {
         let spec = LogSpecification::parse("info,crate1::mod1=off").unwrap();
@@ -839,5 +1440,4 @@ mod test_with_specfile {
             );
         }
     }
-
 }
@@ -1,15 +1,34 @@
-#[cfg(feature = "specfile")]
-use notify::{watcher, DebouncedEvent, RecursiveMode, Watcher};
+use arc_swap::ArcSwap;
+use chrono::FixedOffset;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64};
 use std::sync::{Arc, RwLock};
 
-use crate::flexi_logger::FlexiLogger;
-use crate::primary_writer::PrimaryWriter;
+#[cfg(feature = "kv")]
+use crate::flexi_logger::LogOnChange;
+#[cfg(feature = "specfile")]
+use crate::flexi_logger::INTERNAL_TARGET;
+use crate::flexi_logger::{
+    BufferUntilError, FlexiLogger, LevelCounts, LogOnce, RecordLimit, Redaction, Sampling,
+};
+use crate::primary_writer::{self, LevelGatedWriter, PrimaryWriter, StdOutWriter};
+#[cfg(feature = "specfile")]
+use crate::spec_source::SpecFileSource;
+use crate::spec_source::{spawn_watcher, SpecSource};
+#[cfg(feature = "specfile")]
+use crate::specfile_output::{OutputSpec, OutputTemplate, SharedWriter};
 use crate::writers::{FileLogWriter, FileLogWriterBuilder, LogWriter};
-use crate::FormatFunction;
+use crate::MapRecordFunction;
 use crate::ReconfigurationHandle;
-use crate::{formats, FlexiLoggerError, LogSpecification};
+use crate::{formats, DeferredNow, FlexiLoggerError, LogSpecification, SpecParseError};
+use crate::{Format, FormatFunction};
+use regex::Regex;
+
+#[cfg(feature = "colors")]
+const DEFAULT_FORMAT: FormatFunction = formats::colored_default_format;
+#[cfg(not(feature = "colors"))]
+const DEFAULT_FORMAT: FormatFunction = formats::default_format;
 
 /// The entry-point for using `flexi_logger`.
 ///
@@ -45,11 +64,44 @@ use crate::{formats, FlexiLoggerError, LogSpecification};
 ///
 pub struct Logger {
     spec: LogSpecification,
-    parse_errs: Option<Vec<String>>,
+    parse_errs: Option<Vec<SpecParseError>>,
     log_target: LogTarget,
-    duplicate: Duplicate,
-    format_for_file: FormatFunction,
-    format_for_stderr: FormatFunction,
+    duplicate: DuplicateMap,
+    duplicate_format: DuplicateFormat,
+    format_for_file: Format,
+    format_for_stderr: Format,
+    auto_timestamp: bool,
+    o_under_systemd: Option<bool>,
+    format_error_policy: FormatErrorPolicy,
+    recursion_policy: RecursionPolicy,
+    o_max_line_length: Option<usize>,
+    console_buffering: ConsoleBuffering,
+    stderr_prefix: String,
+    count_levels: bool,
+    o_log_once: Option<usize>,
+    #[cfg(feature = "kv")]
+    o_log_on_change: Option<(String, usize)>,
+    o_sampling: Option<(log::Level, u64)>,
+    o_max_records: Option<u64>,
+    o_max_records_exempt: Option<log::Level>,
+    o_buffer_until_error: Option<usize>,
+    sequence_numbers: bool,
+    #[cfg(feature = "hostname")]
+    with_hostname: bool,
+    sanitize_output: bool,
+    redactions: Vec<Regex>,
+    o_map_record: Option<MapRecordFunction>,
+    o_offset: Option<FixedOffset>,
+    internal_level: log::LevelFilter,
+    #[cfg(feature = "colors")]
+    highlights: Vec<(Regex, yansi::Style)>,
+    pad_levels: bool,
+    log_panics: bool,
+    o_startup_info: Option<StartupInfo>,
+    tee_to_stdout: bool,
+    error_file: bool,
+    o_error_file_path: Option<PathBuf>,
+    trace_file: bool,
     flwb: FileLogWriterBuilder,
     other_writers: HashMap<String, Box<dyn LogWriter>>,
 }
@@ -79,6 +131,31 @@ pub enum LogTarget {
     Writer(Box<dyn LogWriter>),
     /// Log is written to a file, as with `LogTarget::File`, _and_ to an alternative
     /// `LogWriter` implementation.
+    ///
+    /// Each of them uses its own format, so this allows e.g. writing a human-readable
+    /// file and a JSON file for the ingestion by other tools at the same time:
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "kv")]
+    /// # {
+    /// use flexi_logger::writers::FileLogWriter;
+    /// use flexi_logger::{detailed_format, ndjson_nested_format, LogTarget, Logger};
+    ///
+    /// let json_writer = FileLogWriter::builder()
+    ///     .format(ndjson_nested_format)
+    ///     .discriminant("json")
+    /// #   .directory("log_files/file_and_writer")
+    ///     .try_build()
+    ///     .unwrap();
+    ///
+    /// Logger::with_str("info")
+    ///     .log_target(LogTarget::FileAndWriter(Box::new(json_writer)))
+    /// #   .directory("log_files/file_and_writer")
+    ///     .format_for_files(detailed_format)
+    ///     .start()
+    ///     .unwrap();
+    /// # }
+    /// ```
     FileAndWriter(Box<dyn LogWriter>),
     /// Log is processed as if it were written, but is finally not written.
     ///
@@ -114,19 +191,72 @@ impl Logger {
         Logger::from_result(LogSpecification::env_or_parse(s))
     }
 
-    fn from_spec_and_errs(spec: LogSpecification, parse_errs: Option<Vec<String>>) -> Logger {
-        #[cfg(feature = "colors")]
-        let default_format = formats::colored_default_format;
-        #[cfg(not(feature = "colors"))]
-        let default_format = formats::default_format;
+    /// Creates a Logger that reads the LogSpecification from a toml document,
+    /// in the same format as the specfile
+    /// (see [`LogSpecification::from_toml`](struct.LogSpecification.html#method.from_toml)).
+    ///
+    /// This allows e.g. compiling a default configuration into the program with `include_str!`.
+    /// If the document is not valid toml, an error is printed to stderr,
+    /// and logging is switched off.
+    ///
+    /// This method is only available with feature `specfile`.
+    #[cfg(feature = "specfile")]
+    pub fn with_toml_str<S: AsRef<str>>(s: S) -> Logger {
+        let result = LogSpecification::from_toml(s.as_ref());
+        match result {
+            Ok(_) | Err(FlexiLoggerError::Parse(_, _)) => {}
+            Err(ref e) => eprintln!(
+                "[flexi_logger] reading the log specification failed with {}",
+                e
+            ),
+        }
+        Logger::from_result(result)
+    }
 
+    fn from_spec_and_errs(
+        spec: LogSpecification,
+        parse_errs: Option<Vec<SpecParseError>>,
+    ) -> Logger {
         Logger {
             spec,
             parse_errs,
             log_target: LogTarget::StdErr,
-            duplicate: Duplicate::None,
-            format_for_file: default_format,
-            format_for_stderr: default_format,
+            duplicate: DuplicateMap::from(Duplicate::None),
+            duplicate_format: DuplicateFormat::default(),
+            format_for_file: DEFAULT_FORMAT.into(),
+            format_for_stderr: DEFAULT_FORMAT.into(),
+            auto_timestamp: false,
+            o_under_systemd: None,
+            format_error_policy: FormatErrorPolicy::Drop,
+            recursion_policy: RecursionPolicy::Allow,
+            o_max_line_length: None,
+            console_buffering: ConsoleBuffering::default(),
+            stderr_prefix: String::new(),
+            count_levels: false,
+            o_log_once: None,
+            #[cfg(feature = "kv")]
+            o_log_on_change: None,
+            o_sampling: None,
+            o_max_records: None,
+            o_max_records_exempt: None,
+            o_buffer_until_error: None,
+            sequence_numbers: false,
+            #[cfg(feature = "hostname")]
+            with_hostname: false,
+            sanitize_output: false,
+            redactions: Vec::new(),
+            o_map_record: None,
+            o_offset: None,
+            internal_level: log::LevelFilter::Warn,
+            #[cfg(feature = "colors")]
+            highlights: Vec::new(),
+            pad_levels: false,
+            log_panics: false,
+            o_startup_info: None,
+            tee_to_stdout: false,
+            error_file: false,
+            o_error_file_path: None,
+            trace_file: false,
             flwb: FileLogWriter::builder(),
             other_writers: HashMap::<String, Box<dyn LogWriter>>::new(),
         }
@@ -183,6 +313,22 @@ impl Logger {
         self
     }
 
+    /// Is equivalent to
+    /// [`log_to_file`](struct.Logger.html#method.log_to_file)`()`
+    /// [`.duplicate_to_stderr`](struct.Logger.html#method.duplicate_to_stderr)`(dup)`.
+    ///
+    /// ```rust
+    /// use flexi_logger::{Duplicate, Logger};
+    /// Logger::with_str("info")
+    ///     .log_to_file_and_stderr(Duplicate::Warn)
+    /// #   .directory("log_files/log_to_file_and_stderr")
+    ///     .start()
+    ///     .unwrap();
+    /// ```
+    pub fn log_to_file_and_stderr(self, dup: Duplicate) -> Logger {
+        self.log_to_file().duplicate_to_stderr(dup)
+    }
+
     /// Write the main log output to the specified target.
     ///
     /// By default, i.e. if this method is not called, the standard output goes to `stderr`.
@@ -211,8 +357,141 @@ impl Logger {
     }
 
     /// Makes the logger write messages with the specified minimum severity additionally to stderr.
+    ///
+    /// This is a shortcut for a [`duplicate_map()`](struct.Logger.html#method.duplicate_map)
+    /// that maps the respective levels to `DuplicateTarget::StdErr`,
+    /// and replaces a map that was given before.
     pub fn duplicate_to_stderr(mut self, dup: Duplicate) -> Logger {
-        self.duplicate = dup;
+        self.duplicate = DuplicateMap::from(dup);
+        self
+    }
+
+    /// Makes the logger write messages additionally to stdout or stderr,
+    /// as defined for each level in the given map;
+    /// messages of the levels that are not in the map are not duplicated.
+    ///
+    /// This allows e.g. writing info messages to stdout, and warnings and errors to stderr:
+    ///
+    /// ```rust
+    /// use flexi_logger::{DuplicateTarget, Logger};
+    /// use log::Level;
+    /// use std::collections::HashMap;
+    ///
+    /// let mut duplicate_map = HashMap::new();
+    /// duplicate_map.insert(Level::Error, DuplicateTarget::StdErr);
+    /// duplicate_map.insert(Level::Warn, DuplicateTarget::StdErr);
+    /// duplicate_map.insert(Level::Info, DuplicateTarget::StdOut);
+    ///
+    /// Logger::with_str("info")
+    ///     .log_to_file()
+    /// #   .directory("log_files/duplicate_map")
+    ///     .duplicate_map(duplicate_map)
+    ///     .start()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// The map replaces a setting of
+    /// [`duplicate_to_stderr()`](struct.Logger.html#method.duplicate_to_stderr) that was
+    /// given before. The prefix of [`stderr_prefix()`](struct.Logger.html#method.stderr_prefix)
+    /// is only used for the duplicates to stderr.
+    pub fn duplicate_map(mut self, duplicate_map: HashMap<log::Level, DuplicateTarget>) -> Logger {
+        self.duplicate = DuplicateMap::from_map(&duplicate_map);
+        self
+    }
+
+    /// Defines which format function is used for the messages that are duplicated to stderr.
+    ///
+    /// By default, the format for stderr is used
+    /// (see [Logger::format_for_stderr()](struct.Logger.html#method.format_for_stderr)).
+    pub fn duplicate_format(mut self, duplicate_format: DuplicateFormat) -> Logger {
+        self.duplicate_format = duplicate_format;
+        self
+    }
+
+    /// Makes the logger write all log lines that are written to the log file
+    /// also to stdout, in the format that is used for the file.
+    ///
+    /// Other than with `duplicate_to_stderr()`, the lines are not filtered by their level.
+    /// This is e.g. useful for interactive runs of a program that normally logs to a file.
+    ///
+    /// This option only has an effect if `log_to_file()` is used, too.
+    pub fn tee_to_stdout(mut self) -> Logger {
+        self.tee_to_stdout = true;
+        self
+    }
+
+    /// Makes the logger write the error messages additionally to a second file,
+    /// which allows a quick scan for errors.
+    ///
+    /// With `None`, the name of the error file is derived from the log file:
+    /// it has the discriminant `error`, added to the discriminant of the log file (see
+    /// [`discriminant()`](struct.Logger.html#method.discriminant)),
+    /// e.g. `my_prog_error_2016-01-13_15-25-01.log` next to `my_prog_2016-01-13_15-25-01.log`.
+    /// All other file settings, like the directory, rotation, and cleanup, are
+    /// shared with the log file; a symlink is only created for the log file.
+    ///
+    /// With a path, like `log/app.error.log`, the error file is written there, without
+    /// timestamp or discriminant; rotation and cleanup are still shared with the log file,
+    /// and then add their infix to the name, like in `log/app.error_rCURRENT.log`.
+    ///
+    /// This option only has an effect if `log_to_file()` is used, too.
+    pub fn error_file(mut self, o_path: Option<PathBuf>) -> Logger {
+        self.error_file = true;
+        self.o_error_file_path = o_path;
+        self
+    }
+
+    /// Makes the logger write all records, up to level trace, to an additional file,
+    /// independent of the log specification.
+    ///
+    /// This allows deep debugging on demand, while the regular output, e.g. to the console,
+    /// stays at the level of the log specification:
+    ///
+    /// ```rust
+    /// use flexi_logger::Logger;
+    ///
+    /// Logger::with_str("info")
+    ///     .trace_file()
+    /// #   .directory("log_files/trace_file_doc")
+    ///     .start()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// The name of the trace file has the discriminant `trace`, added to the discriminant
+    /// of the log file (see [`discriminant()`](struct.Logger.html#method.discriminant)),
+    /// and it uses the other file settings, like the directory and rotation, and the
+    /// format for files; this works with all log targets.
+    ///
+    /// Note that the log crate then has to hand over all records to the logger,
+    /// which filters them for the other outputs; this costs some performance
+    /// also for the records that are only written to the trace file.
+    /// Records that address only custom writers (see
+    /// [`add_writer()`](struct.Logger.html#method.add_writer)) are written to the trace file, too.
+    pub fn trace_file(mut self) -> Logger {
+        self.trace_file = true;
+        self
+    }
+
+    /// Makes the logger prepend the given String to each line that is written to stderr,
+    /// including the lines that are duplicated to stderr.
+    /// Messages that span several lines get the prefix on each of their lines.
+    ///
+    /// This allows distinguishing the output of several programs that share a terminal,
+    /// and works with any format function.
+    pub fn stderr_prefix<S: Into<String>>(mut self, prefix: S) -> Logger {
+        self.stderr_prefix = prefix.into();
+        self
+    }
+
+    /// Defines how the log lines that are written to stdout or stderr are buffered;
+    /// by default, each line is written as soon as it is complete
+    /// (see [ConsoleBuffering](enum.ConsoleBuffering.html)).
+    ///
+    /// This applies to all output to the console, including the duplicates of
+    /// [`duplicate_to_stderr()`](struct.Logger.html#method.duplicate_to_stderr)
+    /// and the output of [`tee_to_stdout()`](struct.Logger.html#method.tee_to_stdout).
+    pub fn console_buffering(mut self, console_buffering: ConsoleBuffering) -> Logger {
+        self.console_buffering = console_buffering;
         self
     }
 
@@ -229,17 +508,58 @@ impl Logger {
     ///
     /// If the feature `colors` is switched off,
     /// `default_format()` is used for all outputs.
-    pub fn format(mut self, format: FormatFunction) -> Logger {
-        self.format_for_file = format;
+    pub fn format<F: Into<Format>>(mut self, format: F) -> Logger {
+        let format = format.into();
+        self.format_for_file = format.clone();
         self.format_for_stderr = format;
         self
     }
 
+    /// Makes the provided colored format functions write messages that match the given regex
+    /// with the given style, regardless of their level.
+    ///
+    /// If several rules are given, the first matching one is applied.
+    /// Only the message is highlighted, the other parts of the log line are styled as usual.
+    /// Note that the message text has to be formatted for every record to
+    /// check the rules.
+    ///
+    /// ```rust
+    /// use flexi_logger::{colored_default_format, Logger};
+    /// use regex::Regex;
+    /// use yansi::{Color, Style};
+    ///
+    /// Logger::with_str("info")
+    ///     .format(colored_default_format)
+    ///     .highlight(Regex::new("PANIC").unwrap(), Style::new(Color::Red).bold())
+    ///     .start()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// Only available with feature `colors`.
+    #[cfg(feature = "colors")]
+    pub fn highlight(mut self, regex: Regex, style: yansi::Style) -> Logger {
+        self.highlights.push((regex, style));
+        self
+    }
+
+    /// Makes the provided format functions, and those built with
+    /// [`FormatBuilder`](struct.FormatBuilder.html),
+    /// pad the level to five characters, so that the following columns are aligned:
+    ///
+    /// ```text
+    /// ERROR [my_prog::some_submodule] File not found
+    /// INFO  [my_prog::some_submodule] Task successfully read from conf.json
+    /// ```
+    pub fn pad_levels(mut self, pad_levels: bool) -> Logger {
+        self.pad_levels = pad_levels;
+        self
+    }
+
     /// Makes the logger use the provided format function for messages that are written to files.
     ///
     /// Regarding the default, see [Logger::format()](struct.Logger.html#method.format).
-    pub fn format_for_files(mut self, format: FormatFunction) -> Logger {
-        self.format_for_file = format;
+    pub fn format_for_files<F: Into<Format>>(mut self, format: F) -> Logger {
+        self.format_for_file = format.into();
         self
     }
 
@@ -247,8 +567,132 @@ impl Logger {
     /// that are written to stderr or to stdout.
     ///
     /// Regarding the default, see [Logger::format()](struct.Logger.html#method.format).
-    pub fn format_for_stderr(mut self, format: FormatFunction) -> Logger {
-        self.format_for_stderr = format;
+    pub fn format_for_stderr<F: Into<Format>>(mut self, format: F) -> Logger {
+        self.format_for_stderr = format.into();
+        self
+    }
+
+    /// Makes the logger write all messages, to files and to stderr or stdout, in JSON format,
+    /// using [`ndjson_nested_format()`](fn.ndjson_nested_format.html).
+    ///
+    /// This is a shortcut for `format(ndjson_nested_format)`,
+    /// see [Logger::format()](struct.Logger.html#method.format).
+    ///
+    /// Only available with feature `kv`.
+    #[cfg(feature = "kv")]
+    pub fn format_json(self) -> Logger {
+        self.format(formats::ndjson_nested_format)
+    }
+
+    /// Makes the logger use the provided format functions for the records of the
+    /// respective levels that are written to files, e.g. a detailed format for errors
+    /// and a compact one for the rest.
+    ///
+    /// The format functions take precedence over the one of
+    /// [`format_for_files()`](struct.Logger.html#method.format_for_files), which remains
+    /// the fallback for the levels that are not contained in the map.
+    /// The output to stderr or stdout, including the duplicates, is not affected.
+    ///
+    /// ```rust
+    /// use flexi_logger::{default_format, detailed_format, Format, Logger};
+    /// use std::collections::HashMap;
+    ///
+    /// let mut formats = HashMap::<log::Level, Format>::new();
+    /// formats.insert(log::Level::Error, detailed_format.into());
+    ///
+    /// Logger::with_str("info")
+    ///     .format(default_format)
+    ///     .format_per_level(formats)
+    ///     .log_to_file();
+    /// ```
+    pub fn format_per_level<F: Into<Format>>(mut self, formats: HashMap<log::Level, F>) -> Logger {
+        self.flwb = self.flwb.format_per_level(formats);
+        self
+    }
+
+    /// Makes the logger choose the format for messages that are written to stderr or to stdout
+    /// depending on whether the program runs as a systemd service.
+    ///
+    /// The journal adds its own timestamps, so under systemd the log lines are written with
+    /// [`default_format`](fn.default_format.html), otherwise they get a timestamp, like in
+    /// ```[2016-01-13 15:25:01.640870 +01:00] INFO [my_prog::some_submodule] Task successfully read```
+    ///
+    /// The program is assumed to run under systemd if one of the environment variables
+    /// `JOURNAL_STREAM` or `INVOCATION_ID` is set; the detection can be overridden with
+    /// [`assume_systemd()`](struct.Logger.html#method.assume_systemd).
+    ///
+    /// The chosen format replaces the one given with
+    /// [`format_for_stderr()`](struct.Logger.html#method.format_for_stderr).
+    pub fn auto_timestamp(mut self) -> Logger {
+        self.auto_timestamp = true;
+        self
+    }
+
+    /// Overrides the detection of systemd that is done with
+    /// [`auto_timestamp()`](struct.Logger.html#method.auto_timestamp).
+    pub fn assume_systemd(mut self, under_systemd: bool) -> Logger {
+        self.o_under_systemd = Some(under_systemd);
+        self
+    }
+
+    /// Defines how the logger reacts if the format function returns an error.
+    ///
+    /// By default, the error is reported to stderr and the log line is dropped
+    /// (see [FormatErrorPolicy](enum.FormatErrorPolicy.html)).
+    ///
+    /// If the format function panics, the log line is written with a minimal format instead,
+    /// and the first such panic is reported to stderr;
+    /// with `FormatErrorPolicy::Panic`, the panic is propagated.
+    pub fn format_error_policy(mut self, policy: FormatErrorPolicy) -> Logger {
+        self.format_error_policy = policy;
+        self.flwb = self.flwb.format_error_policy(policy);
+        self
+    }
+
+    /// Defines how the logger reacts to recursive logging, i.e. to log calls that are done
+    /// while a record of the same thread is formatted, e.g. in a `Display` implementation
+    /// of a value that is logged.
+    ///
+    /// By default, the recursive records are written, before the outer record
+    /// (see [RecursionPolicy](enum.RecursionPolicy.html)).
+    pub fn recursion_policy(mut self, policy: RecursionPolicy) -> Logger {
+        self.recursion_policy = policy;
+        self
+    }
+
+    /// Defines how the logger reacts if writing to the log file fails, e.g. because
+    /// the disk is full.
+    ///
+    /// By default, the failure is reported once to stderr and the log lines are dropped
+    /// until writing works again (see [WriteErrorPolicy](enum.WriteErrorPolicy.html)).
+    ///
+    /// This option only has an effect if `log_to_file()` is used, too.
+    pub fn write_error_policy(mut self, policy: WriteErrorPolicy) -> Logger {
+        self.flwb = self.flwb.write_error_policy(policy);
+        self
+    }
+
+    /// Limits the length of the log lines to the given number of bytes.
+    ///
+    /// This is useful if some log messages can contain huge blobs (like whole HTTP bodies)
+    /// that would otherwise blow up the log files and terminals.
+    /// Longer lines are truncated and get an ellipsis (`…`) appended;
+    /// the beginning of the line, which is produced by the format function
+    /// (timestamp, level, etc), thus stays intact.
+    /// Multibyte UTF-8 characters are never split.
+    pub fn max_line_length(mut self, max_line_length: usize) -> Logger {
+        self.o_max_line_length = Some(max_line_length);
+        self.flwb = self.flwb.max_line_length(max_line_length);
+        self
+    }
+
+    /// Makes the logger remove ANSI escape sequences (like the color codes of the colored
+    /// format functions) from the lines that are written to files.
+    ///
+    /// A colored format function that is used via [`format()`](#method.format)
+    /// then colors the output to stderr, but does not pollute the files.
+    pub fn strip_ansi_in_files(mut self) -> Logger {
+        self.flwb = self.flwb.strip_ansi();
         self
     }
 
@@ -263,6 +707,7 @@ impl Logger {
     }
 
     /// Specifies a suffix for the log files.
+    /// With an empty suffix, the log files have no extension.
     ///
     /// This parameter only has an effect if `log_to_file()` is used, too.
     pub fn suffix<S: Into<String>>(mut self, suffix: S) -> Logger {
@@ -322,6 +767,10 @@ impl Logger {
     ///
     /// This option only has an effect if `log_to_file()` is used, too.
     /// This option will hardly make an effect if `suppress_timestamp()` is not used.
+    ///
+    /// In combination with [`rotate()`](struct.Logger.html#method.rotate) and `Naming::Numbers`,
+    /// the existing rotated files are discovered at startup,
+    /// and the numbering continues after the highest existing index.
     pub fn append(mut self) -> Logger {
         self.flwb = self.flwb.append();
         self
@@ -335,6 +784,17 @@ impl Logger {
         self
     }
 
+    /// Specifies the separator that is used between the components of the log file name.
+    /// The default is `_`.
+    ///
+    /// This parameter only has an effect if `log_to_file()` is used, too.
+    /// See [`FileLogWriterBuilder::filename_separator()`](
+    /// writers/struct.FileLogWriterBuilder.html#method.filename_separator).
+    pub fn filename_separator<S: Into<String>>(mut self, separator: S) -> Logger {
+        self.flwb = self.flwb.filename_separator(separator);
+        self
+    }
+
     /// The specified path will be used on linux systems to create a symbolic link
     /// to the current log file.
     ///
@@ -371,11 +831,475 @@ impl Logger {
         self
     }
 
+    /// Registers several LogWriter implementations at once, each under its target name,
+    /// like [`add_writer()`](struct.Logger.html#method.add_writer).
+    ///
+    /// Accepts e.g. a [`WriterSet`](writers/struct.WriterSet.html) or a
+    /// `HashMap<String, Box<dyn LogWriter>>`.
+    /// Writers that were registered before under the same name are replaced.
+    pub fn add_writers<I>(mut self, writers: I) -> Logger
+    where
+        I: IntoIterator<Item = (String, Box<dyn LogWriter>)>,
+    {
+        self.other_writers.extend(writers);
+        self
+    }
+
+    /// Registers a LogWriter implementation under the given target name,
+    /// like [`add_writer()`](struct.Logger.html#method.add_writer),
+    /// but with a maximum log level that overrides the one of the writer.
+    ///
+    /// Records on a more detailed level are not handed over to the writer.
+    /// This allows keeping the level policy in the logger configuration,
+    /// without having to modify the writer.
+    pub fn add_writer_with_level<S: Into<String>>(
+        self,
+        target_name: S,
+        writer: Box<dyn LogWriter>,
+        max_log_level: log::LevelFilter,
+    ) -> Logger {
+        self.add_writer(
+            target_name,
+            Box::new(LevelOverride {
+                writer,
+                max_log_level,
+            }),
+        )
+    }
+
     /// Use Windows line endings, rather than just `\n`.
     pub fn use_windows_line_ending(mut self) -> Logger {
         self.flwb = self.flwb.use_windows_line_ending();
         self
     }
+
+    /// Makes the logger sync the log file to disk after each error-level record,
+    /// so that error messages survive a subsequent crash or abort of the program.
+    ///
+    /// This option only has an effect if `log_to_file()` is used, too.
+    pub fn sync_on_error(mut self) -> Logger {
+        self.flwb = self.flwb.sync_on_error();
+        self
+    }
+
+    /// Makes the logger write a UTF-8 byte order mark at the beginning of each new log file.
+    ///
+    /// This option only has an effect if `log_to_file()` is used, too.
+    /// See [`FileLogWriterBuilder::write_bom()`](
+    /// writers/struct.FileLogWriterBuilder.html#method.write_bom).
+    pub fn write_bom(mut self) -> Logger {
+        self.flwb = self.flwb.write_bom();
+        self
+    }
+
+    /// Makes the logger try up to `attempts` times to open a log file,
+    /// waiting for `delay` after each failed attempt, before it gives up.
+    ///
+    /// This option only has an effect if `log_to_file()` is used, too.
+    /// See [`FileLogWriterBuilder::open_retry()`](
+    /// writers/struct.FileLogWriterBuilder.html#method.open_retry).
+    pub fn open_retry(mut self, attempts: u32, delay: std::time::Duration) -> Logger {
+        self.flwb = self.flwb.open_retry(attempts, delay);
+        self
+    }
+
+    /// Makes the logger create the log files with the given permissions, e.g. `0o600`.
+    ///
+    /// This option only has an effect if `log_to_file()` is used, too.
+    /// See [`FileLogWriterBuilder::file_permissions()`](
+    /// writers/struct.FileLogWriterBuilder.html#method.file_permissions).
+    ///
+    /// Only available on unix.
+    #[cfg(unix)]
+    pub fn file_permissions(mut self, mode: u32) -> Logger {
+        self.flwb = self.flwb.file_permissions(mode);
+        self
+    }
+
+    /// Makes the logger write the log lines to the file in batches of `n` records.
+    ///
+    /// This option only has an effect if `log_to_file()` is used, too.
+    /// See [`FileLogWriterBuilder::flush_every()`](
+    /// writers/struct.FileLogWriterBuilder.html#method.flush_every).
+    pub fn flush_every(mut self, n: usize) -> Logger {
+        self.flwb = self.flwb.flush_every(n);
+        self
+    }
+
+    /// Makes the logger rotate the log file additionally when the given number of lines
+    /// has been written to it.
+    ///
+    /// This option only has an effect if `log_to_file()` and `rotate()` are used, too.
+    /// See [`FileLogWriterBuilder::rotate_over_lines()`](
+    /// writers/struct.FileLogWriterBuilder.html#method.rotate_over_lines).
+    pub fn rotate_over_lines(mut self, lines: u64) -> Logger {
+        self.flwb = self.flwb.rotate_over_lines(lines);
+        self
+    }
+
+    /// Makes the logger rotate the log file additionally after writing a record whose message
+    /// matches the given pattern.
+    ///
+    /// This option only has an effect if `log_to_file()` and `rotate()` are used, too.
+    /// See [`FileLogWriterBuilder::rotate_on_match()`](
+    /// writers/struct.FileLogWriterBuilder.html#method.rotate_on_match).
+    pub fn rotate_on_match(mut self, pattern: Regex) -> Logger {
+        self.flwb = self.flwb.rotate_on_match(pattern);
+        self
+    }
+
+    /// Defines whether a `_rCURRENT` file that was left by a previous program run is
+    /// rotated away at startup, or continued.
+    ///
+    /// This option only has an effect if `log_to_file()` and `rotate()` are used, too.
+    /// See [`FileLogWriterBuilder::rotate_current_on_startup()`](
+    /// writers/struct.FileLogWriterBuilder.html#method.rotate_current_on_startup).
+    pub fn rotate_current_on_startup(mut self, rotate: bool) -> Logger {
+        self.flwb = self.flwb.rotate_current_on_startup(rotate);
+        self
+    }
+
+    /// Makes the logger write the given line at the end of each log file.
+    ///
+    /// This option only has an effect if `log_to_file()` is used, too.
+    /// See [`FileLogWriterBuilder::end_marker()`](
+    /// writers/struct.FileLogWriterBuilder.html#method.end_marker).
+    pub fn end_marker<S: Into<String>>(mut self, marker: S) -> Logger {
+        self.flwb = self.flwb.end_marker(marker);
+        self
+    }
+
+    /// Makes the logger count the written log lines per level.
+    ///
+    /// The counts can be retrieved with
+    /// [`ReconfigurationHandle::level_counts()`](struct.ReconfigurationHandle.html#method.level_counts),
+    /// or written as a summary line with
+    /// [`ReconfigurationHandle::summary()`](struct.ReconfigurationHandle.html#method.summary),
+    /// e.g. at the end of a batch job.
+    pub fn count_levels(mut self) -> Logger {
+        self.count_levels = true;
+        self
+    }
+
+    /// Makes the logger write each distinct log message only once.
+    ///
+    /// The first record with a given combination of target and message text is written,
+    /// all later records with the same target and text are dropped, for the whole
+    /// lifetime of the program.
+    /// This is useful e.g. for noisy diagnostics that are repeated over and over.
+    ///
+    /// ## Memory
+    ///
+    /// The logger keeps a 64-bit hash for each distinct message it has written,
+    /// but for at most `max_messages` messages.
+    /// Once this limit is reached, messages that were not yet seen are always written.
+    ///
+    /// Note that the message text has to be formatted for every record to
+    /// determine if it was seen before.
+    pub fn log_once(mut self, max_messages: usize) -> Logger {
+        self.o_log_once = Some(max_messages);
+        self
+    }
+
+    /// Makes the logger write records that carry the key-value field `key`
+    /// only if their message differs from the last written message with the same
+    /// value of this field.
+    ///
+    /// This reduces the noise of status-polling loops, which can log their observations
+    /// unconditionally, and still only the state transitions are written:
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "kv")]
+    /// # {
+    /// use flexi_logger::Logger;
+    /// use log::info;
+    ///
+    /// Logger::with_str("info")
+    ///     .log_on_change("tracked", 100)
+    ///     .start()
+    ///     .unwrap();
+    ///
+    /// for _ in 0..3 {
+    ///     // written only once, until the state of the connection changes
+    ///     info!(tracked = "connection"; "connection is up");
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// Records without the field are not affected.
+    ///
+    /// ## Memory
+    ///
+    /// The logger keeps the value of the field and a 64-bit hash of the last message
+    /// for at most `max_keys` distinct values.
+    /// Records with further values are always written.
+    ///
+    /// Only available with feature `kv`.
+    #[cfg(feature = "kv")]
+    pub fn log_on_change<S: Into<String>>(mut self, key: S, max_keys: usize) -> Logger {
+        self.o_log_on_change = Some((key.into(), max_keys));
+        self
+    }
+
+    /// Makes the logger write only every `rate`-th record with level `level` or
+    /// a less severe level.
+    ///
+    /// This allows getting representative samples from hot code paths without the full cost
+    /// of writing every record, e.g. `sample(Level::Debug, 100)` lets only one of hundred
+    /// debug and trace messages pass. More severe records are never dropped by sampling.
+    ///
+    /// Note that sampling happens after the log specification has enabled the record,
+    /// so the arguments of the log macros are still evaluated for all records.
+    pub fn sample(mut self, level: log::Level, rate: u64) -> Logger {
+        self.o_sampling = Some((level, rate));
+        self
+    }
+
+    /// Makes the logger write at most `max` records; further records are dropped.
+    ///
+    /// This is a safety valve e.g. for unattended debugging sessions, which must not
+    /// fill the disk. In place of the first dropped record, a warning with target
+    /// `flexi_logger::internal` is written, which tells that the limit was reached
+    /// (see [`internal_log_level()`](struct.Logger.html#method.internal_log_level)).
+    ///
+    /// The limit applies to the records that pass the log specification and the other filters;
+    /// see also [`exempt_from_max_records()`](struct.Logger.html#method.exempt_from_max_records).
+    pub fn max_records(mut self, max: u64) -> Logger {
+        self.o_max_records = Some(max);
+        self
+    }
+
+    /// Makes records with the given level, or a more severe level, be written also
+    /// after the limit of [`max_records()`](struct.Logger.html#method.max_records)
+    /// was reached; they are not counted either.
+    pub fn exempt_from_max_records(mut self, level: log::Level) -> Logger {
+        self.o_max_records_exempt = Some(level);
+        self
+    }
+
+    /// Makes the logger hold back all records, until the first error is logged;
+    /// then the held back records are written, followed by the error,
+    /// and from then on all records are written immediately.
+    ///
+    /// This is useful e.g. for command line tools that should be quiet during normal
+    /// operation, but should provide the full context of the first error;
+    /// use a log specification like `debug` to get the context in the desired detail.
+    ///
+    /// At most `capacity` records are held back; when the buffer is full, the oldest record
+    /// is dropped for each new one, so that the most recent records before the error survive.
+    /// If no error is logged, the held back records are never written.
+    ///
+    /// The records are held back after the log specification and the other filters have
+    /// been applied, and as owned copies, with their original timestamps.
+    /// With feature `kv`, the values of their key-value pairs are kept as strings.
+    /// Only the output of the logger itself is held back, not the output to the writers
+    /// that were added with [`add_writer()`](struct.Logger.html#method.add_writer).
+    pub fn buffer_until_error(mut self, capacity: usize) -> Logger {
+        self.o_buffer_until_error = Some(capacity);
+        self
+    }
+
+    /// Makes the logger assign an incrementing sequence number to each record it writes,
+    /// starting with 1.
+    ///
+    /// The number is assigned once per record, when the record has passed all filters,
+    /// so it is the same in all outputs the record is written to, and gaps in the
+    /// numbers of a log file reveal records that were written only to other outputs.
+    /// The number can be rendered with
+    /// [`with_sequence_number`](fn.with_sequence_number.html),
+    /// or in own format functions with [`sequence_number()`](fn.sequence_number.html).
+    ///
+    /// Note that the numbers reflect the order in which the records were accepted;
+    /// with several logging threads, a record with a higher number can still be
+    /// written before a record with a lower number.
+    pub fn sequence_numbers(mut self) -> Logger {
+        self.sequence_numbers = true;
+        self
+    }
+
+    /// Makes the logger provide the hostname of the machine to the format functions,
+    /// which helps correlating the log lines of many machines that are aggregated
+    /// into one stream.
+    ///
+    /// The hostname is determined once, when the logger is started.
+    /// It can be rendered with [`with_hostname`](fn.with_hostname.html),
+    /// or in own format functions with [`hostname()`](fn.hostname.html);
+    /// with feature `kv`, [`ndjson_nested_format`](fn.ndjson_nested_format.html)
+    /// adds it as `host` field.
+    ///
+    /// Only available with feature `hostname`.
+    #[cfg(feature = "hostname")]
+    pub fn with_hostname(mut self) -> Logger {
+        self.with_hostname = true;
+        self
+    }
+
+    /// Makes the logger escape the control characters in the messages of the records,
+    /// before they are filtered and written.
+    ///
+    /// This protects against log injection when untrusted input is logged:
+    /// line breaks can not forge additional log lines, and ANSI escape sequences
+    /// can not manipulate the terminal. A message like `"user\n\x1b[31mERROR forged"`
+    /// is written as `user\n\u{1b}[31mERROR forged`.
+    ///
+    /// The styling that is added by the colored format functions is not affected.
+    /// Note that the key-value pairs of the records are not escaped, and that
+    /// the message text has to be formatted for every record to check it.
+    pub fn sanitize_output(mut self) -> Logger {
+        self.sanitize_output = true;
+        self
+    }
+
+    /// Makes the logger replace all matches of the given patterns in the messages
+    /// of the records with `***`, before they are filtered and written to any writer.
+    ///
+    /// This keeps secrets that are logged by accident, like tokens or card numbers,
+    /// out of the logs:
+    ///
+    /// ```rust
+    /// use flexi_logger::Logger;
+    /// use regex::Regex;
+    ///
+    /// Logger::with_str("info")
+    ///     .redact(vec![
+    ///         Regex::new(r"\b\d{4}[ -]?\d{4}[ -]?\d{4}[ -]?\d{4}\b").unwrap(),
+    ///         Regex::new(r"Bearer [A-Za-z0-9._~+/-]+=*").unwrap(),
+    ///     ])
+    ///     .start()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// Matches of different patterns that overlap or touch each other are replaced by
+    /// a single `***`, independent of the order of the patterns.
+    /// Only the message is redacted, not the other parts of the log line like timestamp
+    /// or level, and not the key-value pairs of the record.
+    /// If [`sanitize_output()`](struct.Logger.html#method.sanitize_output) is used as well,
+    /// the patterns are applied to the message before its control characters are escaped.
+    /// Note that the message text has to be formatted for every record to check it.
+    pub fn redact(mut self, patterns: Vec<Regex>) -> Logger {
+        self.redactions = patterns;
+        self
+    }
+
+    /// Makes the logger hand each record to the given function, which can modify it
+    /// or drop it.
+    ///
+    /// The function returns `None` if the record is to be dropped,
+    /// or an [`OwnedRecord`](struct.OwnedRecord.html) that is written instead of the original record.
+    /// This allows e.g. downgrading the spurious errors of some third-party crate to warnings:
+    ///
+    /// ```rust
+    /// use flexi_logger::{Level, Logger, OwnedRecord};
+    ///
+    /// Logger::with_str("info")
+    ///     .map_record(Box::new(|record| {
+    ///         let mut owned_record = OwnedRecord::from(record);
+    ///         if owned_record.target.starts_with("noisy_crate") && owned_record.level == Level::Error {
+    ///             owned_record.level = Level::Warn;
+    ///         }
+    ///         Some(owned_record)
+    ///     }))
+    ///     .start()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// The function is called for the records that pass the log specification, or,
+    /// for records to custom writers, the level of the addressed writers.
+    /// The returned record then runs through the usual pipeline:
+    /// it is checked again against the log specification (so a downgraded record can
+    /// still be filtered out), then [`redact()`](struct.Logger.html#method.redact)
+    /// and [`sanitize_output()`](struct.Logger.html#method.sanitize_output) are applied,
+    /// and finally it is formatted and written by the writers.
+    ///
+    /// Note that every record that passes the filter is copied into an `OwnedRecord`,
+    /// which costs some performance.
+    pub fn map_record(mut self, map_record: MapRecordFunction) -> Logger {
+        self.o_map_record = Some(map_record);
+        self
+    }
+
+    /// Makes the provided format functions render the timestamps with the given fixed
+    /// UTC offset, rather than in the local timezone.
+    ///
+    /// This is useful e.g. if the servers run in UTC, but the logs are read
+    /// in a known business timezone:
+    ///
+    /// ```rust
+    /// use chrono::FixedOffset;
+    /// use flexi_logger::{opt_format, Logger};
+    ///
+    /// Logger::with_str("info")
+    ///     .format(opt_format)
+    ///     .use_offset(FixedOffset::east_opt(2 * 3600).unwrap())
+    ///     .start()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// Own format functions can use
+    /// [`DeferredNow::format()`](struct.DeferredNow.html#method.format)
+    /// to respect the offset. The names of the log files are not affected.
+    pub fn use_offset(mut self, offset: FixedOffset) -> Logger {
+        self.o_offset = Some(offset);
+        self
+    }
+
+    /// Sets the level up to which the diagnostic messages of `flexi_logger` itself
+    /// are written; the default is `LevelFilter::Warn`.
+    ///
+    /// These messages, e.g. about problems with rereading the specfile
+    /// (see [`start_with_specfile()`](struct.Logger.html#method.start_with_specfile)),
+    /// are logged with target `flexi_logger::internal` and are filtered only with this level,
+    /// independent of the log specification.
+    /// `LevelFilter::Off` silences them, `LevelFilter::Debug` additionally shows
+    /// when the specfile is reread.
+    pub fn internal_log_level(mut self, level: log::LevelFilter) -> Logger {
+        self.internal_level = level;
+        self
+    }
+
+    /// Makes the logger install a panic hook that writes panics as error messages
+    /// to the log, including the thread name, the location, and a backtrace
+    /// (if backtraces are enabled, e.g. with the environment variable `RUST_BACKTRACE`).
+    ///
+    /// A panic hook that was installed before is not replaced, but is called after the
+    /// panic was logged. With the default hook, panics thus still also show up on stderr.
+    ///
+    /// The panic messages are logged with module path `flexi_logger::logger`,
+    /// so make sure that your log specification does not suppress errors from there.
+    pub fn log_panics(mut self) -> Logger {
+        self.log_panics = true;
+        self
+    }
+
+    /// Makes the logger write, right after its initialization, an info-level record
+    /// with the program name, the version, and the optional build information.
+    ///
+    /// The record is written to the configured output, in the configured format,
+    /// and with the program name (with `-` replaced by `_`) as module path and target,
+    /// so it can be suppressed with the log specification like the program's own records.
+    /// Just don't call this method if you don't want the record at all.
+    ///
+    /// The values must be determined at the call site, e.g. with `env!`:
+    ///
+    /// ```rust
+    /// use flexi_logger::{Logger, StartupInfo};
+    ///
+    /// Logger::with_str("info")
+    ///     .log_startup_info(
+    ///         StartupInfo::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+    ///             .build_info(option_env!("GIT_HASH").unwrap_or("unknown commit")),
+    ///     )
+    ///     .start()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// produces a line like
+    /// `INFO [my_app] my-app 1.2.3 (unknown commit) started`
+    /// (for a program called `my-app`).
+    pub fn log_startup_info(mut self, startup_info: StartupInfo) -> Logger {
+        self.o_startup_info = Some(startup_info);
+        self
+    }
 }
 
 /// Alternative set of methods to control the behavior of the Logger.
@@ -463,6 +1387,44 @@ impl Logger {
         self.flwb = self.flwb.o_create_symlink(symlink);
         self
     }
+
+    /// This option only has an effect if `log_to_file` is set to true.
+    ///
+    /// Specifies a suffix for the log files. With None, the default suffix "log" is used.
+    pub fn o_suffix<S: Into<String>>(mut self, suffix: Option<S>) -> Logger {
+        self.flwb = self.flwb.o_suffix(suffix);
+        self
+    }
+
+    /// This option only has an effect if `log_to_file` is set to true.
+    ///
+    /// With true, makes the logger use Windows line endings, rather than just `\n`.
+    pub fn o_use_windows_line_ending(mut self, use_windows_line_ending: bool) -> Logger {
+        self.flwb = self.flwb.o_use_windows_line_ending(use_windows_line_ending);
+        self
+    }
+
+    /// Makes the logger use the provided format function for all messages
+    /// that are written to files or to stderr, like
+    /// [`format()`](struct.Logger.html#method.format).
+    ///
+    /// With None, the default format functions are used.
+    pub fn o_format(self, format: Option<FormatFunction>) -> Logger {
+        self.format(format.unwrap_or(DEFAULT_FORMAT))
+    }
+
+    /// If a writer is specified, registers it under the given target name, like
+    /// [`add_writer()`](struct.Logger.html#method.add_writer).
+    pub fn o_add_writer<S: Into<String>>(
+        self,
+        target_name: S,
+        writer: Option<Box<dyn LogWriter>>,
+    ) -> Logger {
+        match writer {
+            Some(writer) => self.add_writer(target_name, writer),
+            None => self,
+        }
+    }
 }
 
 /// Finally, start logging, optionally with a spec-file.
@@ -472,46 +1434,194 @@ impl Logger {
     /// The returned reconfiguration handle allows updating the log specification programmatically
     /// later on, e.g. to intensify logging for (buggy) parts of a (test) program, etc.
     /// See [ReconfigurationHandle](struct.ReconfigurationHandle.html) for an example.
+    ///
+    /// You don't need to keep the handle if you don't need it; logging continues
+    /// also after the handle was dropped.
     pub fn start(mut self) -> Result<ReconfigurationHandle, FlexiLoggerError> {
+        self.choose_auto_timestamp_format();
         let max_level = self.spec.max_level();
         let spec = Arc::new(RwLock::new(self.spec));
-        let other_writers = Arc::new(self.other_writers);
+        let other_writers = Arc::new(RwLock::new(self.other_writers));
+        let format_for_duplicates = match self.duplicate_format {
+            DuplicateFormat::MatchFile => self.format_for_file.clone(),
+            DuplicateFormat::UseStderrFormat => self.format_for_stderr.clone(),
+        };
+
+        let o_trace_writer = if self.trace_file {
+            let flw = self
+                .flwb
+                .derive("trace")
+                .format(self.format_for_file.clone())
+                .try_build()?;
+            Some(Arc::new(flw) as Arc<dyn LogWriter>)
+        } else {
+            None
+        };
 
-        let primary_writer = Arc::new(match self.log_target {
+        let mut o_log_file_info = None;
+        let primary_writer = Arc::new(ArcSwap::from_pointee(match self.log_target {
             LogTarget::File => {
-                self.flwb = self.flwb.format(self.format_for_file);
+                self.flwb = self.flwb.format(self.format_for_file.clone());
+                let o_error_file_writer = error_file_writer(
+                    self.error_file,
+                    self.o_error_file_path.as_deref(),
+                    &self.flwb,
+                )?;
+                let flw = self.flwb.try_build()?;
+                o_log_file_info = Some(flw.log_file_info());
+                let mut writers: Vec<Box<dyn LogWriter>> = vec![Box::new(flw)];
+                writers.extend(o_error_file_writer);
+                if self.tee_to_stdout {
+                    writers.push(Box::new(StdOutWriter::new(
+                        self.format_for_file.clone(),
+                        self.format_error_policy,
+                        self.o_max_line_length,
+                        self.console_buffering,
+                    )));
+                }
                 PrimaryWriter::multi(
                     self.duplicate,
-                    self.format_for_stderr,
-                    vec![Box::new(self.flwb.try_build()?)],
+                    format_for_duplicates,
+                    self.format_error_policy,
+                    self.o_max_line_length,
+                    self.console_buffering,
+                    self.stderr_prefix,
+                    writers,
                 )
             }
             LogTarget::Writer(w) => {
-                self.flwb = self.flwb.format(self.format_for_file);
-                PrimaryWriter::multi(self.duplicate, self.format_for_stderr, vec![w])
+                self.flwb = self.flwb.format(self.format_for_file.clone());
+                PrimaryWriter::multi(
+                    self.duplicate,
+                    format_for_duplicates,
+                    self.format_error_policy,
+                    self.o_max_line_length,
+                    self.console_buffering,
+                    self.stderr_prefix,
+                    vec![w],
+                )
             }
             LogTarget::FileAndWriter(w) => {
-                self.flwb = self.flwb.format(self.format_for_file);
+                self.flwb = self.flwb.format(self.format_for_file.clone());
+                let o_error_file_writer = error_file_writer(
+                    self.error_file,
+                    self.o_error_file_path.as_deref(),
+                    &self.flwb,
+                )?;
+                let flw = self.flwb.try_build()?;
+                o_log_file_info = Some(flw.log_file_info());
+                let mut writers: Vec<Box<dyn LogWriter>> = vec![Box::new(flw), w];
+                writers.extend(o_error_file_writer);
+                if self.tee_to_stdout {
+                    writers.push(Box::new(StdOutWriter::new(
+                        self.format_for_file.clone(),
+                        self.format_error_policy,
+                        self.o_max_line_length,
+                        self.console_buffering,
+                    )));
+                }
                 PrimaryWriter::multi(
                     self.duplicate,
-                    self.format_for_stderr,
-                    vec![Box::new(self.flwb.try_build()?), w],
+                    format_for_duplicates,
+                    self.format_error_policy,
+                    self.o_max_line_length,
+                    self.console_buffering,
+                    self.stderr_prefix,
+                    writers,
                 )
             }
-            LogTarget::StdOut => PrimaryWriter::stdout(self.format_for_stderr),
-            LogTarget::StdErr => PrimaryWriter::stderr(self.format_for_stderr),
-            LogTarget::DevNull => PrimaryWriter::black_hole(self.duplicate, self.format_for_stderr),
-        });
+            LogTarget::StdOut => PrimaryWriter::stdout(
+                self.format_for_stderr,
+                self.format_error_policy,
+                self.o_max_line_length,
+                self.console_buffering,
+            ),
+            LogTarget::StdErr => PrimaryWriter::stderr(
+                self.format_for_stderr,
+                self.format_error_policy,
+                self.o_max_line_length,
+                self.console_buffering,
+                self.stderr_prefix,
+            ),
+            LogTarget::DevNull => PrimaryWriter::black_hole(
+                self.duplicate,
+                format_for_duplicates,
+                self.format_error_policy,
+                self.o_max_line_length,
+                self.console_buffering,
+                self.stderr_prefix,
+            ),
+        }));
 
+        let o_level_counts = if self.count_levels {
+            Some(Arc::new(LevelCounts::default()))
+        } else {
+            None
+        };
+
+        let paused = Arc::new(AtomicBool::new(false));
+        let dropped = Arc::new(AtomicU64::new(0));
+
+        let o_startup_info = self.o_startup_info.take();
+        let o_max_records_exempt = self.o_max_records_exempt;
         let flexi_logger = FlexiLogger::new(
             Arc::clone(&spec),
             Arc::clone(&primary_writer),
             Arc::clone(&other_writers),
+            o_level_counts.clone(),
+            self.o_log_once.map(LogOnce::new),
+            #[cfg(feature = "kv")]
+            self.o_log_on_change
+                .map(|(key, max_keys)| LogOnChange::new(key, max_keys)),
+            self.o_sampling
+                .map(|(level, rate)| Sampling::new(level, rate)),
+            self.o_max_records
+                .map(|max| RecordLimit::new(max, o_max_records_exempt)),
+            if self.sequence_numbers {
+                Some(AtomicU64::new(1))
+            } else {
+                None
+            },
+            self.o_buffer_until_error.map(BufferUntilError::new),
+            self.o_offset,
+            self.internal_level,
+            self.sanitize_output,
+            if self.redactions.is_empty() {
+                None
+            } else {
+                Some(Redaction::new(self.redactions))
+            },
+            self.o_map_record.take(),
+            o_trace_writer.clone(),
+            Arc::clone(&paused),
+            Arc::clone(&dropped),
         );
 
         log::set_boxed_logger(Box::new(flexi_logger))?;
-        let handle = ReconfigurationHandle::new(spec, primary_writer, other_writers);
+        #[cfg(feature = "colors")]
+        formats::set_highlights(self.highlights);
+        formats::set_pad_levels(self.pad_levels);
+        primary_writer::set_recursion_policy(self.recursion_policy);
+        #[cfg(feature = "hostname")]
+        formats::set_with_hostname(self.with_hostname);
+        if self.log_panics {
+            install_panic_hook();
+        }
+        let handle = ReconfigurationHandle::new(
+            spec,
+            primary_writer,
+            other_writers,
+            o_trace_writer,
+            o_level_counts,
+            self.internal_level,
+            paused,
+            dropped,
+            o_log_file_info,
+        );
         handle.reconfigure(max_level);
+        if let Some(startup_info) = o_startup_info {
+            startup_info.log();
+        }
         Ok(handle)
     }
 
@@ -553,6 +1663,13 @@ impl Logger {
     /// #'mod1' = 'warn'
     /// #'mod2' = 'debug'
     /// #'mod2::mod3' = 'trace'
+    ///
+    /// ### Optional: define where and how the log lines are written
+    /// ### (target = 'stderr', 'stdout', 'file', or 'devnull')
+    /// #[output]
+    /// #target = 'file'
+    /// #directory = 'log_files'
+    /// #format = 'detailed'
     /// ```
     ///
     /// You can subsequently edit and modify the file according to your needs,
@@ -567,62 +1684,236 @@ impl Logger {
     /// If the file cannot be read anymore, e.g. because the format is not correct, the
     /// previous logspec remains active.
     /// If the file is corrected subsequently, the log spec update will work again.
+    ///
+    /// ## Output section
+    ///
+    /// The optional `[output]` section of the file overrides the output settings
+    /// of the Logger:
+    ///
+    /// * `target` can be `'stderr'`, `'stdout'`, `'file'`, or `'devnull'`,
+    /// * `directory` is the folder for the log files
+    ///   (see [`directory()`](struct.Logger.html#method.directory)),
+    /// * `format` can be `'default'`, `'opt'`, `'detailed'`, `'with_thread'`,
+    ///   `'target_only'`, or, with feature `kv`, `'json'` (see
+    ///   [`ndjson_nested_format()`](fn.ndjson_nested_format.html)) or `'json_ecs'` (see
+    ///   [`json_format_ecs()`](fn.json_format_ecs.html)) or `'default_json_kv'` (see
+    ///   [`default_format_json_kv()`](fn.default_format_json_kv.html)),
+    ///   or, with feature `gelf`, `'gelf'` (see [`gelf_format()`](fn.gelf_format.html)).
+    ///
+    /// All other settings, e.g. for rotation, and the values that are missing in the section
+    /// are taken from the Logger.
+    /// A writer that was given with `LogTarget::Writer` or `LogTarget::FileAndWriter` is
+    /// kept, and gets the log lines in addition to the output of the section;
+    /// with `LogTarget::Writer` and no `target` in the section, it remains the only output.
+    ///
+    /// When the section is changed while the program is running, the output is switched
+    /// accordingly; removing the section keeps the current output.
     #[cfg(feature = "specfile")]
     pub fn start_with_specfile<P: AsRef<std::path::Path>>(
-        self,
+        mut self,
         specfile: P,
     ) -> Result<(), FlexiLoggerError> {
-        // Make logging work, before caring for the specfile
-        let mut handle = self.start()?;
-
         let specfile = specfile.as_ref().to_owned();
+        self.choose_auto_timestamp_format();
+
+        // the writer of the log target is shared with the primary writers of the output section
+        let mut o_user_writer: Option<Arc<dyn LogWriter>> = None;
+        self.log_target = match std::mem::replace(&mut self.log_target, LogTarget::DevNull) {
+            LogTarget::Writer(w) => {
+                let w: Arc<dyn LogWriter> = Arc::from(w);
+                o_user_writer = Some(Arc::clone(&w));
+                LogTarget::Writer(Box::new(SharedWriter(w)))
+            }
+            LogTarget::FileAndWriter(w) => {
+                let w: Arc<dyn LogWriter> = Arc::from(w);
+                o_user_writer = Some(Arc::clone(&w));
+                LogTarget::FileAndWriter(Box::new(SharedWriter(w)))
+            }
+            log_target => log_target,
+        };
+
+        let template = OutputTemplate {
+            default_target: match self.log_target {
+                LogTarget::File | LogTarget::FileAndWriter(_) => "file",
+                LogTarget::StdOut => "stdout",
+                LogTarget::StdErr => "stderr",
+                // with the user writer, "devnull" means only the user writer,
+                // plus the duplication of the Logger
+                LogTarget::Writer(_) | LogTarget::DevNull => "devnull",
+            },
+            duplicate: self.duplicate,
+            duplicate_format: self.duplicate_format,
+            format_for_file: self.format_for_file.clone(),
+            format_for_stderr: self.format_for_stderr.clone(),
+            format_error_policy: self.format_error_policy,
+            o_max_line_length: self.o_max_line_length,
+            console_buffering: self.console_buffering,
+            stderr_prefix: self.stderr_prefix.clone(),
+            tee_to_stdout: self.tee_to_stdout,
+            error_file: self.error_file,
+            o_error_file_path: self.o_error_file_path.clone(),
+            flwb: self.flwb.clone(),
+            o_user_writer: o_user_writer.clone(),
+        };
+        let mut o_output = if specfile.is_file() {
+            OutputSpec::try_from_file(&specfile).unwrap_or_else(|e| {
+                eprintln!(
+                    "[flexi_logger] reading the output section of the log specification file \
+                     failed with {:?}",
+                    e
+                );
+                None
+            })
+        } else {
+            None
+        };
+        if o_output.is_some() {
+            // avoid creating a log file that is not used
+            self.log_target = match o_user_writer {
+                Some(user_writer) => LogTarget::Writer(Box::new(SharedWriter(user_writer))),
+                None => LogTarget::DevNull,
+            };
+        }
+        // the startup info must go to the output of the specfile, if there is one
+        let o_startup_info = self.o_startup_info.take();
+        let mut source = SpecFileSource::new(&specfile);
+        // no record must pass with the specification of the Logger
+        self.spec = source.initial_spec(&self.spec)?;
 
-        handle.synchronize_with_specfile(&specfile)?;
-
-        // Now that the file exists, we can canonicalize the path
-        let specfile = specfile.canonicalize().map_err(FlexiLoggerError::Io)?;
-
-        // Watch the parent folder of the specfile, using debounced events
-        let (tx, rx) = std::sync::mpsc::channel();
-        let debouncing_delay = std::time::Duration::from_millis(1000);
-        let mut watcher = watcher(tx, debouncing_delay)?;
-        watcher.watch(&specfile.parent().unwrap(), RecursiveMode::NonRecursive)?;
-
-        // in a separate thread, reread the specfile when it was updated
-        std::thread::Builder::new().spawn(move || {
-            let _watcher = watcher; // keep it alive!
-            loop {
-                match rx.recv() {
-                    Ok(debounced_event) => match debounced_event {
-                        DebouncedEvent::Create(ref path) | DebouncedEvent::Write(ref path) => {
-                            if path.canonicalize().unwrap() == specfile {
-                                match LogSpecification::try_from_file(&specfile) {
-                                    Ok(spec) => handle.set_new_spec(spec),
-                                    Err(e) => eprintln!(
-                                        "[flexi_logger] rereading the log specification file \
-                                         failed with {:?}, \
-                                         continuing with previous log specification",
-                                        e
-                                    ),
-                                }
+        // Make logging work, before caring for the output section
+        let handle = self.start()?;
+        if let Some(ref output) = o_output {
+            handle.replace_primary_writer(template.primary_writer(output)?);
+        }
+        if let Some(startup_info) = o_startup_info {
+            startup_info.log();
+        }
+
+        // reread the output section whenever the specfile was updated
+        let output_handle = handle.clone();
+        source.on_change(Box::new(move || {
+            match OutputSpec::try_from_file(&specfile) {
+                Ok(None) => {}
+                Ok(o_new_output) => {
+                    if o_new_output != o_output {
+                        match template.primary_writer(o_new_output.as_ref().unwrap()) {
+                            Ok(primary_writer) => {
+                                output_handle.replace_primary_writer(primary_writer);
+                                o_output = o_new_output;
                             }
+                            Err(e) => log::error!(
+                                target: INTERNAL_TARGET,
+                                "applying the output section \
+                                 failed with {:?}, \
+                                 continuing with previous output",
+                                e
+                            ),
                         }
-                        _event => {}
-                    },
-                    Err(e) => {
-                        eprintln!("[flexi_logger] error while watching the specfile: {:?}", e)
                     }
                 }
+                Err(e) => log::error!(
+                    target: INTERNAL_TARGET,
+                    "rereading the output section \
+                     failed with {:?}, \
+                     continuing with previous output",
+                    e
+                ),
             }
-        })?;
+        }));
+        spawn_watcher(Box::new(source), handle)
+    }
+
+    /// Consumes the Logger object and initializes `flexi_logger` in a way that
+    /// the log specification is taken from the given source, and is updated
+    /// whenever the source provides a new one.
+    ///
+    /// The source is asked for the initial specification before the logger is started,
+    /// and is then watched in a thread of its own;
+    /// see [`SpecSource`](trait.SpecSource.html) and its implementations
+    /// [`PollingSpecSource`](struct.PollingSpecSource.html), which allows backing the
+    /// log specification e.g. with a key in a configuration service, and
+    /// [`SpecFileSource`](struct.SpecFileSource.html), which works like
+    /// [`start_with_specfile()`](struct.Logger.html#method.start_with_specfile)
+    /// without support for the `[output]` section.
+    ///
+    /// Unlike `start_with_specfile()`, this method returns the `ReconfigurationHandle`;
+    /// note that a specification that is set with it is replaced with the next
+    /// specification that the source provides.
+    ///
+    /// # Errors
+    ///
+    /// If the source fails to provide the initial specification, the error is returned,
+    /// and the logger is not installed.
+    pub fn start_with_spec_source(
+        mut self,
+        mut source: Box<dyn SpecSource>,
+    ) -> Result<ReconfigurationHandle, FlexiLoggerError> {
+        let o_startup_info = self.o_startup_info.take();
+        self.spec = source.initial_spec(&self.spec)?;
 
-        Ok(())
+        let handle = self.start()?;
+        if let Some(startup_info) = o_startup_info {
+            startup_info.log();
+        }
+
+        spawn_watcher(source, handle.clone())?;
+        Ok(handle)
+    }
+}
+
+/// Describes the program for the record that is written at startup.
+///
+/// Used in [Logger::log_startup_info()](struct.Logger.html#method.log_startup_info).
+#[derive(Clone, Debug)]
+pub struct StartupInfo {
+    name: String,
+    version: String,
+    o_build_info: Option<String>,
+}
+impl StartupInfo {
+    /// Creates a `StartupInfo` with the program name and its version.
+    pub fn new<S: Into<String>, T: Into<String>>(name: S, version: T) -> StartupInfo {
+        StartupInfo {
+            name: name.into(),
+            version: version.into(),
+            o_build_info: None,
+        }
+    }
+
+    /// Adds arbitrary build information, like the git commit or the build time.
+    pub fn build_info<S: Into<String>>(mut self, build_info: S) -> StartupInfo {
+        self.o_build_info = Some(build_info.into());
+        self
+    }
+
+    // The record pretends to come from the program's root module, so that it can be
+    // filtered like the program's own records.
+    fn log(&self) {
+        let module = self.name.replace('-', "_");
+        let log = |args: std::fmt::Arguments| {
+            log::logger().log(
+                &log::Record::builder()
+                    .args(args)
+                    .level(log::Level::Info)
+                    .target(&module)
+                    .module_path(Some(&module))
+                    .build(),
+            );
+        };
+        match self.o_build_info {
+            Some(ref build_info) => log(format_args!(
+                "{} {} ({}) started",
+                self.name, self.version, build_info
+            )),
+            None => log(format_args!("{} {} started", self.name, self.version)),
+        }
     }
 }
 
 /// Criterion when to rotate the log file.
 ///
 /// Used in [Logger::rotate()](struct.Logger.html#method.rotate).
+#[derive(Copy, Clone)]
 pub enum Criterion {
     /// Rotate the log file when it exceeds the specified size in bytes.
     Size(u64),
@@ -687,6 +1978,14 @@ pub enum Age {
     /// Rotate the log file when the local clock has started a new second since the
     /// current file had been created.
     Second,
+    /// Rotate the log file daily, when the local clock reaches the given time of day
+    /// (hour, minute), e.g. `Age::At(0, 0)` for rotating at midnight.
+    ///
+    /// The hour must be in `0..24` and the minute in `0..60`.
+    /// If the time of day is skipped because of a daylight saving time transition,
+    /// the file is rotated when the clock has jumped over it;
+    /// if it occurs twice, the file is rotated at its first occurence.
+    At(u32, u32),
 }
 
 /// The naming convention for rotated log files.
@@ -694,6 +1993,7 @@ pub enum Age {
 /// With file rotation, the logs are written to a file with infix "_rCURRENT".
 /// When rotation happens, the CURRENT log file will be renamed to a file with
 /// another infix of the form `"_r..."`. `Naming` defines which other infix will be used.
+/// Only with `Naming::Dates`, the logs are written directly to files with the final name.
 ///
 /// Used in [Logger::rotate()](struct.Logger.html#method.rotate).
 #[derive(Copy, Clone)]
@@ -707,6 +2007,16 @@ pub enum Naming {
     /// File rotation closes the file, renames it
     /// to a file with a number-infix, and opens a new file with the rCURRENT infix.
     Numbers,
+    /// The output file uses the current date as infix, like in `my_prog_2024-06-10.log`,
+    /// and no _rCURRENT file is used.
+    ///
+    /// File rotation just closes the file and opens the file of the new day.
+    /// If the file of the current day exists already when the program starts,
+    /// the logs are appended to it.
+    ///
+    /// Can only be used with daily rotation at midnight, i.e. with `Criterion::Age(Age::Day)`
+    /// or `Criterion::Age(Age::At(0, 0))`.
+    Dates,
 }
 
 /// Defines the strategy for handling older log files.
@@ -735,7 +2045,138 @@ pub enum Cleanup {
     KeepLogAndZipFiles(usize, usize),
 }
 
+// The writer for the error file, see Logger::error_file().
+pub(crate) fn error_file_writer(
+    error_file: bool,
+    o_path: Option<&Path>,
+    flwb: &FileLogWriterBuilder,
+) -> Result<Option<Box<dyn LogWriter>>, FlexiLoggerError> {
+    if !error_file {
+        return Ok(None);
+    }
+    let flw = match o_path {
+        Some(path) => flwb.derive_path(path),
+        None => flwb.derive("error"),
+    }
+    .try_build()?;
+    Ok(Some(Box::new(LevelGatedWriter::new(
+        log::LevelFilter::Error,
+        Box::new(flw),
+    ))))
+}
+
+impl Logger {
+    fn choose_auto_timestamp_format(&mut self) {
+        if self.auto_timestamp {
+            self.format_for_stderr = if self.o_under_systemd.unwrap_or_else(is_under_systemd) {
+                formats::default_format.into()
+            } else {
+                formats::FormatBuilder::new().timestamp(true).build()
+            };
+        }
+    }
+}
+
+// Tells if the program runs as a systemd service, whose output goes to the journal.
+fn is_under_systemd() -> bool {
+    is_systemd_env(
+        std::env::var_os("JOURNAL_STREAM").as_deref(),
+        std::env::var_os("INVOCATION_ID").as_deref(),
+    )
+}
+
+// Tells from the values of the environment variables JOURNAL_STREAM and INVOCATION_ID,
+// which systemd sets for its services, if the program runs as a systemd service.
+fn is_systemd_env(
+    journal_stream: Option<&std::ffi::OsStr>,
+    invocation_id: Option<&std::ffi::OsStr>,
+) -> bool {
+    journal_stream.is_some_and(|value| !value.is_empty())
+        || invocation_id.is_some_and(|value| !value.is_empty())
+}
+
+// Chains a panic hook that logs the panic in front of the current hook.
+fn install_panic_hook() {
+    let previous_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        // a panic within flexi_logger, including one while logging a panic,
+        // must not end in a deadlock or an endless recursion
+        if !crate::flexi_logger::is_logging() {
+            let message = match info.payload().downcast_ref::<&str>() {
+                Some(s) => *s,
+                None => match info.payload().downcast_ref::<String>() {
+                    Some(s) => &s[..],
+                    None => "Box<dyn Any>",
+                },
+            };
+            let thread = std::thread::current();
+            let location = info
+                .location()
+                .map(|l| format!("{}:{}", l.file(), l.line()))
+                .unwrap_or_else(|| "<unknown>".to_string());
+            let backtrace = std::backtrace::Backtrace::capture();
+            if backtrace.status() == std::backtrace::BacktraceStatus::Captured {
+                log::error!(
+                    "thread '{}' panicked at '{}', {}\n{}",
+                    thread.name().unwrap_or("<unnamed>"),
+                    message,
+                    location,
+                    backtrace
+                );
+            } else {
+                log::error!(
+                    "thread '{}' panicked at '{}', {}",
+                    thread.name().unwrap_or("<unnamed>"),
+                    message,
+                    location
+                );
+            }
+            log::logger().flush();
+        }
+        previous_hook(info);
+    }));
+}
+
+// Wraps a registered writer and gates the records to it by an overriding maximum log level.
+struct LevelOverride {
+    writer: Box<dyn LogWriter>,
+    max_log_level: log::LevelFilter,
+}
+impl LogWriter for LevelOverride {
+    fn write(&self, now: &mut DeferredNow, record: &log::Record) -> std::io::Result<()> {
+        if record.level() <= self.max_log_level {
+            self.writer.write(now, record)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.max_log_level
+    }
+
+    fn dropped_count(&self) -> u64 {
+        self.writer.dropped_count()
+    }
+
+    fn shutdown(&self) {
+        self.writer.shutdown()
+    }
+
+    fn validate_logs(&self, expected: &[(&'static str, &'static str, &'static str)]) {
+        self.writer.validate_logs(expected)
+    }
+}
+
 /// Used to control which messages are to be duplicated to stderr, when log_to_file() is used.
+///
+/// See [Logger::duplicate_map()](struct.Logger.html#method.duplicate_map) for a more
+/// fine-grained control.
+#[derive(Copy, Clone)]
 pub enum Duplicate {
     /// No messages are duplicated.
     None,
@@ -752,3 +2193,167 @@ pub enum Duplicate {
     /// All messages are duplicated.
     All,
 }
+
+/// Where the messages of a level are duplicated to.
+///
+/// Used in [Logger::duplicate_map()](struct.Logger.html#method.duplicate_map).
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum DuplicateTarget {
+    /// The messages are not duplicated.
+    #[default]
+    None,
+    /// The messages are duplicated to stdout.
+    StdOut,
+    /// The messages are duplicated to stderr.
+    StdErr,
+}
+
+// The duplicate target per level; the index is the level's numeric value minus one.
+#[derive(Copy, Clone)]
+pub(crate) struct DuplicateMap([DuplicateTarget; 5]);
+impl DuplicateMap {
+    fn from_map(map: &HashMap<log::Level, DuplicateTarget>) -> DuplicateMap {
+        let mut targets = [DuplicateTarget::None; 5];
+        for (level, target) in map {
+            targets[*level as usize - 1] = *target;
+        }
+        DuplicateMap(targets)
+    }
+
+    pub(crate) fn target(&self, level: log::Level) -> DuplicateTarget {
+        self.0[level as usize - 1]
+    }
+}
+impl From<Duplicate> for DuplicateMap {
+    fn from(duplicate: Duplicate) -> DuplicateMap {
+        let max_level = match duplicate {
+            Duplicate::None => log::LevelFilter::Off,
+            Duplicate::Error => log::LevelFilter::Error,
+            Duplicate::Warn => log::LevelFilter::Warn,
+            Duplicate::Info => log::LevelFilter::Info,
+            Duplicate::Debug => log::LevelFilter::Debug,
+            Duplicate::Trace | Duplicate::All => log::LevelFilter::Trace,
+        };
+        let mut targets = [DuplicateTarget::None; 5];
+        for level in log::Level::iter().filter(|level| *level <= max_level) {
+            targets[level as usize - 1] = DuplicateTarget::StdErr;
+        }
+        DuplicateMap(targets)
+    }
+}
+
+/// Defines how the output to stdout and stderr is buffered.
+///
+/// Used in [Logger::console_buffering()](struct.Logger.html#method.console_buffering).
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum ConsoleBuffering {
+    /// Each log line is written as soon as it is complete.
+    ///
+    /// This is the default.
+    #[default]
+    Line,
+    /// The log lines are collected until they have at least the given size in bytes,
+    /// and are then written in one chunk.
+    ///
+    /// This is the most efficient mode, e.g. if the output is piped into another program,
+    /// but the log lines are only visible with a delay.
+    /// The collected lines are written when the logger is flushed, e.g. with
+    /// `log::logger().flush()`, so you should do that at the end of the program.
+    Block(usize),
+    /// Each log line is written and the stream is flushed immediately.
+    None,
+}
+
+/// Used to control which format function is used for the messages that are duplicated
+/// to stderr.
+///
+/// Used in [Logger::duplicate_format()](struct.Logger.html#method.duplicate_format).
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum DuplicateFormat {
+    /// The duplicates look like the lines in the log file, e.g. if both are to be
+    /// processed by the same tools.
+    MatchFile,
+    /// The duplicates are formatted with the format for stderr, e.g. for better
+    /// readability on the console.
+    #[default]
+    UseStderrFormat,
+}
+
+/// Defines how the logger reacts if writing to the log file fails,
+/// e.g. because the disk is full.
+///
+/// In any case, the failure is reported once to stderr, and again when writing works again;
+/// the writes in between fail silently.
+///
+/// Used in [Logger::write_error_policy()](struct.Logger.html#method.write_error_policy).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum WriteErrorPolicy {
+    /// The log lines that cannot be written are dropped; they are counted in
+    /// [`ReconfigurationHandle::dropped_count()`](struct.ReconfigurationHandle.html#method.dropped_count).
+    ///
+    /// This is the default.
+    Drop,
+    /// The log lines that cannot be written are written to stderr instead.
+    StdErr,
+}
+
+/// Defines how the logger reacts to recursive logging.
+///
+/// Used in [Logger::recursion_policy()](struct.Logger.html#method.recursion_policy).
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum RecursionPolicy {
+    /// The recursive record is written, before the record whose formatting caused it.
+    ///
+    /// This is the default.
+    #[default]
+    Allow,
+    /// The recursive record is dropped; it is counted in
+    /// [`ReconfigurationHandle::dropped_count()`](struct.ReconfigurationHandle.html#method.dropped_count).
+    Drop,
+    /// In debug builds, the logger panics, to reveal the recursive log call;
+    /// the panic is propagated through the format function of the outer record.
+    /// In release builds, the recursive record is written.
+    PanicInDebug,
+}
+
+/// Defines how the logger reacts if a format function returns an error.
+///
+/// Used in [Logger::format_error_policy()](struct.Logger.html#method.format_error_policy).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FormatErrorPolicy {
+    /// The error is reported to stderr and the log line is dropped.
+    ///
+    /// This is the default.
+    Drop,
+    /// The error is reported to stderr and the log line is written with
+    /// [`default_format`](fn.default_format.html) instead.
+    Fallback,
+    /// The logger panics.
+    ///
+    /// Useful for critical logging where a lost log line is not acceptable.
+    /// With this policy, panics of the format function are propagated, too,
+    /// rather than being replaced with a minimal format.
+    Panic,
+}
+
+#[cfg(test)]
+mod test {
+    use super::is_systemd_env;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn test_is_systemd_env() {
+        assert!(!is_systemd_env(None, None));
+        assert!(is_systemd_env(Some(OsStr::new("8:12345")), None));
+        assert!(is_systemd_env(
+            None,
+            Some(OsStr::new("a5b3c8d2e1f04a6b9c7d8e9f0a1b2c3d"))
+        ));
+        assert!(is_systemd_env(
+            Some(OsStr::new("8:12345")),
+            Some(OsStr::new("a5b3c8d2e1f04a6b9c7d8e9f0a1b2c3d"))
+        ));
+        // empty values are ignored
+        assert!(!is_systemd_env(Some(OsStr::new("")), Some(OsStr::new(""))));
+    }
+}
@@ -0,0 +1,64 @@
+use log::Record;
+
+/// Function type for [`Logger::map_record()`](struct.Logger.html#method.map_record).
+///
+/// Returns `None` if the record is to be dropped, or the record that is to be written instead.
+pub type MapRecordFunction = Box<dyn Fn(&Record) -> Option<OwnedRecord> + Send + Sync>;
+
+/// An owned copy of a log record, as it is used by
+/// [`Logger::map_record()`](struct.Logger.html#method.map_record).
+///
+/// Other than `log::Record`, which borrows its content from the logging call,
+/// this struct owns all its parts, so that they can be modified freely.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OwnedRecord {
+    /// The level of the record.
+    pub level: log::Level,
+    /// The target of the record.
+    pub target: String,
+    /// The module path of the record, if known.
+    pub module_path: Option<String>,
+    /// The source file of the record, if known.
+    pub file: Option<String>,
+    /// The line in the source file, if known.
+    pub line: Option<u32>,
+    /// The formatted message of the record.
+    pub message: String,
+    /// The key-value pairs of the record, with the values rendered as strings.
+    ///
+    /// Is only filled from the record, and only written, with feature `kv`.
+    pub key_values: Vec<(String, String)>,
+}
+
+impl OwnedRecord {
+    // Provides a log::Record with the content of this OwnedRecord to the given function.
+    pub(crate) fn with_record<F: FnOnce(&Record)>(&self, f: F) {
+        let mut builder = Record::builder();
+        builder
+            .level(self.level)
+            .target(&self.target)
+            .module_path(self.module_path.as_deref())
+            .file(self.file.as_deref())
+            .line(self.line);
+        #[cfg(feature = "kv")]
+        builder.key_values(&self.key_values);
+        f(&builder.args(format_args!("{}", self.message)).build())
+    }
+}
+
+impl<'a> From<&Record<'a>> for OwnedRecord {
+    fn from(record: &Record<'a>) -> OwnedRecord {
+        OwnedRecord {
+            level: record.level(),
+            target: record.target().to_owned(),
+            module_path: record.module_path().map(ToOwned::to_owned),
+            file: record.file().map(ToOwned::to_owned),
+            line: record.line(),
+            message: record.args().to_string(),
+            #[cfg(feature = "kv")]
+            key_values: crate::flexi_logger::collect_key_values(record),
+            #[cfg(not(feature = "kv"))]
+            key_values: Vec::new(),
+        }
+    }
+}
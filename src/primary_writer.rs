@@ -1,11 +1,17 @@
 use log::Record;
 use std::cell::RefCell;
 use std::io::Write;
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::sync::Mutex;
 
 use crate::deferred_now::DeferredNow;
-use crate::logger::Duplicate;
+use crate::formats::{self, default_format};
+use crate::logger::{
+    ConsoleBuffering, DuplicateMap, DuplicateTarget, FormatErrorPolicy, RecursionPolicy,
+};
 use crate::writers::LogWriter;
-use crate::FormatFunction;
+use crate::Format;
 
 // Writes either to stderr,
 // or to a file (with optional duplication to stderr),
@@ -19,26 +25,66 @@ pub(crate) enum PrimaryWriter {
 }
 impl PrimaryWriter {
     pub fn multi(
-        duplicate: Duplicate,
-        format_for_stderr: FormatFunction,
+        duplicate: DuplicateMap,
+        format_for_stderr: Format,
+        format_error_policy: FormatErrorPolicy,
+        o_max_line_length: Option<usize>,
+        console_buffering: ConsoleBuffering,
+        stderr_prefix: String,
         writers: Vec<Box<dyn LogWriter>>,
     ) -> PrimaryWriter {
         PrimaryWriter::MultiWriter(MultiWriter {
-            duplicate,
+            duplicates: Duplicates::new(duplicate, console_buffering, stderr_prefix),
             format_for_stderr,
+            format_error_policy,
+            o_max_line_length,
             writers,
         })
     }
-    pub fn stderr(format: FormatFunction) -> PrimaryWriter {
-        PrimaryWriter::StdErrWriter(StdErrWriter::new(format))
+    pub fn stderr(
+        format: Format,
+        format_error_policy: FormatErrorPolicy,
+        o_max_line_length: Option<usize>,
+        console_buffering: ConsoleBuffering,
+        prefix: String,
+    ) -> PrimaryWriter {
+        PrimaryWriter::StdErrWriter(StdErrWriter::new(
+            format,
+            format_error_policy,
+            o_max_line_length,
+            console_buffering,
+            prefix,
+        ))
     }
 
-    pub fn stdout(format: FormatFunction) -> PrimaryWriter {
-        PrimaryWriter::StdOutWriter(StdOutWriter::new(format))
+    pub fn stdout(
+        format: Format,
+        format_error_policy: FormatErrorPolicy,
+        o_max_line_length: Option<usize>,
+        console_buffering: ConsoleBuffering,
+    ) -> PrimaryWriter {
+        PrimaryWriter::StdOutWriter(StdOutWriter::new(
+            format,
+            format_error_policy,
+            o_max_line_length,
+            console_buffering,
+        ))
     }
 
-    pub fn black_hole(duplicate: Duplicate, format: FormatFunction) -> PrimaryWriter {
-        PrimaryWriter::BlackHole(BlackHoleWriter { duplicate, format })
+    pub fn black_hole(
+        duplicate: DuplicateMap,
+        format: Format,
+        format_error_policy: FormatErrorPolicy,
+        o_max_line_length: Option<usize>,
+        console_buffering: ConsoleBuffering,
+        stderr_prefix: String,
+    ) -> PrimaryWriter {
+        PrimaryWriter::BlackHole(BlackHoleWriter {
+            duplicates: Duplicates::new(duplicate, console_buffering, stderr_prefix),
+            format,
+            format_error_policy,
+            o_max_line_length,
+        })
     }
 
     // Write out a log line.
@@ -61,6 +107,24 @@ impl PrimaryWriter {
         }
     }
 
+    // Finish the output, e.g. at the end of the program.
+    pub fn shutdown(&self) {
+        match *self {
+            PrimaryWriter::MultiWriter(ref w) => w.shutdown(),
+            _ => {
+                self.flush().ok();
+            }
+        }
+    }
+
+    // The number of records that the writers have dropped.
+    pub fn dropped_count(&self) -> u64 {
+        match *self {
+            PrimaryWriter::MultiWriter(ref w) => w.dropped_count(),
+            _ => 0,
+        }
+    }
+
     pub fn validate_logs(&self, expected: &[(&'static str, &'static str, &'static str)]) {
         if let PrimaryWriter::MultiWriter(ref w) = *self {
             w.validate_logs(expected);
@@ -70,76 +134,235 @@ impl PrimaryWriter {
 
 // `StdErrWriter` writes logs to stderr.
 pub(crate) struct StdErrWriter {
-    format: FormatFunction,
+    format: Format,
+    format_error_policy: FormatErrorPolicy,
+    o_max_line_length: Option<usize>,
+    stderr: Console,
+    prefix: String,
 }
 
 impl StdErrWriter {
-    fn new(format: FormatFunction) -> StdErrWriter {
-        StdErrWriter { format }
+    fn new(
+        format: Format,
+        format_error_policy: FormatErrorPolicy,
+        o_max_line_length: Option<usize>,
+        console_buffering: ConsoleBuffering,
+        prefix: String,
+    ) -> StdErrWriter {
+        StdErrWriter {
+            format,
+            format_error_policy,
+            o_max_line_length,
+            stderr: Console::stderr(console_buffering),
+            prefix,
+        }
     }
     #[inline]
     fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
-        write_buffered(self.format, now, record, &mut std::io::stderr())
+        write_buffered(
+            &self.format,
+            self.format_error_policy,
+            self.o_max_line_length,
+            &self.prefix,
+            now,
+            record,
+            &self.stderr,
+        )
     }
 
     #[inline]
     fn flush(&self) -> std::io::Result<()> {
-        std::io::stderr().flush()
+        self.stderr.flush()
     }
 }
 
-// `StdOutWriter` writes logs to stderr.
+// `StdOutWriter` writes logs to stdout.
+//
+// Is also used as additional writer in the `MultiWriter`, see `Logger::tee_to_stdout()`.
 pub(crate) struct StdOutWriter {
-    format: FormatFunction,
+    format: Format,
+    format_error_policy: FormatErrorPolicy,
+    o_max_line_length: Option<usize>,
+    stdout: Console,
 }
 
 impl StdOutWriter {
-    fn new(format: FormatFunction) -> StdOutWriter {
-        StdOutWriter { format }
+    pub fn new(
+        format: Format,
+        format_error_policy: FormatErrorPolicy,
+        o_max_line_length: Option<usize>,
+        console_buffering: ConsoleBuffering,
+    ) -> StdOutWriter {
+        StdOutWriter {
+            format,
+            format_error_policy,
+            o_max_line_length,
+            stdout: Console::stdout(console_buffering),
+        }
     }
+}
+impl LogWriter for StdOutWriter {
     #[inline]
     fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
-        write_buffered(self.format, now, record, &mut std::io::stdout())
+        write_buffered(
+            &self.format,
+            self.format_error_policy,
+            self.o_max_line_length,
+            "",
+            now,
+            record,
+            &self.stdout,
+        )
     }
 
     #[inline]
     fn flush(&self) -> std::io::Result<()> {
-        std::io::stdout().flush()
+        self.stdout.flush()
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        log::LevelFilter::Trace
+    }
+
+    // What is written to stdout cannot be validated
+    fn validate_logs(&self, _expected: &[(&'static str, &'static str, &'static str)]) {}
+}
+
+// The consoles to which the `BlackHoleWriter` and the `MultiWriter` duplicate messages,
+// depending on their level.
+struct Duplicates {
+    map: DuplicateMap,
+    stderr_prefix: String,
+    stdout: Console,
+    stderr: Console,
+}
+impl Duplicates {
+    fn new(
+        map: DuplicateMap,
+        console_buffering: ConsoleBuffering,
+        stderr_prefix: String,
+    ) -> Duplicates {
+        Duplicates {
+            map,
+            stderr_prefix,
+            stdout: Console::stdout(console_buffering),
+            stderr: Console::stderr(console_buffering),
+        }
+    }
+
+    // Writes the record to the console that is configured for its level, if any.
+    fn write(
+        &self,
+        format_function: &Format,
+        format_error_policy: FormatErrorPolicy,
+        o_max_line_length: Option<usize>,
+        now: &mut DeferredNow,
+        record: &Record,
+    ) -> std::io::Result<()> {
+        let (console, prefix) = match self.map.target(record.level()) {
+            DuplicateTarget::None => return Ok(()),
+            DuplicateTarget::StdOut => (&self.stdout, ""),
+            DuplicateTarget::StdErr => (&self.stderr, self.stderr_prefix.as_str()),
+        };
+        write_buffered(
+            format_function,
+            format_error_policy,
+            o_max_line_length,
+            prefix,
+            now,
+            record,
+            console,
+        )
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        self.stdout.flush()?;
+        self.stderr.flush()
     }
 }
 
-// The `BlackHoleWriter` does not write any log, but can 'duplicate' messages to stderr.
+// The `BlackHoleWriter` does not write any log, but can 'duplicate' messages
+// to stderr or stdout.
 pub(crate) struct BlackHoleWriter {
-    duplicate: Duplicate,
-    format: FormatFunction,
+    duplicates: Duplicates,
+    format: Format,
+    format_error_policy: FormatErrorPolicy,
+    o_max_line_length: Option<usize>,
 }
 impl BlackHoleWriter {
     fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
-        let dupl = match self.duplicate {
-            Duplicate::Error => record.level() == log::Level::Error,
-            Duplicate::Warn => record.level() <= log::Level::Warn,
-            Duplicate::Info => record.level() <= log::Level::Info,
-            Duplicate::Debug => record.level() <= log::Level::Debug,
-            Duplicate::Trace | Duplicate::All => true,
-            Duplicate::None => false,
-        };
-        if dupl {
-            (self.format)(&mut std::io::stderr(), now, record)?;
-            std::io::stderr().write_all(b"\n")?;
+        self.duplicates.write(
+            &self.format,
+            self.format_error_policy,
+            self.o_max_line_length,
+            now,
+            record,
+        )
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        self.duplicates.flush()
+    }
+}
+
+// Forwards only the records up to the given level to another writer,
+// e.g. for Logger::error_file().
+pub(crate) struct LevelGatedWriter {
+    max_level: log::LevelFilter,
+    inner: Box<dyn LogWriter>,
+}
+impl LevelGatedWriter {
+    pub fn new(max_level: log::LevelFilter, inner: Box<dyn LogWriter>) -> LevelGatedWriter {
+        LevelGatedWriter { max_level, inner }
+    }
+}
+impl LogWriter for LevelGatedWriter {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        if record.level() <= self.max_level {
+            self.inner.write(now, record)
+        } else {
+            Ok(())
         }
-        Ok(())
     }
 
     fn flush(&self) -> std::io::Result<()> {
-        std::io::stderr().flush()
+        self.inner.flush()
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        std::cmp::min(self.max_level, self.inner.max_log_level())
+    }
+
+    fn dropped_count(&self) -> u64 {
+        self.inner.dropped_count()
+    }
+
+    fn shutdown(&self) {
+        self.inner.shutdown();
+    }
+
+    // validates only the expected records that pass the gate
+    fn validate_logs(&self, expected: &[(&'static str, &'static str, &'static str)]) {
+        let expected: Vec<_> = expected
+            .iter()
+            .filter(|(level, _, _)| {
+                level
+                    .parse::<log::Level>()
+                    .map_or(true, |level| level <= self.max_level)
+            })
+            .cloned()
+            .collect();
+        self.inner.validate_logs(&expected);
     }
 }
 
 // The `MultiWriter` writes logs to stderr or to a set of `Writer`s, and in the latter case
-// can duplicate messages to stderr.
+// can duplicate messages to stderr or stdout.
 pub(crate) struct MultiWriter {
-    duplicate: Duplicate,
-    format_for_stderr: FormatFunction,
+    duplicates: Duplicates,
+    format_for_stderr: Format,
+    format_error_policy: FormatErrorPolicy,
+    o_max_line_length: Option<usize>,
     writers: Vec<Box<dyn LogWriter>>,
 }
 impl LogWriter for MultiWriter {
@@ -150,19 +373,19 @@ impl LogWriter for MultiWriter {
     }
 
     fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
-        let dupl = match self.duplicate {
-            Duplicate::Error => record.level() == log::Level::Error,
-            Duplicate::Warn => record.level() <= log::Level::Warn,
-            Duplicate::Info => record.level() <= log::Level::Info,
-            Duplicate::Debug => record.level() <= log::Level::Debug,
-            Duplicate::Trace | Duplicate::All => true,
-            Duplicate::None => false,
-        };
-        if dupl {
-            write_buffered(self.format_for_stderr, now, record, &mut std::io::stderr())?;
-        }
+        self.duplicates.write(
+            &self.format_for_stderr,
+            self.format_error_policy,
+            self.o_max_line_length,
+            now,
+            record,
+        )?;
+        // log::max_level() can be higher than the level of some writers,
+        // e.g. with Logger::trace_file(), so each writer gets only the records it accepts
         for writer in &self.writers {
-            writer.write(now, record)?;
+            if record.level() <= writer.max_log_level() {
+                writer.write(now, record)?;
+            }
         }
         Ok(())
     }
@@ -180,32 +403,141 @@ impl LogWriter for MultiWriter {
         for writer in &self.writers {
             writer.flush()?;
         }
-        std::io::stderr().flush()
+        self.duplicates.flush()
+    }
+
+    fn dropped_count(&self) -> u64 {
+        self.writers.iter().map(|w| w.dropped_count()).sum()
+    }
+
+    fn shutdown(&self) {
+        for writer in &self.writers {
+            writer.shutdown();
+        }
+        self.duplicates.flush().ok();
+    }
+}
+
+// Writes to stdout or stderr, with the configured buffering.
+pub(crate) struct Console {
+    stream: ConsoleStream,
+    buffering: ConsoleBuffering,
+    // the collected lines with ConsoleBuffering::Block
+    block: Mutex<Vec<u8>>,
+}
+#[derive(Clone, Copy)]
+pub(crate) enum ConsoleStream {
+    StdOut,
+    StdErr,
+}
+impl Console {
+    fn stdout(buffering: ConsoleBuffering) -> Console {
+        Console::new(ConsoleStream::StdOut, buffering)
+    }
+
+    fn stderr(buffering: ConsoleBuffering) -> Console {
+        Console::new(ConsoleStream::StdErr, buffering)
+    }
+
+    fn new(stream: ConsoleStream, buffering: ConsoleBuffering) -> Console {
+        let capacity = match buffering {
+            ConsoleBuffering::Block(size) => size,
+            ConsoleBuffering::Line | ConsoleBuffering::None => 0,
+        };
+        Console {
+            stream,
+            buffering,
+            block: Mutex::new(Vec::with_capacity(capacity)),
+        }
+    }
+
+    fn write_all(&self, line: &[u8]) -> std::io::Result<()> {
+        match self.buffering {
+            ConsoleBuffering::Line => self.write_through(line),
+            ConsoleBuffering::None => {
+                self.write_through(line)?;
+                self.flush_stream()
+            }
+            ConsoleBuffering::Block(size) => {
+                let mut block = self.block.lock().unwrap();
+                block.extend_from_slice(line);
+                if block.len() >= size {
+                    let result = self.write_through(&block);
+                    block.clear();
+                    result
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        {
+            let mut block = self.block.lock().unwrap();
+            if !block.is_empty() {
+                let result = self.write_through(&block);
+                block.clear();
+                result?;
+            }
+        }
+        self.flush_stream()
+    }
+
+    fn write_through(&self, buf: &[u8]) -> std::io::Result<()> {
+        match self.stream {
+            ConsoleStream::StdOut => std::io::stdout().write_all(buf),
+            ConsoleStream::StdErr => std::io::stderr().write_all(buf),
+        }
+    }
+
+    fn flush_stream(&self) -> std::io::Result<()> {
+        match self.stream {
+            ConsoleStream::StdOut => std::io::stdout().flush(),
+            ConsoleStream::StdErr => std::io::stderr().flush(),
+        }
     }
 }
 
 // Use a thread-local buffer for writing to stderr
 fn write_buffered(
-    format_function: FormatFunction,
+    format_function: &Format,
+    format_error_policy: FormatErrorPolicy,
+    o_max_line_length: Option<usize>,
+    prefix: &str,
     now: &mut DeferredNow,
     record: &Record,
-    w: &mut dyn Write,
+    console: &Console,
 ) -> Result<(), std::io::Error> {
     let mut result: Result<(), std::io::Error> = Ok(());
+    formats::set_current_output(console as *const Console as usize, Some(console.stream));
 
     buffer_with(|tl_buf| match tl_buf.try_borrow_mut() {
         Ok(mut buffer) => {
-            (format_function)(&mut *buffer, now, record)
-                .unwrap_or_else(|e| write_err(ERR_FORMATTING, &e));
-            buffer
-                .write_all(b"\n")
-                .unwrap_or_else(|e| write_err(ERR_FORMATTING, &e));
-
-            result = w.write_all(&*buffer).map_err(|e| {
-                write_err(ERR_WRITING, &e);
-                e
-            });
+            // the line is truncated only after the prefix is added,
+            // so that the prefixed line does not exceed the maximum line length
+            if format_with_policy(
+                format_function,
+                format_error_policy,
+                None,
+                false,
+                &mut buffer,
+                now,
+                record,
+            ) {
+                prefix_lines(&mut buffer, prefix);
+                if let Some(max_line_length) = o_max_line_length {
+                    truncate_line(&mut buffer, 0, max_line_length);
+                }
+                buffer
+                    .write_all(b"\n")
+                    .unwrap_or_else(|e| write_err(ERR_FORMATTING, &e));
 
+                result = console.write_all(&buffer).map_err(|e| {
+                    write_err(ERR_WRITING, &e);
+                    e
+                });
+            }
             buffer.clear();
         }
         Err(_e) => {
@@ -213,22 +545,240 @@ fn write_buffered(
             // (e.g. log calls in Debug or Display implementations)
             // we print the inner calls, in chronological order, before finally the
             // outer most message is printed
+            if !on_recursive_logging() {
+                return;
+            }
             let mut tmp_buf = Vec::<u8>::with_capacity(200);
-            (format_function)(&mut tmp_buf, now, record)
-                .unwrap_or_else(|e| write_err(ERR_FORMATTING, &e));
-            tmp_buf
-                .write_all(b"\n")
-                .unwrap_or_else(|e| write_err(ERR_FORMATTING, &e));
+            if format_with_policy(
+                format_function,
+                format_error_policy,
+                None,
+                false,
+                &mut tmp_buf,
+                now,
+                record,
+            ) {
+                prefix_lines(&mut tmp_buf, prefix);
+                if let Some(max_line_length) = o_max_line_length {
+                    truncate_line(&mut tmp_buf, 0, max_line_length);
+                }
+                tmp_buf
+                    .write_all(b"\n")
+                    .unwrap_or_else(|e| write_err(ERR_FORMATTING, &e));
 
-            result = w.write_all(&tmp_buf).map_err(|e| {
-                write_err(ERR_WRITING, &e);
-                e
-            });
+                result = console.write_all(&tmp_buf).map_err(|e| {
+                    write_err(ERR_WRITING, &e);
+                    e
+                });
+            }
         }
     });
     result
 }
 
+// Formats the record into the buffer and applies the given policy if formatting fails.
+// Removes ANSI escape sequences, if the sink does not support them,
+// and truncates the formatted line if it exceeds the maximum line length.
+// Returns false if the record is to be dropped.
+pub(crate) fn format_with_policy(
+    format_function: &Format,
+    format_error_policy: FormatErrorPolicy,
+    o_max_line_length: Option<usize>,
+    strip_ansi: bool,
+    buffer: &mut Vec<u8>,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> bool {
+    let start = buffer.len();
+    let formatted = format_record(format_function, format_error_policy, buffer, now, record);
+    if formatted {
+        if strip_ansi {
+            strip_ansi_escapes(buffer, start);
+        }
+        if let Some(max_line_length) = o_max_line_length {
+            truncate_line(buffer, start, max_line_length);
+        }
+    }
+    formatted
+}
+
+fn format_record(
+    format_function: &Format,
+    format_error_policy: FormatErrorPolicy,
+    buffer: &mut Vec<u8>,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> bool {
+    let start = buffer.len();
+    let result = match catch_unwind(AssertUnwindSafe(|| {
+        format_function.write(buffer, now, record)
+    })) {
+        Ok(result) => result,
+        Err(panic) => {
+            if format_error_policy == FormatErrorPolicy::Panic || is_recursion_panic(&*panic) {
+                resume_unwind(panic);
+            }
+            buffer.truncate(start);
+            return format_after_panic(buffer, now, record);
+        }
+    };
+    match result {
+        Ok(()) => true,
+        Err(e) => match format_error_policy {
+            FormatErrorPolicy::Drop => {
+                write_err(ERR_FORMATTING, &e);
+                buffer.truncate(start);
+                false
+            }
+            FormatErrorPolicy::Fallback => {
+                write_err(ERR_FORMATTING, &e);
+                buffer.truncate(start);
+                match default_format(buffer, now, record) {
+                    Ok(()) => true,
+                    Err(e) => {
+                        write_err(ERR_FORMATTING, &e);
+                        buffer.truncate(start);
+                        false
+                    }
+                }
+            }
+            FormatErrorPolicy::Panic => panic!("[flexi_logger] formatting failed with {}", e),
+        },
+    }
+}
+
+// Is called if the format function panicked; writes the record with default_format,
+// or, if that panics too (e.g. in the Display implementation of an argument),
+// only its level and module path.
+fn format_after_panic(buffer: &mut Vec<u8>, now: &mut DeferredNow, record: &Record) -> bool {
+    static REPORTED: AtomicBool = AtomicBool::new(false);
+    if !REPORTED.swap(true, Ordering::Relaxed) {
+        eprintln!(
+            "[flexi_logger] the format function panicked, the log line is written \
+             with a minimal format; further panics are not reported"
+        );
+    }
+    let start = buffer.len();
+    match catch_unwind(AssertUnwindSafe(|| default_format(buffer, now, record))) {
+        Ok(Ok(())) => true,
+        _ => {
+            buffer.truncate(start);
+            write!(
+                buffer,
+                "{} [{}] <formatting the message panicked>",
+                record.level(),
+                record.module_path().unwrap_or("<unnamed>")
+            )
+            .is_ok()
+        }
+    }
+}
+
+// Removes the ANSI escape sequences (like the color codes that are produced by the
+// colored format functions) from the line that starts at `start`.
+pub(crate) fn strip_ansi_escapes(buffer: &mut Vec<u8>, start: usize) {
+    const ESC: u8 = 0x1b;
+    if !buffer[start..].contains(&ESC) {
+        return;
+    }
+    let mut write = start;
+    let mut read = start;
+    while read < buffer.len() {
+        if buffer[read] != ESC {
+            buffer[write] = buffer[read];
+            write += 1;
+            read += 1;
+        } else if buffer.get(read + 1) == Some(&b'[') {
+            // control sequence: parameter and intermediate bytes, then one final byte
+            read += 2;
+            while read < buffer.len() && !(0x40..=0x7e).contains(&buffer[read]) {
+                read += 1;
+            }
+            read += 1;
+        } else {
+            // other escape sequence: intermediate bytes, then one final byte
+            read += 1;
+            while read < buffer.len() && (0x20..=0x2f).contains(&buffer[read]) {
+                read += 1;
+            }
+            read += 1;
+        }
+    }
+    buffer.truncate(write);
+}
+
+const ELLIPSIS: &str = "…";
+
+// Prepends the prefix to each line of the formatted record.
+fn prefix_lines(buffer: &mut Vec<u8>, prefix: &str) {
+    if prefix.is_empty() {
+        return;
+    }
+    let mut prefixed = Vec::<u8>::with_capacity(buffer.len() + prefix.len());
+    for (idx, line) in buffer.split(|b| *b == b'\n').enumerate() {
+        if idx > 0 {
+            prefixed.push(b'\n');
+        }
+        prefixed.extend_from_slice(prefix.as_bytes());
+        prefixed.extend_from_slice(line);
+    }
+    *buffer = prefixed;
+}
+
+// Cuts the line that starts at `start` down to `max_line_length` bytes and appends an ellipsis;
+// a multibyte UTF-8 character is never split.
+fn truncate_line(buffer: &mut Vec<u8>, start: usize, max_line_length: usize) {
+    if buffer.len() - start <= max_line_length {
+        return;
+    }
+    let mut end = start + max_line_length;
+    // continuation bytes of a UTF-8 sequence have the bit pattern 10xxxxxx
+    while end > start && (buffer[end] & 0b1100_0000) == 0b1000_0000 {
+        end -= 1;
+    }
+    buffer.truncate(end);
+    buffer.extend_from_slice(ELLIPSIS.as_bytes());
+}
+
+// The reaction to recursive logging, see Logger::recursion_policy(),
+// as the discriminant of the RecursionPolicy.
+static RECURSION_POLICY: AtomicU8 = AtomicU8::new(RecursionPolicy::Allow as u8);
+// The number of recursive records that were dropped.
+static RECURSIVE_DROPPED: AtomicU64 = AtomicU64::new(0);
+
+const ERR_RECURSION: &str = "[flexi_logger] recursive logging, \
+                             a record was logged while another one was formatted";
+
+pub(crate) fn set_recursion_policy(policy: RecursionPolicy) {
+    RECURSION_POLICY.store(policy as u8, Ordering::Relaxed);
+}
+
+pub(crate) fn recursive_dropped() -> u64 {
+    RECURSIVE_DROPPED.load(Ordering::Relaxed)
+}
+
+// Is called by the writers when they detect recursive logging;
+// returns true if the recursive record is to be written.
+pub(crate) fn on_recursive_logging() -> bool {
+    let policy = RECURSION_POLICY.load(Ordering::Relaxed);
+    if policy == RecursionPolicy::Drop as u8 {
+        RECURSIVE_DROPPED.fetch_add(1, Ordering::Relaxed);
+        false
+    } else {
+        if policy == RecursionPolicy::PanicInDebug as u8 && cfg!(debug_assertions) {
+            panic!("{}", ERR_RECURSION);
+        }
+        true
+    }
+}
+
+// The panic of RecursionPolicy::PanicInDebug must not be caught by format_record().
+fn is_recursion_panic(panic: &(dyn std::any::Any + Send)) -> bool {
+    panic
+        .downcast_ref::<String>()
+        .is_some_and(|message| message == ERR_RECURSION)
+}
+
 pub(crate) fn buffer_with<F>(f: F)
 where
     F: FnOnce(&RefCell<Vec<u8>>) -> (),
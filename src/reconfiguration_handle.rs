@@ -1,12 +1,15 @@
-#[cfg(feature = "specfile")]
 use crate::flexi_error::FlexiLoggerError;
 
+use crate::flexi_logger::{LevelCounts, LoggingGuard};
 use crate::log_specification::LogSpecification;
 use crate::primary_writer::PrimaryWriter;
-use crate::writers::LogWriter;
+#[cfg(feature = "specfile")]
+use crate::specfile_output::OutputSpec;
+use crate::writers::{LogFileInfo, LogWriter};
 
-use std::borrow::Borrow;
+use arc_swap::ArcSwap;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 
 /// Allows reconfiguring the logger programmatically.
@@ -54,98 +57,155 @@ use std::sync::{Arc, RwLock};
 /// // Continue with the log spec you had before.
 /// // ...
 /// ```
-
+///
+/// # Lifetime
+///
+/// The handle is only needed for reconfiguring the logger;
+/// dropping it (e.g. with `Logger::with_str("info").start().unwrap();`) does not stop logging.
+/// The writers are shared with the logger that is installed in the `log` crate
+/// and remain alive as long as the program runs.
+/// Use [`forget()`](struct.ReconfigurationHandle.html#method.forget) to make it explicit
+/// that the handle is not needed.
+/// Dropping the handle also does not flush the writers; use
+/// [`flush_guard()`](struct.ReconfigurationHandle.html#method.flush_guard) for that.
+///
+/// # Sharing
+///
+/// The handle can be cloned, e.g. to give each subsystem of a multi-threaded program
+/// its own handle:
+///
+/// ```rust
+/// # use flexi_logger::Logger;
+/// let log_handle = Logger::with_str("info")
+///     .start()
+///     .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+///
+/// let mut subsystem_handle = log_handle.clone();
+/// std::thread::spawn(move || {
+///     subsystem_handle.parse_new_spec("info, my_subsystem = debug");
+/// })
+/// .join()
+/// .unwrap();
+/// assert_eq!(log_handle.max_level(), log::LevelFilter::Debug);
+/// ```
+///
+/// All clones act on the same logger, so a change of the log specification that is done
+/// with one of them applies to all of them.
+/// Concurrent calls of e.g. `set_new_spec()` are serialized by a lock,
+/// and the specification that is set last wins.
+/// The stack of temporary specifications (see
+/// [`push_temp_spec()`](struct.ReconfigurationHandle.html#method.push_temp_spec))
+/// is however kept per handle, and a clone starts with a copy of the stack of its original.
+#[derive(Clone)]
 pub struct ReconfigurationHandle {
     spec: Arc<RwLock<LogSpecification>>,
     spec_stack: Vec<LogSpecification>,
-    primary_writer: Arc<PrimaryWriter>,
-    other_writers: Arc<HashMap<String, Box<dyn LogWriter>>>,
+    primary_writer: Arc<ArcSwap<PrimaryWriter>>,
+    other_writers: Arc<RwLock<HashMap<String, Box<dyn LogWriter>>>>,
+    o_trace_writer: Option<Arc<dyn LogWriter>>,
+    o_level_counts: Option<Arc<LevelCounts>>,
+    internal_level: log::LevelFilter,
+    paused: Arc<AtomicBool>,
+    dropped: Arc<AtomicU64>,
+    o_log_file_info: Option<LogFileInfo>,
 }
+// The shared state is only changed under locks or by swapping it as a whole,
+// so a panic that unwinds through code using the handle cannot leave it inconsistent.
+impl std::panic::UnwindSafe for ReconfigurationHandle {}
+impl std::panic::RefUnwindSafe for ReconfigurationHandle {}
 impl ReconfigurationHandle {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         spec: Arc<RwLock<LogSpecification>>,
-        primary_writer: Arc<PrimaryWriter>,
-        other_writers: Arc<HashMap<String, Box<dyn LogWriter>>>,
+        primary_writer: Arc<ArcSwap<PrimaryWriter>>,
+        other_writers: Arc<RwLock<HashMap<String, Box<dyn LogWriter>>>>,
+        o_trace_writer: Option<Arc<dyn LogWriter>>,
+        o_level_counts: Option<Arc<LevelCounts>>,
+        internal_level: log::LevelFilter,
+        paused: Arc<AtomicBool>,
+        dropped: Arc<AtomicU64>,
+        o_log_file_info: Option<LogFileInfo>,
     ) -> ReconfigurationHandle {
         ReconfigurationHandle {
             spec,
             spec_stack: Default::default(),
             primary_writer,
             other_writers,
+            o_trace_writer,
+            o_level_counts,
+            internal_level,
+            paused,
+            dropped,
+            o_log_file_info,
         }
     }
 
+    // Replaces the primary writer, e.g. when the output section of the specfile was changed.
     #[cfg(feature = "specfile")]
-    pub(crate) fn current_spec(&self) -> Arc<RwLock<LogSpecification>> {
-        Arc::clone(&self.spec)
+    pub(crate) fn replace_primary_writer(&self, primary_writer: PrimaryWriter) {
+        let _guard = LoggingGuard::new();
+        let old_primary_writer = self.primary_writer.swap(Arc::new(primary_writer));
+        old_primary_writer.flush().unwrap_or_else(|e| {
+            eprintln!("[flexi_logger] flushing primary writer failed with {}", e);
+        });
     }
 
-    // If the specfile exists, read the file and update the log_spec from it;
-    // otherwise try to create the file, with the current spec as content, under the specified name.
+    /// Writes the active LogSpecification in toml format to the given file,
+    /// e.g. to persist changes that were done with
+    /// [`set_new_spec()`](struct.ReconfigurationHandle.html#method.set_new_spec)
+    /// to the specfile (see
+    /// [`Logger::start_with_specfile()`](struct.Logger.html#method.start_with_specfile)).
+    ///
+    /// Only files with suffix `toml` are supported. An existing file is overwritten,
+    /// but its `[output]` section is kept.
+    /// What is lost of the specification is described in
+    /// [`LogSpecification::to_toml()`](struct.LogSpecification.html#method.to_toml).
+    ///
+    /// This method is only avaible with feature `specfile`.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Io` if the path has another suffix, or if reading or writing
+    /// the file fails, and `FlexiLoggerError::Toml` if an existing file cannot be parsed.
     #[cfg(feature = "specfile")]
-    pub(crate) fn synchronize_with_specfile(
-        &mut self,
-        specfile: &std::path::PathBuf,
-    ) -> Result<(), FlexiLoggerError> {
-        if specfile
-            .extension()
-            .unwrap_or_else(|| std::ffi::OsStr::new(""))
-            .to_str()
-            .unwrap_or("")
-            != "toml"
-        {
-            return Err(FlexiLoggerError::Parse(
-                vec!["[flexi_logger] only spec files with suffix toml are supported".to_owned()],
-                LogSpecification::off(),
-            ));
-        }
-
-        if std::path::Path::is_file(specfile) {
-            self.set_new_spec(LogSpecification::try_from_file(&specfile).map_err(|e| {
-                eprintln!(
-                    "[flexi_logger] reading the log specification file failed with {:?}",
-                    e
-                );
-                e
-            })?);
-            Ok(())
+    pub fn save_spec_to<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), FlexiLoggerError> {
+        let path = path.as_ref();
+        check_toml_suffix(path)?;
+        let o_output = if path.is_file() {
+            OutputSpec::try_from_file(path)?
         } else {
-            if let Some(specfolder) = specfile.parent() {
-                std::fs::DirBuilder::new()
-                    .recursive(true)
-                    .create(specfolder)
-                    .map_err(|e| {
-                        eprintln!(
-                            "[flexi_logger] cannot create the folder for the logspec file \
-                             under the specified name {:?}, caused by: {}",
-                            &specfile, e
-                        );
-                        e
-                    })?;
+            None
+        };
+        let mut buf = Vec::<u8>::with_capacity(512);
+        let spec = self.spec.read().unwrap();
+        match o_output {
+            Some(output) => {
+                spec.spec_to_toml(&mut buf)?;
+                output.to_toml(&mut buf)?;
             }
-
-            let mut file = std::fs::OpenOptions::new()
-                .write(true)
-                .create_new(true)
-                .open(specfile)
-                .map_err(|e| {
-                    eprintln!(
-                        "[flexi_logger] cannot create an initial logspec file \
-                         under the specified name {:?}, caused by: {}",
-                        &specfile, e
-                    );
-                    e
-                })?;
-            self.current_spec().read().unwrap().to_toml(&mut file)?;
-            Ok(())
+            None => spec.to_toml(&mut buf)?,
         }
+        std::fs::write(path, buf)?;
+        Ok(())
     }
 
     //
-    pub(crate) fn reconfigure(&self, mut max_level: log::LevelFilter) {
-        for w in self.other_writers.as_ref().values() {
+    pub(crate) fn reconfigure(&self, max_level: log::LevelFilter) {
+        self.reconfigure_with(max_level, &self.other_writers.read().unwrap());
+    }
+
+    fn reconfigure_with(
+        &self,
+        mut max_level: log::LevelFilter,
+        other_writers: &HashMap<String, Box<dyn LogWriter>>,
+    ) {
+        max_level = std::cmp::max(max_level, self.internal_level);
+        for w in other_writers.values() {
             max_level = std::cmp::max(max_level, w.max_log_level());
         }
+        if let Some(ref trace_writer) = self.o_trace_writer {
+            max_level = std::cmp::max(max_level, trace_writer.max_log_level());
+        }
         log::set_max_level(max_level);
     }
 
@@ -156,6 +216,36 @@ impl ReconfigurationHandle {
         self.reconfigure(max_level);
     }
 
+    /// Replaces the active LogSpecification, like
+    /// [`set_new_spec()`](struct.ReconfigurationHandle.html#method.set_new_spec),
+    /// but without waiting for the locks that are involved.
+    ///
+    /// If the active LogSpecification or the additional writers are currently locked,
+    /// e.g. by a logging thread that checks whether a record is enabled,
+    /// or by a concurrent reconfiguration, nothing is changed, and the given specification
+    /// is handed back as error, so that you can retry later.
+    /// The method thus never blocks, and also cannot deadlock if it is called
+    /// from within a logging context, e.g. from a `LogWriter`.
+    ///
+    /// # Errors
+    ///
+    /// The given specification, if a lock could not be acquired immediately.
+    pub fn try_set_new_spec(&mut self, new_spec: LogSpecification) -> Result<(), LogSpecification> {
+        let other_writers = match self.other_writers.try_read() {
+            Ok(other_writers) => other_writers,
+            Err(_) => return Err(new_spec),
+        };
+        let mut spec = match self.spec.try_write() {
+            Ok(spec) => spec,
+            Err(_) => return Err(new_spec),
+        };
+        let max_level = new_spec.max_level();
+        spec.update_from(new_spec);
+        drop(spec);
+        self.reconfigure_with(max_level, &other_writers);
+        Ok(())
+    }
+
     /// Tries to replace the active LogSpecification with the result from parsing the given String.
     pub fn parse_new_spec(&mut self, spec: &str) {
         self.set_new_spec(LogSpecification::parse(spec).unwrap_or_else(|e| {
@@ -167,6 +257,38 @@ impl ReconfigurationHandle {
         }))
     }
 
+    /// Replaces the active LogSpecification with the one derived from the current value
+    /// of the environment variable `RUST_LOG` (see
+    /// [`LogSpecification::env()`](struct.LogSpecification.html#method.env)).
+    ///
+    /// If `RUST_LOG` is not set, logging is switched off, like with `Logger::with_env()`.
+    /// If `RUST_LOG` cannot be parsed, the active LogSpecification is kept
+    /// and the error is returned.
+    ///
+    /// `flexi_logger` does not install signal handlers; if you want to trigger the reload
+    /// e.g. with `SIGUSR1`, let your own signal handling call this method.
+    /// With the `signal-hook` crate, this could look like:
+    ///
+    /// ```ignore
+    /// let signals = signal_hook::iterator::Signals::new(&[signal_hook::SIGUSR1])?;
+    /// std::thread::spawn(move || {
+    ///     for _ in signals.forever() {
+    ///         log_handle.reload_from_env().ok();
+    ///     }
+    /// });
+    /// ```
+    pub fn reload_from_env(&mut self) -> Result<(), FlexiLoggerError> {
+        let new_spec = LogSpecification::env().map_err(|e| {
+            eprintln!(
+                "[flexi_logger] ReconfigurationHandle::reload_from_env(): failed with {}",
+                e
+            );
+            e
+        })?;
+        self.set_new_spec(new_spec);
+        Ok(())
+    }
+
     /// Replaces the active LogSpecification and pushes the previous one to a Stack.
     pub fn push_temp_spec(&mut self, new_spec: LogSpecification) {
         self.spec_stack
@@ -196,9 +318,276 @@ impl ReconfigurationHandle {
         }
     }
 
+    /// Returns the maximum level of the active LogSpecification.
+    pub fn max_level(&self) -> log::LevelFilter {
+        self.spec.read().unwrap(/* catch and expose error? */).max_level()
+    }
+
+    /// Returns a copy of the active LogSpecification,
+    /// e.g. for showing it in an admin endpoint after it was changed with
+    /// [`set_new_spec()`](struct.ReconfigurationHandle.html#method.set_new_spec).
+    pub fn current_spec(&self) -> LogSpecification {
+        self.spec.read().unwrap(/* catch and expose error? */).clone()
+    }
+
+    /// Returns the names of the writers that were registered with
+    /// [`Logger::add_writer()`](struct.Logger.html#method.add_writer), in alphabetical order.
+    pub fn writer_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.other_writers.read().unwrap().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Replaces the writers that were registered with
+    /// [`Logger::add_writer()`](struct.Logger.html#method.add_writer) with the given ones,
+    /// and returns the previous writers, after flushing them.
+    ///
+    /// This allows reconfiguring the output destinations at runtime, e.g. switching from
+    /// a file to a network writer. Log calls that address a writer name that is not
+    /// contained in the new set are reported as bad writer spec.
+    ///
+    /// ```rust
+    /// # use flexi_logger::{Logger, writers::{FileLogWriter, LogWriter}};
+    /// # use std::collections::HashMap;
+    /// let mut handle = Logger::with_str("info").start().unwrap();
+    /// let mut writers = HashMap::<String, Box<dyn LogWriter>>::new();
+    /// writers.insert(
+    ///     "Sec".to_string(),
+    ///     Box::new(
+    ///         FileLogWriter::builder()
+    ///             .discriminant("Security")
+    /// #           .directory("log_files/replace_writers")
+    ///             .try_build()
+    ///             .unwrap(),
+    ///     ),
+    /// );
+    /// let old_writers = handle.replace_writers(writers);
+    /// assert!(old_writers.is_empty());
+    /// assert_eq!(handle.writer_names(), vec!["Sec"]);
+    /// ```
+    pub fn replace_writers(
+        &mut self,
+        writers: HashMap<String, Box<dyn LogWriter>>,
+    ) -> HashMap<String, Box<dyn LogWriter>> {
+        let old_writers = {
+            let _guard = LoggingGuard::new();
+            let mut guard = self.other_writers.write().unwrap();
+            for writer in guard.values() {
+                writer.flush().unwrap_or_else(|e| {
+                    eprintln!("[flexi_logger] flushing custom writer failed with {}", e);
+                });
+            }
+            std::mem::replace(&mut *guard, writers)
+        };
+        self.reconfigure(self.max_level());
+        old_writers
+    }
+
+    /// Gives up the handle, while logging continues unchanged.
+    ///
+    /// The active configuration stays in place for the rest of the program,
+    /// but it cannot be changed programmatically anymore (unless a clone of the handle exists).
+    /// This is what happens anyway when the handle is dropped;
+    /// `forget()` only documents the intention in the code.
+    ///
+    /// ```rust
+    /// # use flexi_logger::Logger;
+    /// Logger::with_str("info")
+    ///     .start()
+    ///     .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e))
+    ///     .forget();
+    ///
+    /// log::info!("logging continues");
+    /// ```
+    pub fn forget(self) {}
+
+    /// Finishes the output of all writers, e.g. by writing buffered records and
+    /// the end marker of log files (see
+    /// [`FileLogWriterBuilder::end_marker()`](writers/struct.FileLogWriterBuilder.html#method.end_marker)).
+    ///
+    /// Call this at the end of the program; log files are closed, so that later
+    /// records cannot be written to them anymore.
+    pub fn shutdown(&self) {
+        let _guard = LoggingGuard::new();
+        self.primary_writer.load().shutdown();
+        for writer in self.other_writers.read().unwrap().values() {
+            writer.shutdown();
+        }
+        if let Some(ref trace_writer) = self.o_trace_writer {
+            trace_writer.shutdown();
+        }
+    }
+
+    /// Flushes all writers, so that all records that were logged so far are written out.
+    pub fn flush(&self) {
+        let _guard = LoggingGuard::new();
+        // don't panic on poisoned locks, we might be called while unwinding a panic
+        self.primary_writer.load().flush().unwrap_or_else(|e| {
+            eprintln!("[flexi_logger] flushing primary writer failed with {}", e);
+        });
+        if let Ok(other_writers) = self.other_writers.read() {
+            for writer in other_writers.values() {
+                writer.flush().unwrap_or_else(|e| {
+                    eprintln!("[flexi_logger] flushing custom writer failed with {}", e);
+                });
+            }
+        }
+        if let Some(ref trace_writer) = self.o_trace_writer {
+            trace_writer.flush().unwrap_or_else(|e| {
+                eprintln!("[flexi_logger] flushing trace file failed with {}", e);
+            });
+        }
+    }
+
+    /// Returns a [`LoggerGuard`](struct.LoggerGuard.html) that flushes all writers when it is
+    /// dropped.
+    ///
+    /// Keep the guard in a variable at the top of `main`, so that buffered records are
+    /// written out when `main` returns, and also when it is left by an unwinding panic:
+    ///
+    /// ```rust
+    /// # use flexi_logger::Logger;
+    /// let _guard = Logger::with_str("info")
+    ///     .log_to_file()
+    /// #   .directory("log_files/flush_guard")
+    ///     .start()
+    ///     .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e))
+    ///     .flush_guard();
+    /// ```
+    ///
+    /// Note that a panic with `panic = "abort"`, or a call to `std::process::exit()`,
+    /// does not run any destructors.
+    pub fn flush_guard(&self) -> LoggerGuard {
+        LoggerGuard {
+            handle: self.clone(),
+        }
+    }
+
+    /// Describes where the log files are written, if the logger writes to files.
+    ///
+    /// The info is taken when the logger is started, e.g. for printing the log file location
+    /// at program start.
+    pub fn log_file_info(&self) -> Option<&LogFileInfo> {
+        self.o_log_file_info.as_ref()
+    }
+
+    /// Suppresses all log output until `resume()` is called.
+    ///
+    /// While the logger is paused, all log records are dropped, independent of the
+    /// log specification, which stays unchanged.
+    /// This is e.g. useful to keep the logs from interfering with an interactive prompt.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    /// Resumes logging after a call to `pause()`.
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Relaxed);
+    }
+
+    /// Returns the number of log lines that were written so far,
+    /// per level (error, warn, info, debug, trace).
+    ///
+    /// Only available if [`Logger::count_levels()`](struct.Logger.html#method.count_levels)
+    /// was used, otherwise all counts are zero.
+    pub fn level_counts(&self) -> [u64; 5] {
+        self.o_level_counts
+            .as_ref()
+            .map(|level_counts| level_counts.get())
+            .unwrap_or_default()
+    }
+
+    /// Returns the number of records that were dropped so far, because of
+    /// [`Logger::sample()`](struct.Logger.html#method.sample) or
+    /// [`Logger::max_records()`](struct.Logger.html#method.max_records),
+    /// or [`RecursionPolicy::Drop`](enum.RecursionPolicy.html#variant.Drop),
+    /// or by writers that can lose records, e.g. because their queue was full
+    /// or sending failed (see
+    /// [`LogWriter::dropped_count()`](writers/trait.LogWriter.html#method.dropped_count)).
+    pub fn dropped_count(&self) -> u64 {
+        let _guard = LoggingGuard::new();
+        self.dropped.load(Ordering::Relaxed)
+            + crate::primary_writer::recursive_dropped()
+            + self.primary_writer.load().dropped_count()
+            + self
+                .other_writers
+                .read()
+                .unwrap()
+                .values()
+                .map(|writer| writer.dropped_count())
+                .sum::<u64>()
+            + self
+                .o_trace_writer
+                .as_ref()
+                .map_or(0, |trace_writer| trace_writer.dropped_count())
+    }
+
+    /// Writes an info message with the number of errors and warnings that were
+    /// written so far, like `completed with 3 errors, 12 warnings`, and returns
+    /// the counts per level (see
+    /// [`level_counts()`](struct.ReconfigurationHandle.html#method.level_counts)).
+    ///
+    /// The message is not included in the counts.
+    pub fn summary(&self) -> [u64; 5] {
+        let counts = self.level_counts();
+        log::info!(
+            "completed with {} error{}, {} warning{}",
+            counts[0],
+            plural_s(counts[0]),
+            counts[1],
+            plural_s(counts[1])
+        );
+        counts
+    }
+
     // Allows checking the logs written so far to the writer
     #[doc(hidden)]
     pub fn validate_logs(&self, expected: &[(&'static str, &'static str, &'static str)]) {
-        Borrow::<PrimaryWriter>::borrow(&self.primary_writer).validate_logs(expected)
+        let _guard = LoggingGuard::new();
+        self.primary_writer.load().validate_logs(expected)
+    }
+}
+
+fn plural_s(count: u64) -> &'static str {
+    if count == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
+
+/// Flushes all writers of the logger when it is dropped.
+///
+/// Is obtained with
+/// [`ReconfigurationHandle::flush_guard()`](struct.ReconfigurationHandle.html#method.flush_guard).
+pub struct LoggerGuard {
+    handle: ReconfigurationHandle,
+}
+impl LoggerGuard {
+    /// Gives access to the `ReconfigurationHandle` of the logger.
+    pub fn handle(&self) -> &ReconfigurationHandle {
+        &self.handle
+    }
+}
+impl Drop for LoggerGuard {
+    fn drop(&mut self) {
+        self.handle.flush();
+    }
+}
+
+#[cfg(feature = "specfile")]
+pub(crate) fn check_toml_suffix(path: &std::path::Path) -> Result<(), FlexiLoggerError> {
+    if path
+        .extension()
+        .unwrap_or_else(|| std::ffi::OsStr::new(""))
+        .to_str()
+        .unwrap_or("")
+        != "toml"
+    {
+        return Err(FlexiLoggerError::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "only spec files with suffix toml are supported",
+        )));
     }
+    Ok(())
 }
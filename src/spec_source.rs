@@ -0,0 +1,318 @@
+use crate::flexi_error::FlexiLoggerError;
+use crate::flexi_logger::INTERNAL_TARGET;
+use crate::log_specification::LogSpecification;
+use crate::reconfiguration_handle::ReconfigurationHandle;
+#[cfg(feature = "specfile")]
+use notify::{watcher, DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+#[cfg(feature = "specfile")]
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+/// A source of log specifications that can change while the program is running.
+///
+/// Used in [`Logger::start_with_spec_source()`](struct.Logger.html#method.start_with_spec_source).
+///
+/// `flexi_logger` provides a [`SpecFileSource`](struct.SpecFileSource.html)
+/// (with feature `specfile`) and a [`PollingSpecSource`](struct.PollingSpecSource.html),
+/// which can e.g. read the specification from a key in a configuration service.
+/// Other sources, e.g. such that subscribe to changes, can implement this trait directly.
+pub trait SpecSource: Send {
+    /// Is called once when the logger is started, and returns the specification
+    /// that is to be used initially.
+    ///
+    /// `logger_spec` is the specification that was given to the `Logger`.
+    ///
+    /// Sources that subscribe to changes should do so here already, so that no change
+    /// is missed until `watch()` is running.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned from `Logger::start_with_spec_source()`,
+    /// and the logger is not installed.
+    fn initial_spec(
+        &mut self,
+        logger_spec: &LogSpecification,
+    ) -> Result<LogSpecification, FlexiLoggerError>;
+
+    /// Is called in a thread of its own after `initial_spec()`;
+    /// waits for changes and hands every new specification to `update`.
+    ///
+    /// Should only return when no further changes can be detected.
+    ///
+    /// # Errors
+    ///
+    /// An error is logged with the internal log level
+    /// (see [`Logger::internal_log_level()`](struct.Logger.html#method.internal_log_level)).
+    fn watch(
+        self: Box<Self>,
+        update: &mut dyn FnMut(LogSpecification),
+    ) -> Result<(), FlexiLoggerError>;
+}
+
+// Lets the source update the handle's spec, in a thread of its own.
+pub(crate) fn spawn_watcher(
+    source: Box<dyn SpecSource>,
+    mut handle: ReconfigurationHandle,
+) -> Result<(), FlexiLoggerError> {
+    std::thread::Builder::new().spawn(move || {
+        if let Err(e) = source.watch(&mut |spec| handle.set_new_spec(spec)) {
+            log::error!(
+                target: INTERNAL_TARGET,
+                "watching the source of the log specification failed with {:?}",
+                e
+            );
+        }
+    })?;
+    Ok(())
+}
+
+/// A [`SpecSource`](trait.SpecSource.html) that regularly calls a closure
+/// that provides the log specification as String.
+///
+/// The closure returns `None` if the specification is currently not available,
+/// e.g. because a remote configuration service cannot be reached;
+/// the active specification then remains valid.
+/// A string that cannot be parsed is reported and also ignored;
+/// at startup, the specification of the `Logger` is used instead.
+///
+/// The polling thread runs until the program ends.
+///
+/// ## Example
+///
+/// ```rust
+/// use flexi_logger::{Logger, PollingSpecSource};
+/// use std::time::Duration;
+///
+/// Logger::with_str("info")
+///     .start_with_spec_source(Box::new(PollingSpecSource::new(
+///         Duration::from_secs(10),
+///         || std::env::var("MY_LOG_SPEC").ok(), // or ask your configuration service
+///     )))
+///     .unwrap();
+/// ```
+pub struct PollingSpecSource<F>
+where
+    F: FnMut() -> Option<String> + Send,
+{
+    interval: Duration,
+    provider: F,
+    o_last: Option<String>,
+}
+impl<F> PollingSpecSource<F>
+where
+    F: FnMut() -> Option<String> + Send,
+{
+    /// Creates a `PollingSpecSource` that calls `provider` at startup
+    /// and then with the given interval.
+    pub fn new(interval: Duration, provider: F) -> PollingSpecSource<F> {
+        PollingSpecSource {
+            interval,
+            provider,
+            o_last: None,
+        }
+    }
+}
+impl<F> SpecSource for PollingSpecSource<F>
+where
+    F: FnMut() -> Option<String> + Send,
+{
+    fn initial_spec(
+        &mut self,
+        logger_spec: &LogSpecification,
+    ) -> Result<LogSpecification, FlexiLoggerError> {
+        match (self.provider)() {
+            Some(s) => {
+                let spec = LogSpecification::parse(&s).unwrap_or_else(|e| {
+                    // the logger is not yet running
+                    eprintln!(
+                        "[flexi_logger] parsing the polled log specification failed with {}, \
+                         starting with the log specification of the Logger",
+                        e
+                    );
+                    logger_spec.clone()
+                });
+                self.o_last = Some(s);
+                Ok(spec)
+            }
+            None => Ok(logger_spec.clone()),
+        }
+    }
+
+    fn watch(
+        mut self: Box<Self>,
+        update: &mut dyn FnMut(LogSpecification),
+    ) -> Result<(), FlexiLoggerError> {
+        // polling never ends, like the logger itself
+        loop {
+            std::thread::sleep(self.interval);
+            if let Some(s) = (self.provider)() {
+                if self.o_last.as_ref() != Some(&s) {
+                    match LogSpecification::parse(&s) {
+                        Ok(spec) => update(spec),
+                        Err(e) => log::error!(
+                            target: INTERNAL_TARGET,
+                            "parsing the polled log specification failed with {}, \
+                             continuing with previous log specification",
+                            e
+                        ),
+                    }
+                    self.o_last = Some(s);
+                }
+            }
+        }
+    }
+}
+
+/// A [`SpecSource`](trait.SpecSource.html) that reads the log specification
+/// from a toml file and rereads it whenever the file is changed.
+///
+/// If the file does not exist, it is created with the specification of the `Logger`.
+/// See [`Logger::start_with_specfile()`](struct.Logger.html#method.start_with_specfile)
+/// for the file format;
+/// an `[output]` section is however only evaluated by `start_with_specfile()`.
+///
+/// Only available with feature `specfile`.
+#[cfg(feature = "specfile")]
+pub struct SpecFileSource {
+    specfile: std::path::PathBuf,
+    o_on_change: Option<Box<dyn FnMut() + Send>>,
+    // the file watcher, its events, and the canonicalized path of the specfile
+    o_watching: Option<(
+        RecommendedWatcher,
+        Receiver<DebouncedEvent>,
+        std::path::PathBuf,
+    )>,
+}
+#[cfg(feature = "specfile")]
+impl SpecFileSource {
+    /// Creates a `SpecFileSource` for the given file, which must have the suffix `toml`.
+    pub fn new<P: AsRef<std::path::Path>>(specfile: P) -> SpecFileSource {
+        SpecFileSource {
+            specfile: specfile.as_ref().to_owned(),
+            o_on_change: None,
+            o_watching: None,
+        }
+    }
+
+    // Registers a closure that is called whenever the file was changed,
+    // after the new log specification was handed over.
+    pub(crate) fn on_change(&mut self, on_change: Box<dyn FnMut() + Send>) {
+        self.o_on_change = Some(on_change);
+    }
+
+    // Starts watching the file, which must exist.
+    fn start_watching(&mut self) -> Result<(), FlexiLoggerError> {
+        // Now that the file exists, we can canonicalize the path
+        let specfile = self.specfile.canonicalize()?;
+
+        // Watch the parent folder of the specfile, using debounced events
+        let (tx, rx) = std::sync::mpsc::channel();
+        let debouncing_delay = Duration::from_millis(1000);
+        let mut watcher = watcher(tx, debouncing_delay)?;
+        watcher.watch(specfile.parent().unwrap(), RecursiveMode::NonRecursive)?;
+        self.o_watching = Some((watcher, rx, specfile));
+        Ok(())
+    }
+
+    fn read_or_create(
+        &self,
+        logger_spec: &LogSpecification,
+    ) -> Result<LogSpecification, FlexiLoggerError> {
+        let specfile = &self.specfile;
+        crate::reconfiguration_handle::check_toml_suffix(specfile)?;
+
+        if specfile.is_file() {
+            LogSpecification::try_from_file(specfile).map_err(|e| {
+                eprintln!(
+                    "[flexi_logger] reading the log specification file failed with {:?}",
+                    e
+                );
+                e
+            })
+        } else {
+            if let Some(specfolder) = specfile.parent() {
+                std::fs::DirBuilder::new()
+                    .recursive(true)
+                    .create(specfolder)
+                    .map_err(|e| {
+                        eprintln!(
+                            "[flexi_logger] cannot create the folder for the logspec file \
+                             under the specified name {:?}, caused by: {}",
+                            specfile, e
+                        );
+                        e
+                    })?;
+            }
+
+            let mut file = std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(specfile)
+                .map_err(|e| {
+                    eprintln!(
+                        "[flexi_logger] cannot create an initial logspec file \
+                         under the specified name {:?}, caused by: {}",
+                        specfile, e
+                    );
+                    e
+                })?;
+            logger_spec.to_toml(&mut file)?;
+            Ok(logger_spec.clone())
+        }
+    }
+}
+#[cfg(feature = "specfile")]
+impl SpecSource for SpecFileSource {
+    fn initial_spec(
+        &mut self,
+        logger_spec: &LogSpecification,
+    ) -> Result<LogSpecification, FlexiLoggerError> {
+        let spec = self.read_or_create(logger_spec)?;
+        self.start_watching()?;
+        Ok(spec)
+    }
+
+    fn watch(
+        mut self: Box<Self>,
+        update: &mut dyn FnMut(LogSpecification),
+    ) -> Result<(), FlexiLoggerError> {
+        if self.o_watching.is_none() {
+            self.start_watching()?;
+        }
+        let (_watcher, rx, specfile) = self.o_watching.take().unwrap(); // keep the watcher alive!
+
+        loop {
+            match rx.recv() {
+                Ok(DebouncedEvent::Create(ref path)) | Ok(DebouncedEvent::Write(ref path)) => {
+                    if path.canonicalize().ok().as_ref() == Some(&specfile) {
+                        log::debug!(
+                            target: INTERNAL_TARGET,
+                            "rereading the log specification file {}",
+                            specfile.display()
+                        );
+                        match LogSpecification::try_from_file(&specfile) {
+                            Ok(spec) => update(spec),
+                            Err(e) => log::error!(
+                                target: INTERNAL_TARGET,
+                                "rereading the log specification file \
+                                 failed with {:?}, \
+                                 continuing with previous log specification",
+                                e
+                            ),
+                        }
+                        if let Some(ref mut on_change) = self.o_on_change {
+                            on_change();
+                        }
+                    }
+                }
+                Ok(event) => log::trace!(target: INTERNAL_TARGET, "ignoring event {:?}", event),
+                Err(e) => {
+                    return Err(FlexiLoggerError::Io(std::io::Error::new(
+                        std::io::ErrorKind::Other,
+                        format!("error while watching the specfile: {:?}", e),
+                    )))
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use crate::flexi_error::FlexiLoggerError;
+use crate::formats;
+use crate::logger::{
+    error_file_writer, ConsoleBuffering, Duplicate, DuplicateFormat, DuplicateMap,
+    FormatErrorPolicy,
+};
+use crate::primary_writer::{PrimaryWriter, StdOutWriter};
+use crate::writers::{FileLogWriterBuilder, LogWriter};
+use crate::{DeferredNow, Format, FormatFunction};
+
+use log::Record;
+use serde_derive::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// The optional `[output]` section of the specfile.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub(crate) struct OutputSpec {
+    target: Option<String>,
+    directory: Option<PathBuf>,
+    format: Option<String>,
+}
+impl OutputSpec {
+    // Reads the `[output]` section from the toml document, if it has one.
+    pub fn from_toml(s: &str) -> Result<Option<OutputSpec>, FlexiLoggerError> {
+        #[derive(Deserialize)]
+        struct SpecFileOutputPart {
+            output: Option<OutputSpec>,
+        }
+        Ok(toml::from_str::<SpecFileOutputPart>(s)?.output)
+    }
+
+    pub fn try_from_file(specfile: &Path) -> Result<Option<OutputSpec>, FlexiLoggerError> {
+        OutputSpec::from_toml(&std::fs::read_to_string(specfile)?)
+    }
+
+    // Writes the `[output]` section, with the values that are set.
+    pub fn to_toml(&self, w: &mut dyn std::io::Write) -> Result<(), FlexiLoggerError> {
+        w.write_all(
+            b"\n### Optional: define where and how the log lines are written\n\
+            ### (target = 'stderr', 'stdout', 'file', or 'devnull')\n",
+        )?;
+        w.write_all(b"[output]\n")?;
+        let string = |s: &str| toml::Value::String(s.to_string());
+        if let Some(ref target) = self.target {
+            writeln!(w, "target = {}", string(target))?;
+        }
+        if let Some(ref directory) = self.directory {
+            writeln!(w, "directory = {}", string(&directory.to_string_lossy()))?;
+        }
+        if let Some(ref format) = self.format {
+            writeln!(w, "format = {}", string(format))?;
+        }
+        Ok(())
+    }
+}
+
+// The output-related settings of the Logger, from which the primary writer is (re)built
+// when the specfile contains an `[output]` section.
+pub(crate) struct OutputTemplate {
+    // used if the output spec has no target
+    pub default_target: &'static str,
+    pub duplicate: DuplicateMap,
+    pub duplicate_format: DuplicateFormat,
+    pub format_for_file: Format,
+    pub format_for_stderr: Format,
+    pub format_error_policy: FormatErrorPolicy,
+    pub o_max_line_length: Option<usize>,
+    pub console_buffering: ConsoleBuffering,
+    pub stderr_prefix: String,
+    pub tee_to_stdout: bool,
+    pub error_file: bool,
+    pub o_error_file_path: Option<PathBuf>,
+    pub flwb: FileLogWriterBuilder,
+    // the writer of LogTarget::Writer or LogTarget::FileAndWriter, which is kept
+    // in addition to the output of the output spec
+    pub o_user_writer: Option<Arc<dyn LogWriter>>,
+}
+impl OutputTemplate {
+    // The values that are not given in the output spec are taken from the Logger.
+    pub fn primary_writer(&self, output: &OutputSpec) -> Result<PrimaryWriter, FlexiLoggerError> {
+        let o_format = match output.format {
+            Some(ref name) => Some(Format::from(format_function(name)?)),
+            None => None,
+        };
+        let format_for_stderr = o_format
+            .clone()
+            .unwrap_or_else(|| self.format_for_stderr.clone());
+        let format_for_file = o_format.unwrap_or_else(|| self.format_for_file.clone());
+        let format_for_duplicates = match self.duplicate_format {
+            DuplicateFormat::MatchFile => format_for_file.clone(),
+            DuplicateFormat::UseStderrFormat => self.format_for_stderr.clone(),
+        };
+        let target = output
+            .target
+            .as_ref()
+            .map_or(self.default_target, String::as_str);
+        if let Some(ref user_writer) = self.o_user_writer {
+            let user_writer = Box::new(SharedWriter(Arc::clone(user_writer)));
+            return self.primary_writer_with(
+                target,
+                output,
+                format_for_stderr,
+                format_for_file,
+                format_for_duplicates,
+                user_writer,
+            );
+        }
+        match target {
+            "stderr" => Ok(PrimaryWriter::stderr(
+                format_for_stderr,
+                self.format_error_policy,
+                self.o_max_line_length,
+                self.console_buffering,
+                self.stderr_prefix.clone(),
+            )),
+            "stdout" => Ok(PrimaryWriter::stdout(
+                format_for_stderr,
+                self.format_error_policy,
+                self.o_max_line_length,
+                self.console_buffering,
+            )),
+            "file" => {
+                let writers = self.file_writers(output, format_for_file)?;
+                Ok(PrimaryWriter::multi(
+                    self.duplicate,
+                    format_for_duplicates,
+                    self.format_error_policy,
+                    self.o_max_line_length,
+                    self.console_buffering,
+                    self.stderr_prefix.clone(),
+                    writers,
+                ))
+            }
+            "devnull" => Ok(PrimaryWriter::black_hole(
+                self.duplicate,
+                format_for_duplicates,
+                self.format_error_policy,
+                self.o_max_line_length,
+                self.console_buffering,
+                self.stderr_prefix.clone(),
+            )),
+            target => Err(unknown_target(target)),
+        }
+    }
+
+    // Like primary_writer(), but always with a MultiWriter, which also writes to the user writer.
+    fn primary_writer_with(
+        &self,
+        target: &str,
+        output: &OutputSpec,
+        format_for_stderr: Format,
+        format_for_file: Format,
+        format_for_duplicates: Format,
+        user_writer: Box<dyn LogWriter>,
+    ) -> Result<PrimaryWriter, FlexiLoggerError> {
+        let (duplicate, format_for_duplicates, writers) = match target {
+            "stderr" => (
+                DuplicateMap::from(Duplicate::All),
+                format_for_stderr,
+                vec![user_writer],
+            ),
+            "stdout" => {
+                let stdout_writer: Box<dyn LogWriter> = Box::new(StdOutWriter::new(
+                    format_for_stderr,
+                    self.format_error_policy,
+                    self.o_max_line_length,
+                    self.console_buffering,
+                ));
+                (
+                    DuplicateMap::from(Duplicate::None),
+                    format_for_duplicates,
+                    vec![stdout_writer, user_writer],
+                )
+            }
+            "file" => {
+                let mut writers = self.file_writers(output, format_for_file)?;
+                writers.push(user_writer);
+                (self.duplicate, format_for_duplicates, writers)
+            }
+            "devnull" => (self.duplicate, format_for_duplicates, vec![user_writer]),
+            target => return Err(unknown_target(target)),
+        };
+        Ok(PrimaryWriter::multi(
+            duplicate,
+            format_for_duplicates,
+            self.format_error_policy,
+            self.o_max_line_length,
+            self.console_buffering,
+            self.stderr_prefix.clone(),
+            writers,
+        ))
+    }
+
+    // The file log writer, the error file writer with error_file,
+    // and the stdout writer with tee_to_stdout.
+    fn file_writers(
+        &self,
+        output: &OutputSpec,
+        format_for_file: Format,
+    ) -> Result<Vec<Box<dyn LogWriter>>, FlexiLoggerError> {
+        let mut flwb = self.flwb.clone().format(format_for_file.clone());
+        if let Some(ref directory) = output.directory {
+            flwb = flwb.directory(directory);
+        }
+        let o_error_file_writer =
+            error_file_writer(self.error_file, self.o_error_file_path.as_deref(), &flwb)?;
+        let mut writers: Vec<Box<dyn LogWriter>> = vec![Box::new(flwb.try_build()?)];
+        writers.extend(o_error_file_writer);
+        if self.tee_to_stdout {
+            writers.push(Box::new(StdOutWriter::new(
+                format_for_file,
+                self.format_error_policy,
+                self.o_max_line_length,
+                self.console_buffering,
+            )));
+        }
+        Ok(writers)
+    }
+}
+
+fn unknown_target(target: &str) -> FlexiLoggerError {
+    invalid_input(format!(
+        "unknown output target '{}', use 'stderr', 'stdout', 'file', or 'devnull'",
+        target
+    ))
+}
+
+// Gives the primary writers that are built from the output spec access to the
+// writer of LogTarget::Writer or LogTarget::FileAndWriter.
+pub(crate) struct SharedWriter(pub Arc<dyn LogWriter>);
+impl LogWriter for SharedWriter {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        self.0.write(now, record)
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.0.max_log_level()
+    }
+
+    fn dropped_count(&self) -> u64 {
+        self.0.dropped_count()
+    }
+
+    fn shutdown(&self) {
+        self.0.shutdown();
+    }
+
+    fn validate_logs(&self, expected: &[(&'static str, &'static str, &'static str)]) {
+        self.0.validate_logs(expected);
+    }
+}
+
+fn format_function(name: &str) -> Result<FormatFunction, FlexiLoggerError> {
+    match name {
+        "default" => Ok(formats::default_format),
+        "opt" => Ok(formats::opt_format),
+        "detailed" => Ok(formats::detailed_format),
+        "with_thread" => Ok(formats::with_thread),
+        "target_only" => Ok(formats::target_only_format),
+        #[cfg(feature = "kv")]
+        "json" => Ok(formats::ndjson_nested_format),
+        #[cfg(feature = "kv")]
+        "json_ecs" => Ok(formats::json_format_ecs),
+        #[cfg(feature = "kv")]
+        "default_json_kv" => Ok(formats::default_format_json_kv),
+        #[cfg(feature = "gelf")]
+        "gelf" => Ok(formats::gelf_format),
+        _ => Err(invalid_input(format!("unknown output format '{}'", name))),
+    }
+}
+
+fn invalid_input(msg: String) -> FlexiLoggerError {
+    FlexiLoggerError::Io(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg))
+}
@@ -0,0 +1,132 @@
+//! Supports testing the logging of applications.
+//!
+//! [`capture()`](fn.capture.html) installs a logger that writes the log lines
+//! into memory, and provides a function for reading them.
+//!
+//! ```rust
+//! use log::*;
+//!
+//! let (_handle, lines) = flexi_logger::testing::capture();
+//!
+//! warn!("The configuration file is missing");
+//!
+//! assert_eq!(lines().len(), 1);
+//! assert!(lines()[0].starts_with("WARN ["));
+//! assert!(lines()[0].ends_with("] The configuration file is missing"));
+//! ```
+//!
+//! [`expect_no_logs_above()`](fn.expect_no_logs_above.html) lets a test fail
+//! if unexpected warnings or errors are logged.
+//!
+//! Only available with feature `testing`.
+use crate::deferred_now::DeferredNow;
+use crate::formats::default_format;
+use crate::writers::LogWriter;
+use crate::{LogTarget, Logger, ReconfigurationHandle};
+use log::Record;
+
+use std::io;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// The handle that is returned by [`capture()`](fn.capture.html).
+///
+/// It allows e.g. changing the log specification, which is initially `trace`.
+pub type CaptureHandle = ReconfigurationHandle;
+
+/// Installs a logger that writes all log lines, formatted with
+/// [`default_format`](../fn.default_format.html), into memory,
+/// and returns its handle and a function that returns the lines written so far.
+///
+/// Like any logger, the capturing logger can only be installed once per process,
+/// i.e., once per integration test file; a second call panics.
+pub fn capture() -> (CaptureHandle, impl Fn() -> Vec<String>) {
+    let lines = Arc::new(Mutex::new(Vec::<String>::new()));
+    let handle = Logger::with_str("trace")
+        .log_target(LogTarget::Writer(Box::new(CaptureWriter(Arc::clone(
+            &lines,
+        )))))
+        .start()
+        .unwrap_or_else(|e| panic!("Installing the capturing logger failed with {}", e));
+    (handle, move || lines.lock().unwrap().clone())
+}
+
+/// Returns a guard that panics when it is dropped, if records with the given level
+/// or a more severe level were logged during its lifetime.
+///
+/// ```rust
+/// use log::*;
+///
+/// // in a test:
+/// let _guard = flexi_logger::testing::expect_no_logs_above(Level::Warn);
+/// info!("Starting"); // fine
+/// // my_app::start(); // fails the test if it logs a warning or an error
+/// ```
+///
+/// With the first call, a logger is installed that counts all records and does not
+/// write them; the call panics if another logger is installed already.
+/// Further calls, also from other tests in the same integration test file, share that logger.
+/// The records of all threads are counted, so tests that log warnings intentionally
+/// should not run in parallel with tests that use this guard.
+pub fn expect_no_logs_above(level: log::Level) -> NoLogsGuard {
+    static COUNTING_HANDLE: OnceLock<ReconfigurationHandle> = OnceLock::new();
+    let handle = COUNTING_HANDLE.get_or_init(|| {
+        Logger::with_str("trace")
+            .do_not_log()
+            .count_levels()
+            .start()
+            .unwrap_or_else(|e| panic!("Installing the counting logger failed with {}", e))
+    });
+    NoLogsGuard {
+        handle,
+        level,
+        counts_before: handle.level_counts(),
+    }
+}
+
+/// The guard that is returned by [`expect_no_logs_above()`](fn.expect_no_logs_above.html).
+pub struct NoLogsGuard {
+    handle: &'static ReconfigurationHandle,
+    level: log::Level,
+    counts_before: [u64; 5],
+}
+impl Drop for NoLogsGuard {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            return;
+        }
+        let counts = self.handle.level_counts();
+        let logged: u64 = (0..self.level as usize)
+            .map(|idx| counts[idx] - self.counts_before[idx])
+            .sum();
+        if logged > 0 {
+            panic!(
+                "{} records with level {} or a more severe level were logged",
+                logged, self.level
+            );
+        }
+    }
+}
+
+// Writes the formatted log lines into a shared Vec.
+struct CaptureWriter(Arc<Mutex<Vec<String>>>);
+impl LogWriter for CaptureWriter {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> io::Result<()> {
+        let mut buf = Vec::<u8>::with_capacity(200);
+        default_format(&mut buf, now, record)?;
+        self.0
+            .lock()
+            .unwrap()
+            .push(String::from_utf8_lossy(&buf).to_string());
+        Ok(())
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        log::LevelFilter::Trace
+    }
+
+    fn validate_logs(&self, _expected: &[(&'static str, &'static str, &'static str)]) {}
+}
@@ -1,10 +1,18 @@
 //! Contains a trait ([`LogWriter`](trait.LogWriter.html)) for extending `flexi_logger`
 //! with additional log writers,
-//! and two concrete implementations
+//! and some concrete implementations
 //! for writing to files
-//! ([`FileLogWriter`](struct.FileLogWriter.html))
-//! or to the syslog
-//! ([`SyslogWriter`](struct.SyslogWriter.html)).
+//! ([`FileLogWriter`](struct.FileLogWriter.html)),
+//! to a separate file per key that is derived from the log record
+//! ([`SplittingFileWriter`](struct.SplittingFileWriter.html)),
+//! to the syslog
+//! ([`SyslogWriter`](struct.SyslogWriter.html)),
+//! to a Unix domain socket
+//! ([`UnixSocketWriter`](struct.UnixSocketWriter.html)),
+//! or to an HTTP endpoint
+//! ([`HttpWriter`](struct.HttpWriter.html)),
+//! and a wrapper that forwards only records with matching messages
+//! ([`FilteredWriter`](struct.FilteredWriter.html)).
 //! You can also use your own implementations of [`LogWriter`](trait.LogWriter.html).
 //!
 //! Such log writers can be used in two ways:
@@ -91,15 +99,32 @@
 //!
 
 mod file_log_writer;
+mod filtered_writer;
 mod log_writer;
+mod queue_policy;
+mod splitting_file_writer;
+mod writer_set;
 
+#[cfg(feature = "http_writer")]
+mod http_writer;
 #[cfg(feature = "syslog_writer")]
 mod syslog_writer;
+#[cfg(unix)]
+mod unix_socket_writer;
 
+#[cfg(feature = "http_writer")]
+pub use self::http_writer::{HttpWriter, HttpWriterBuilder};
 #[cfg(feature = "syslog_writer")]
 pub use self::syslog_writer::{
     LevelToSyslogSeverity, SyslogConnector, SyslogFacility, SyslogSeverity, SyslogWriter,
 };
 
-pub use self::file_log_writer::{FileLogWriter, FileLogWriterBuilder};
+pub use self::file_log_writer::{FileLogWriter, FileLogWriterBuilder, LogFileInfo};
+pub use self::filtered_writer::FilteredWriter;
 pub use self::log_writer::LogWriter;
+pub use self::queue_policy::QueuePolicy;
+pub use self::splitting_file_writer::{SplittingFileWriter, SplittingFileWriterBuilder};
+pub use self::writer_set::WriterSet;
+
+#[cfg(unix)]
+pub use self::unix_socket_writer::UnixSocketWriter;
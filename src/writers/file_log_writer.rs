@@ -1,15 +1,17 @@
 use crate::deferred_now::DeferredNow;
 use crate::flexi_error::FlexiLoggerError;
-use crate::formats::default_format;
-use crate::logger::{Age, Cleanup, Criterion, Naming};
-use crate::primary_writer::buffer_with;
+use crate::formats::{self, default_format};
+use crate::logger::{Age, Cleanup, Criterion, FormatErrorPolicy, Naming, WriteErrorPolicy};
+use crate::primary_writer::{buffer_with, format_with_policy, on_recursive_logging};
 use crate::writers::log_writer::LogWriter;
-use crate::FormatFunction;
-use chrono::{DateTime, Datelike, Local, Timelike};
+use crate::Format;
+use chrono::{DateTime, Datelike, Duration, Local, LocalResult, NaiveDateTime, TimeZone, Timelike};
 use log::Record;
+use regex::Regex;
 
 use std::borrow::BorrowMut;
 use std::cmp::max;
+use std::collections::HashMap;
 use std::env;
 use std::fs::{File, OpenOptions};
 #[cfg(feature = "ziplogs")]
@@ -17,14 +19,20 @@ use std::io::Read;
 use std::io::{BufRead, BufReader, Write};
 use std::ops::{Add, Deref, DerefMut};
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
-const CURRENT_INFIX: &str = "_rCURRENT";
+// The infixes are appended to the file basename with the separator.
+const CURRENT_INFIX: &str = "rCURRENT";
 fn number_infix(idx: u32) -> String {
-    format!("_r{:0>5}", idx)
+    format!("r{:0>5}", idx)
 }
+fn date_infix(date: &DateTime<Local>) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+const DATE_INFIX_PATTERN: &str = "[0-9][0-9][0-9][0-9]-[0-9][0-9]-[0-9][0-9]";
 
 // Describes how rotation should work
+#[derive(Clone)]
 struct RotationConfig {
     // Defines if rotation should be based on size or date
     criterion: Criterion,
@@ -33,43 +41,99 @@ struct RotationConfig {
     // Defines the cleanup strategy
     cleanup: Cleanup,
 }
+#[derive(Clone)]
 struct FilenameConfig {
     directory: PathBuf,
     file_basename: String,
     suffix: String,
+    separator: String,
     use_timestamp: bool,
 }
+type ClockFn = dyn Fn() -> DateTime<Local> + Send + Sync;
+
+// Provides the current time to the naming and rotation logic;
+// can be replaced in tests, see FileLogWriterBuilder::clock().
+#[derive(Clone, Default)]
+struct Clock(Option<Arc<ClockFn>>);
+impl Clock {
+    fn now(&self) -> DateTime<Local> {
+        match self.0 {
+            Some(ref clock) => clock(),
+            None => Local::now(),
+        }
+    }
+}
+
 // The immutable configuration of a FileLogWriter.
+#[derive(Clone)]
 struct FileLogWriterConfig {
-    format: FormatFunction,
+    format: Format,
+    // the index is the level's numeric value minus one
+    level_formats: [Option<Format>; 5],
+    format_error_policy: FormatErrorPolicy,
+    write_error_policy: WriteErrorPolicy,
+    o_max_line_length: Option<usize>,
+    strip_ansi: bool,
     print_message: bool,
     append: bool,
     filename_config: FilenameConfig,
     o_create_symlink: Option<PathBuf>,
     use_windows_line_ending: bool,
+    sync_on_error: bool,
+    write_bom: bool,
+    // attempts, delay; see open_retry()
+    o_open_retry: Option<(u32, std::time::Duration)>,
+    #[cfg(unix)]
+    o_file_permissions: Option<u32>,
+    o_flush_every: Option<usize>,
+    o_end_marker: Option<String>,
+    o_rotate_over_lines: Option<u64>,
+    o_rotate_on_match: Option<Regex>,
+    o_rotate_current_on_startup: Option<bool>,
+    clock: Clock,
 }
 impl FileLogWriterConfig {
     // Factory method; uses the same defaults as Logger.
     pub fn default() -> FileLogWriterConfig {
         FileLogWriterConfig {
-            format: default_format,
+            format: default_format.into(),
+            level_formats: Default::default(),
+            format_error_policy: FormatErrorPolicy::Drop,
+            write_error_policy: WriteErrorPolicy::Drop,
+            o_max_line_length: None,
+            strip_ansi: false,
             print_message: false,
             filename_config: FilenameConfig {
                 directory: PathBuf::from("."),
                 file_basename: String::new(),
                 suffix: "log".to_string(),
+                separator: "_".to_string(),
                 use_timestamp: true,
             },
             append: false,
             o_create_symlink: None,
             use_windows_line_ending: false,
+            sync_on_error: false,
+            write_bom: false,
+            o_open_retry: None,
+            #[cfg(unix)]
+            o_file_permissions: None,
+            o_flush_every: None,
+            o_end_marker: None,
+            o_rotate_over_lines: None,
+            o_rotate_on_match: None,
+            o_rotate_current_on_startup: None,
+            clock: Clock::default(),
         }
     }
 }
 
 /// Builder for `FileLogWriter`.
+#[derive(Clone)]
 pub struct FileLogWriterBuilder {
     discriminant: Option<String>,
+    // replaces the name of the program, see derive_path()
+    o_basename: Option<String>,
     config: FileLogWriterConfig,
     o_rotation_config: Option<RotationConfig>,
     max_log_level: log::LevelFilter,
@@ -86,8 +150,57 @@ impl FileLogWriterBuilder {
 
     /// Makes the `FileLogWriter` use the provided format function for the log entries,
     /// rather than the default ([formats::default_format](fn.default_format.html)).
-    pub fn format(mut self, format: FormatFunction) -> FileLogWriterBuilder {
-        self.config.format = format;
+    pub fn format<F: Into<Format>>(mut self, format: F) -> FileLogWriterBuilder {
+        self.config.format = format.into();
+        self
+    }
+
+    /// Makes the `FileLogWriter` use the provided format functions for the log entries of the
+    /// respective levels; the format of
+    /// [`format()`](struct.FileLogWriterBuilder.html#method.format) remains the fallback
+    /// for the levels that are not contained in the map.
+    pub fn format_per_level<F: Into<Format>>(
+        mut self,
+        formats: HashMap<log::Level, F>,
+    ) -> FileLogWriterBuilder {
+        for (level, format) in formats {
+            self.config.level_formats[level as usize - 1] = Some(format.into());
+        }
+        self
+    }
+
+    /// Defines how the `FileLogWriter` reacts if the format function returns an error.
+    ///
+    /// See [FormatErrorPolicy](../enum.FormatErrorPolicy.html).
+    pub fn format_error_policy(mut self, policy: FormatErrorPolicy) -> FileLogWriterBuilder {
+        self.config.format_error_policy = policy;
+        self
+    }
+
+    /// Defines how the `FileLogWriter` reacts if writing to the file fails.
+    ///
+    /// See [WriteErrorPolicy](../enum.WriteErrorPolicy.html).
+    pub fn write_error_policy(mut self, policy: WriteErrorPolicy) -> FileLogWriterBuilder {
+        self.config.write_error_policy = policy;
+        self
+    }
+
+    /// Limits the length of the log lines to the given number of bytes.
+    ///
+    /// Longer lines are truncated and get an ellipsis (`…`) appended;
+    /// multibyte UTF-8 characters are never split.
+    pub fn max_line_length(mut self, max_line_length: usize) -> FileLogWriterBuilder {
+        self.config.o_max_line_length = Some(max_line_length);
+        self
+    }
+
+    /// Makes the `FileLogWriter` remove ANSI escape sequences, like the color codes of the
+    /// colored format functions, from the lines it writes.
+    ///
+    /// This avoids that the file is polluted with escape sequences
+    /// if a colored format function is used for the file output.
+    pub fn strip_ansi(mut self) -> FileLogWriterBuilder {
+        self.config.strip_ansi = true;
         self
     }
 
@@ -101,6 +214,8 @@ impl FileLogWriterBuilder {
     }
 
     /// Specifies a suffix for the log files. The default is "log".
+    ///
+    /// With an empty suffix, the file names have no extension at all.
     pub fn suffix<S: Into<String>>(mut self, suffix: S) -> FileLogWriterBuilder {
         self.config.filename_config.suffix = suffix.into();
         self
@@ -155,8 +270,72 @@ impl FileLogWriterBuilder {
         self
     }
 
+    /// Makes the `FileLogWriter` rotate the log file additionally when the given number
+    /// of lines has been written to it, regardless of which criterion was given to `rotate()`;
+    /// whichever condition is met first causes the rotation.
+    ///
+    /// The lines are counted by their line endings, so a record with a multi-line message
+    /// counts as several lines.
+    ///
+    /// This option only has an effect if `rotate()` is used, too.
+    pub fn rotate_over_lines(mut self, lines: u64) -> FileLogWriterBuilder {
+        self.config.o_rotate_over_lines = Some(std::cmp::max(1, lines));
+        self
+    }
+
+    /// Makes the `FileLogWriter` rotate the log file additionally after writing a record
+    /// whose message matches the given pattern, regardless of which criterion was given
+    /// to `rotate()`.
+    ///
+    /// This allows e.g. starting a new log file for each session of a request-driven tool,
+    /// if the end of a session is logged with a specific message.
+    /// The matching record is the last one in the rotated file.
+    ///
+    /// This option only has an effect if `rotate()` is used, too.
+    pub fn rotate_on_match(mut self, pattern: Regex) -> FileLogWriterBuilder {
+        self.config.o_rotate_on_match = Some(pattern);
+        self
+    }
+
+    /// Defines what happens at startup with a `_rCURRENT` file that was left by a previous
+    /// program run.
+    ///
+    /// With `true`, the existing `_rCURRENT` file is rotated away first, i.e., it is renamed
+    /// like with a regular rotation, and the logs go to a new `_rCURRENT` file.
+    /// With `false`, the logs are appended to the existing `_rCURRENT` file,
+    /// and its size and number of lines count for the next rotation.
+    ///
+    /// By default, the file is rotated away unless [`append()`](#method.append) is used.
+    ///
+    /// This option only has an effect if `rotate()` is used with `Naming::Numbers`
+    /// or `Naming::Timestamps`.
+    pub fn rotate_current_on_startup(mut self, rotate: bool) -> FileLogWriterBuilder {
+        self.config.o_rotate_current_on_startup = Some(rotate);
+        self
+    }
+
+    /// Makes the `FileLogWriter` take the current time from the given function,
+    /// rather than from the system clock, for naming the files and for the time-based rotation.
+    ///
+    /// This allows testing the rotation deterministically, without waiting for the
+    /// rotation boundaries; see also
+    /// [`FileLogWriter::rotate_now()`](struct.FileLogWriter.html#method.rotate_now).
+    ///
+    /// Only available with feature `testing`.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn clock<F>(mut self, clock: F) -> FileLogWriterBuilder
+    where
+        F: Fn() -> DateTime<Local> + Send + Sync + 'static,
+    {
+        self.config.clock = Clock(Some(Arc::new(clock)));
+        self
+    }
+
     /// Makes the logger append to the given file, if it exists; by default, the file would be
     /// truncated.
+    ///
+    /// In combination with rotation and `Naming::Numbers`, the existing rotated files
+    /// are discovered at startup, and the numbering continues after the highest existing index.
     pub fn append(mut self) -> FileLogWriterBuilder {
         self.config.append = true;
         self
@@ -168,6 +347,17 @@ impl FileLogWriterBuilder {
         self
     }
 
+    /// Specifies the separator that is used between the components of the log file name,
+    /// i.e. between the program name, the discriminant, the timestamp, and the rotation infix.
+    /// The default is `_`.
+    ///
+    /// With `filename_separator("-")`, a rotated log file is e.g. named `my_prog-r00003.log`
+    /// rather than `my_prog_r00003.log`.
+    pub fn filename_separator<S: Into<String>>(mut self, separator: S) -> FileLogWriterBuilder {
+        self.config.filename_config.separator = separator.into();
+        self
+    }
+
     /// The specified String will be used on linux systems to create in the current folder
     /// a symbolic link to the current log file.
     pub fn create_symlink<P: Into<PathBuf>>(mut self, symlink: P) -> FileLogWriterBuilder {
@@ -181,25 +371,144 @@ impl FileLogWriterBuilder {
         self
     }
 
+    /// Makes the `FileLogWriter` sync the log file to disk (with `File::sync_data()`)
+    /// after each error-level record.
+    ///
+    /// This ensures that error messages survive a subsequent crash or abort of the program,
+    /// while records on lower levels are written without the cost of syncing.
+    pub fn sync_on_error(mut self) -> FileLogWriterBuilder {
+        self.config.sync_on_error = true;
+        self
+    }
+
+    /// Makes the `FileLogWriter` write a UTF-8 byte order mark at the beginning of each
+    /// new log file, including the files that are started with rotation.
+    ///
+    /// This helps some Windows tools, like Notepad or Excel, to recognize the encoding.
+    /// No byte order mark is written when an existing, non-empty file is continued.
+    pub fn write_bom(mut self) -> FileLogWriterBuilder {
+        self.config.write_bom = true;
+        self
+    }
+
+    /// Makes the `FileLogWriter` try up to `attempts` times to open a log file,
+    /// waiting for `delay` after each failed attempt, before it gives up.
+    ///
+    /// This helps e.g. in containers, where the volume with the log directory
+    /// can be mounted slightly after the program was started.
+    /// The retries apply as well to creating the log directory.
+    /// By default, a failure to open the file is reported immediately.
+    ///
+    /// The retries also apply to the files that are opened with rotation;
+    /// the logging thread is then blocked while waiting.
+    pub fn open_retry(mut self, attempts: u32, delay: std::time::Duration) -> FileLogWriterBuilder {
+        self.config.o_open_retry = Some((std::cmp::max(1, attempts), delay));
+        self
+    }
+
+    /// Makes the `FileLogWriter` create the log files with the given permissions,
+    /// e.g. `file_permissions(0o600)` for files that only the owner can read.
+    ///
+    /// The permissions are applied when a file is created, including the files that are
+    /// started with rotation; existing files that are continued keep their permissions.
+    /// As usual, the umask of the process is applied, too.
+    ///
+    /// Only available on unix.
+    #[cfg(unix)]
+    pub fn file_permissions(mut self, mode: u32) -> FileLogWriterBuilder {
+        self.config.o_file_permissions = Some(mode);
+        self
+    }
+
+    /// Makes the `FileLogWriter` collect the formatted records in memory and write them
+    /// to the file together, after every `n` records.
+    ///
+    /// This reduces the number of write calls, at the risk of losing the collected records
+    /// if the program crashes. Error-level records are written immediately, together with
+    /// the records that were collected before them.
+    /// The collected records are also written when the `FileLogWriter` is flushed
+    /// (e.g. with `log::logger().flush()`) or dropped, and before a rotation.
+    ///
+    /// With size-based rotation, a log file can exceed its size limit by the collected records.
+    pub fn flush_every(mut self, n: usize) -> FileLogWriterBuilder {
+        self.config.o_flush_every = Some(std::cmp::max(1, n));
+        self
+    }
+
+    /// Makes the `FileLogWriter` write the given line at the end of each log file,
+    /// when the file is rotated away, or when the output is finished with
+    /// [`ReconfigurationHandle::shutdown()`](../struct.ReconfigurationHandle.html#method.shutdown)
+    /// or by dropping the `FileLogWriter`.
+    ///
+    /// A log file without the marker was thus not closed properly, e.g. because the program
+    /// crashed.
+    pub fn end_marker<S: Into<String>>(mut self, marker: S) -> FileLogWriterBuilder {
+        self.config.o_end_marker = Some(marker.into());
+        self
+    }
+
     /// Produces the FileLogWriter.
     pub fn try_build(mut self) -> Result<FileLogWriter, FlexiLoggerError> {
         // make sure the folder exists or create it
         let p_directory = Path::new(&self.config.filename_config.directory);
-        std::fs::create_dir_all(&p_directory)?;
+        with_retry(
+            "creating directory",
+            p_directory,
+            self.config.o_open_retry,
+            || std::fs::create_dir_all(&p_directory),
+        )?;
         if !std::fs::metadata(&p_directory)?.is_dir() {
             return Err(FlexiLoggerError::BadDirectory);
         };
 
-        let arg0 = env::args().nth(0).unwrap_or_else(|| "rs".to_owned());
-        self.config.filename_config.file_basename =
-            Path::new(&arg0).file_stem().unwrap(/*cannot fail*/).to_string_lossy().to_string();
+        self.config.filename_config.file_basename = match self.o_basename {
+            Some(ref basename) => basename.clone(),
+            None => {
+                let arg0 = env::args().next().unwrap_or_else(|| "rs".to_owned());
+                Path::new(&arg0).file_stem().unwrap(/*cannot fail*/).to_string_lossy().to_string()
+            }
+        };
 
         if let Some(discriminant) = self.discriminant {
-            self.config.filename_config.file_basename += &format!("_{}", discriminant);
+            self.config.filename_config.file_basename +=
+                &format!("{}{}", self.config.filename_config.separator, discriminant);
+        }
+        if let Some(RotationConfig {
+            criterion: Criterion::Age(Age::At(hour, minute)),
+            ..
+        }) = self.o_rotation_config
+        {
+            if hour > 23 || minute > 59 {
+                return Err(FlexiLoggerError::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "Age::At() needs an hour below 24 and a minute below 60",
+                )));
+            }
+        }
+        if let Some(RotationConfig {
+            ref criterion,
+            naming: Naming::Dates,
+            ..
+        }) = self.o_rotation_config
+        {
+            match criterion {
+                Criterion::Age(Age::Day) | Criterion::Age(Age::At(0, 0)) => {}
+                _ => {
+                    return Err(FlexiLoggerError::Io(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "Naming::Dates needs daily rotation at midnight, \
+                         with Age::Day or Age::At(0, 0)",
+                    )));
+                }
+            }
         }
+
         if self.config.filename_config.use_timestamp {
-            self.config.filename_config.file_basename +=
-                &Local::now().format("_%Y-%m-%d_%H-%M-%S").to_string();
+            self.config.filename_config.file_basename += &format!(
+                "{}{}",
+                self.config.filename_config.separator,
+                self.config.clock.now().format("%Y-%m-%d_%H-%M-%S")
+            );
         };
 
         Ok(FileLogWriter {
@@ -211,6 +520,44 @@ impl FileLogWriterBuilder {
             max_log_level: self.max_log_level,
         })
     }
+
+    // Derives the builder for an additional file, e.g. for Logger::error_file(),
+    // whose name has the given addition to the discriminant, and that gets no symlink.
+    pub(crate) fn derive(&self, discriminant: &str) -> FileLogWriterBuilder {
+        let mut builder = self.clone();
+        builder.discriminant = Some(match self.discriminant {
+            Some(ref own) => format!(
+                "{}{}{}",
+                own, self.config.filename_config.separator, discriminant
+            ),
+            None => discriminant.to_string(),
+        });
+        builder.config.o_create_symlink = None;
+        builder
+    }
+
+    // Derives the builder for an additional file with the given path, e.g. for
+    // Logger::error_file(); the file gets no timestamp, discriminant, and symlink.
+    pub(crate) fn derive_path(&self, path: &Path) -> FileLogWriterBuilder {
+        let mut builder = self.clone();
+        builder.config.filename_config.directory = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        builder.o_basename = Some(
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        );
+        builder.config.filename_config.suffix = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_string())
+            .unwrap_or_default();
+        builder.config.filename_config.use_timestamp = false;
+        builder.discriminant = None;
+        builder.config.o_create_symlink = None;
+        builder
+    }
 }
 
 /// Alternative set of methods to control the behavior of the `FileLogWriterBuilder`.
@@ -297,6 +644,22 @@ impl FileLogWriterBuilder {
         self.config.o_create_symlink = symlink.map(Into::into);
         self
     }
+
+    /// Specifies a suffix for the log files. With None, the default suffix "log" is used.
+    pub fn o_suffix<S: Into<String>>(mut self, suffix: Option<S>) -> FileLogWriterBuilder {
+        self.config.filename_config.suffix =
+            suffix.map(Into::into).unwrap_or_else(|| "log".to_string());
+        self
+    }
+
+    /// With true, makes the `FileLogWriter` use Windows line endings, rather than just `\n`.
+    pub fn o_use_windows_line_ending(
+        mut self,
+        use_windows_line_ending: bool,
+    ) -> FileLogWriterBuilder {
+        self.config.use_windows_line_ending = use_windows_line_ending;
+        self
+    }
 }
 
 //  Describes the latest existing numbered log file.
@@ -314,25 +677,40 @@ enum IdxState {
 enum NamingState {
     CreatedAt,
     IdxState(IdxState),
+    // the file name contains the creation date, there is no CURRENT file
+    Dates,
 }
 
 enum RollState {
     Size(u64, u64), // max_size, current_size
     Age(Age),
+    Scheduled(u32, u32, DateTime<Local>), // hour, minute, next rotation
 }
 
 struct RotationState {
     naming_state: NamingState,
     roll_state: RollState,
+    // max_lines, current_lines, see rotate_over_lines()
+    o_line_state: Option<(u64, u64)>,
+    // set after writing a record that matches the pattern of rotate_on_match()
+    matched: bool,
     created_at: DateTime<Local>,
     cleanup: Cleanup,
 }
 impl RotationState {
-    fn rotation_necessary(&self) -> bool {
+    fn rotation_necessary(&self, clock: &Clock) -> bool {
+        if self.matched {
+            return true;
+        }
+        if let Some((max_lines, current_lines)) = self.o_line_state {
+            if current_lines >= max_lines {
+                return true;
+            }
+        }
         match &self.roll_state {
             RollState::Size(max_size, current_size) => current_size > max_size,
             RollState::Age(age) => {
-                let now = Local::now();
+                let now = clock.now();
                 match age {
                     Age::Day => self.created_at.num_days_from_ce() != now.num_days_from_ce(),
                     Age::Hour => {
@@ -350,9 +728,61 @@ impl RotationState {
                             || self.created_at.minute() != now.minute()
                             || self.created_at.second() != now.second()
                     }
+                    Age::At(..) => false, // see RollState::Scheduled
                 }
             }
+            RollState::Scheduled(_, _, next_rotation) => clock.now() >= *next_rotation,
+        }
+    }
+}
+
+fn count_lines(buf: &[u8]) -> u64 {
+    buf.iter().filter(|b| **b == b'\n').count() as u64
+}
+
+// Counts the lines of an existing file chunk by chunk, without reading it into memory.
+fn count_lines_in_file(path: &Path) -> std::io::Result<u64> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut lines = 0;
+    loop {
+        let len = {
+            let chunk = reader.fill_buf()?;
+            if chunk.is_empty() {
+                return Ok(lines);
+            }
+            lines += count_lines(chunk);
+            chunk.len()
+        };
+        reader.consume(len);
+    }
+}
+
+// Determines the first point in time after `after` at which the local clock shows
+// the given time of day.
+fn next_scheduled_rotation(after: &DateTime<Local>, hour: u32, minute: u32) -> DateTime<Local> {
+    let mut date = after.naive_local().date();
+    loop {
+        let naive = date.and_hms_opt(hour, minute, 0).unwrap(/* was checked in try_build */);
+        let o_candidate = match Local.from_local_datetime(&naive) {
+            LocalResult::Single(t) => Some(t),
+            LocalResult::Ambiguous(earliest, _) => Some(earliest),
+            // skipped by a DST transition: rotate when the clock has jumped
+            LocalResult::None => local_after_gap(naive),
+        };
+        if let Some(candidate) = o_candidate {
+            if candidate > *after {
+                return candidate;
+            }
         }
+        date = date.succ_opt().unwrap(/* far from the end of time */);
+    }
+}
+
+fn local_after_gap(naive: NaiveDateTime) -> Option<DateTime<Local>> {
+    // DST gaps are at most one hour long
+    match Local.from_local_datetime(&(naive + Duration::hours(1))) {
+        LocalResult::Single(t) | LocalResult::Ambiguous(t, _) => Some(t),
+        LocalResult::None => None,
     }
 }
 
@@ -361,6 +791,21 @@ struct FileLogWriterState {
     o_log_file: Option<File>,
     o_rotation_state: Option<RotationState>,
     line_ending: &'static [u8],
+    // number of consecutive writes that failed
+    failed_writes: u64,
+    // number of records that were dropped with WriteErrorPolicy::Drop
+    dropped: u64,
+    o_pending: Option<PendingRecords>,
+    // number of syncs, see sync_on_error()
+    #[cfg(test)]
+    syncs: usize,
+}
+
+// Formatted records that were not yet written to the file, see flush_every().
+struct PendingRecords {
+    flush_every: usize,
+    count: usize,
+    buffer: Vec<u8>,
 }
 impl FileLogWriterState {
     // If rotate, the logger writes into a file with infix `_rCURRENT`.
@@ -370,19 +815,22 @@ impl FileLogWriterState {
     ) -> Result<FileLogWriterState, FlexiLoggerError> {
         let (log_file, o_rotation_state) = match o_rotation_config {
             None => {
-                let (log_file, _created_at, _p_path) = open_log_file(config, false)?;
+                let (log_file, _created_at, _p_path) = open_log_file(config, None, config.append)?;
                 (log_file, None)
             }
             Some(rotate_config) => {
+                // an existing CURRENT file is either rotated away or continued
+                let rotate_current = config.o_rotate_current_on_startup.unwrap_or(!config.append);
+
                 // first rotate, then open the log file
                 let naming_state = match rotate_config.naming {
                     Naming::Timestamps => {
-                        if !config.append {
+                        if rotate_current {
                             rotate_output_file_to_date(
-                                &get_creation_date(&get_filepath(
-                                    Some(CURRENT_INFIX),
-                                    &config.filename_config,
-                                ))?,
+                                &get_creation_date(
+                                    &get_filepath(Some(CURRENT_INFIX), &config.filename_config),
+                                    &config.clock,
+                                )?,
                                 config,
                             )?;
                         }
@@ -390,20 +838,27 @@ impl FileLogWriterState {
                     }
                     Naming::Numbers => {
                         let mut rotation_state = get_highest_rotate_idx(&config.filename_config);
-                        if !config.append {
+                        if rotate_current {
                             rotation_state = rotate_output_file_to_idx(rotation_state, config)?;
                         }
                         NamingState::IdxState(rotation_state)
                     }
+                    Naming::Dates => NamingState::Dates,
                 };
-                let (log_file, created_at, p_path) = open_log_file(config, true)?;
+                let (log_file, created_at, p_path) =
+                    open_rotated_log_file(config, &naming_state, !rotate_current)?;
 
                 let cleanup = rotate_config.cleanup;
 
                 let roll_state = match &rotate_config.criterion {
+                    Criterion::Age(Age::At(hour, minute)) => RollState::Scheduled(
+                        *hour,
+                        *minute,
+                        next_scheduled_rotation(&created_at, *hour, *minute),
+                    ),
                     Criterion::Age(age) => RollState::Age(*age),
                     Criterion::Size(size) => {
-                        let written_bytes = if config.append {
+                        let written_bytes = if !rotate_current {
                             std::fs::metadata(&p_path)?.len()
                         } else {
                             0
@@ -412,11 +867,25 @@ impl FileLogWriterState {
                     } // max_size, current_size
                 };
 
+                let o_line_state = match config.o_rotate_over_lines {
+                    Some(max_lines) => {
+                        let current_lines = if !rotate_current {
+                            count_lines_in_file(&p_path)?
+                        } else {
+                            0
+                        };
+                        Some((max_lines, current_lines))
+                    }
+                    None => None,
+                };
+
                 (
                     log_file,
                     Some(RotationState {
                         naming_state,
                         roll_state,
+                        o_line_state,
+                        matched: false,
                         created_at,
                         cleanup,
                     }),
@@ -432,6 +901,15 @@ impl FileLogWriterState {
             } else {
                 b"\n"
             },
+            failed_writes: 0,
+            dropped: 0,
+            #[cfg(test)]
+            syncs: 0,
+            o_pending: config.o_flush_every.map(|flush_every| PendingRecords {
+                flush_every,
+                count: 0,
+                buffer: Vec::with_capacity(200 * flush_every),
+            }),
         })
     }
 
@@ -443,74 +921,234 @@ impl FileLogWriterState {
         &mut self,
         config: &FileLogWriterConfig,
     ) -> Result<(), FlexiLoggerError> {
+        let rotation_necessary = self
+            .o_rotation_state
+            .as_ref()
+            .is_some_and(|rotation_state| rotation_state.rotation_necessary(&config.clock));
+        if rotation_necessary {
+            self.rotate(config)?;
+        }
+        Ok(())
+    }
+
+    // Closes the current output file, renames it according to the naming,
+    // and opens a new one.
+    fn rotate(&mut self, config: &FileLogWriterConfig) -> Result<(), FlexiLoggerError> {
+        if self.o_rotation_state.is_none() {
+            return Ok(());
+        }
+        // the collected records and the end marker belong into the file that is closed now
+        self.write_pending(config.write_error_policy);
+        self.write_end_marker(config);
         if let Some(ref mut rotation_state) = self.o_rotation_state {
-            if rotation_state.rotation_necessary() {
-                self.o_log_file = None; // close the output file
+            self.o_log_file = None; // close the output file
 
-                match rotation_state.naming_state {
-                    NamingState::CreatedAt => {
-                        rotate_output_file_to_date(&rotation_state.created_at, config)?;
-                    }
-                    NamingState::IdxState(ref mut idx_state) => {
-                        *idx_state = rotate_output_file_to_idx(*idx_state, config)?;
-                    }
+            match rotation_state.naming_state {
+                NamingState::CreatedAt => {
+                    rotate_output_file_to_date(&rotation_state.created_at, config)?;
                 }
-
-                let (line_writer, created_at, _) = open_log_file(config, true)?;
-                self.o_log_file = Some(line_writer);
-                rotation_state.created_at = created_at;
-                if let RollState::Size(_max_size, ref mut current_size) = rotation_state.roll_state
-                {
-                    *current_size = 0;
+                NamingState::IdxState(ref mut idx_state) => {
+                    *idx_state = rotate_output_file_to_idx(*idx_state, config)?;
                 }
+                NamingState::Dates => {}
+            }
 
-                let cleanup_config: &Cleanup = &rotation_state.cleanup;
-                let filename_config: &FilenameConfig = &config.filename_config;
-                remove_or_zip_too_old_logfiles(cleanup_config, filename_config)?;
+            let (line_writer, created_at, p_path) =
+                open_rotated_log_file(config, &rotation_state.naming_state, config.append)?;
+            self.o_log_file = Some(line_writer);
+            rotation_state.created_at = created_at;
+            match rotation_state.roll_state {
+                RollState::Size(_max_size, ref mut current_size) => *current_size = 0,
+                RollState::Scheduled(hour, minute, ref mut next_rotation) => {
+                    *next_rotation = next_scheduled_rotation(&created_at, hour, minute);
+                }
+                RollState::Age(_) => {}
             }
+            if let Some((_max_lines, ref mut current_lines)) = rotation_state.o_line_state {
+                *current_lines = 0;
+            }
+            rotation_state.matched = false;
+
+            let cleanup_config: &Cleanup = &rotation_state.cleanup;
+            let filename_config: &FilenameConfig = &config.filename_config;
+            let dates = matches!(rotation_state.naming_state, NamingState::Dates);
+            remove_or_zip_too_old_logfiles(cleanup_config, filename_config, dates, &p_path)?;
         }
 
         Ok(())
     }
 
+    // Makes the next write rotate the file first, see rotate_on_match().
+    fn rotate_before_next_write(&mut self) {
+        if let Some(ref mut rotation_state) = self.o_rotation_state {
+            rotation_state.matched = true;
+        }
+    }
+
     fn write_buffer(&mut self, buf: &[u8]) -> std::io::Result<()> {
         self.o_log_file
             .as_mut()
-            .expect("FlexiLogger: log_file unexpectedly not available")
+            .ok_or_else(|| {
+                // opening the file failed, e.g. when rotating
+                std::io::Error::new(std::io::ErrorKind::NotFound, "no log file available")
+            })?
             .write_all(buf)?;
 
         if let Some(ref mut rotation_state) = self.o_rotation_state {
             if let RollState::Size(_max_size, ref mut current_size) = rotation_state.roll_state {
                 *current_size += buf.len() as u64;
             }
+            if let Some((_max_lines, ref mut current_lines)) = rotation_state.o_line_state {
+                *current_lines += count_lines(buf);
+            }
         };
         Ok(())
     }
+
+    // Collects the buffer if flush_every() is used, otherwise writes it.
+    fn write_buffer_or_fallback(&mut self, buf: &[u8], policy: WriteErrorPolicy) {
+        if let Some(ref mut pending) = self.o_pending {
+            pending.buffer.extend_from_slice(buf);
+            pending.count += 1;
+            if pending.count >= pending.flush_every {
+                self.write_pending(policy);
+            }
+        } else {
+            self.write_or_fallback(buf, 1, policy);
+        }
+    }
+
+    // Writes the collected records, if there are some.
+    fn write_pending(&mut self, policy: WriteErrorPolicy) {
+        if let Some(mut pending) = self.o_pending.take() {
+            if !pending.buffer.is_empty() {
+                self.write_or_fallback(&pending.buffer, pending.count, policy);
+                pending.buffer.clear();
+                pending.count = 0;
+            }
+            self.o_pending = Some(pending);
+        }
+    }
+
+    fn write_end_marker(&mut self, config: &FileLogWriterConfig) {
+        if let Some(ref marker) = config.o_end_marker {
+            let mut buf = Vec::with_capacity(marker.len() + 2);
+            buf.extend_from_slice(marker.as_bytes());
+            buf.extend_from_slice(self.line_ending);
+            self.write_or_fallback(&buf, 0, config.write_error_policy);
+        }
+    }
+
+    // Writes the collected records and the end marker, and closes the file.
+    fn close(&mut self, config: &FileLogWriterConfig) {
+        if self.o_log_file.is_some() {
+            self.write_pending(config.write_error_policy);
+            self.write_end_marker(config);
+            self.o_log_file = None;
+        }
+    }
+
+    // Reports only the first of consecutive failures, and applies the policy to all of them;
+    // buf contains the given number of records.
+    fn write_or_fallback(&mut self, buf: &[u8], records: usize, policy: WriteErrorPolicy) {
+        match self.write_buffer(buf) {
+            Ok(()) => {
+                if self.failed_writes > 0 {
+                    eprintln!(
+                        "[flexi_logger] FileLogWriter: writing works again, \
+                         after {} failed writes",
+                        self.failed_writes
+                    );
+                    self.failed_writes = 0;
+                }
+            }
+            Err(e) => {
+                if self.failed_writes == 0 {
+                    eprintln!(
+                        "[flexi_logger] FileLogWriter: writing failed with {}; \
+                         further failures are not reported, the log lines are {}",
+                        e,
+                        match policy {
+                            WriteErrorPolicy::Drop => "dropped",
+                            WriteErrorPolicy::StdErr => "written to stderr",
+                        }
+                    );
+                }
+                self.failed_writes += 1;
+                match policy {
+                    WriteErrorPolicy::Drop => self.dropped += records as u64,
+                    WriteErrorPolicy::StdErr => {
+                        std::io::stderr().write_all(buf).ok();
+                    }
+                }
+            }
+        }
+    }
+
+    fn sync_data(&mut self) -> std::io::Result<()> {
+        match self.o_log_file {
+            Some(ref log_file) => {
+                #[cfg(test)]
+                {
+                    self.syncs += 1;
+                }
+                log_file.sync_data()
+            }
+            None => Ok(()),
+        }
+    }
+
+    // The infix of the file that is currently written.
+    fn current_infix(&self) -> Option<String> {
+        self.o_rotation_state
+            .as_ref()
+            .map(|rotation_state| match rotation_state.naming_state {
+                NamingState::Dates => date_infix(&rotation_state.created_at),
+                _ => CURRENT_INFIX.to_string(),
+            })
+    }
 }
 
 fn get_filepath(o_infix: Option<&str>, config: &FilenameConfig) -> PathBuf {
     let mut s_filename = String::with_capacity(
-        config.file_basename.len() + o_infix.map(str::len).unwrap_or(0) + 1 + config.suffix.len(),
+        config.file_basename.len()
+            + config.separator.len()
+            + o_infix.map(str::len).unwrap_or(0)
+            + 1
+            + config.suffix.len(),
     ) + &config.file_basename;
     if let Some(infix) = o_infix {
+        s_filename += &config.separator;
         s_filename += infix;
     };
-    s_filename += ".";
-    s_filename += &config.suffix;
+    if !config.suffix.is_empty() {
+        s_filename += ".";
+        s_filename += &config.suffix;
+    }
     let mut p_path = config.directory.to_path_buf();
     p_path.push(s_filename);
     p_path
 }
 
+// With rotation, the logs are written to the CURRENT file, or, with `Naming::Dates`,
+// to the file of the current day, which is continued if it exists already.
+// An existing CURRENT file is continued if `append_current` is set.
+fn open_rotated_log_file(
+    config: &FileLogWriterConfig,
+    naming_state: &NamingState,
+    append_current: bool,
+) -> Result<(File, DateTime<Local>, PathBuf), FlexiLoggerError> {
+    match naming_state {
+        NamingState::Dates => open_log_file(config, Some(&date_infix(&config.clock.now())), true),
+        _ => open_log_file(config, Some(CURRENT_INFIX), append_current),
+    }
+}
+
 fn open_log_file(
     config: &FileLogWriterConfig,
-    with_rotation: bool,
+    o_infix: Option<&str>,
+    append: bool,
 ) -> Result<(File, DateTime<Local>, PathBuf), FlexiLoggerError> {
-    let o_infix = if with_rotation {
-        Some(CURRENT_INFIX)
-    } else {
-        None
-    };
     let p_path = get_filepath(o_infix, &config.filename_config);
     if config.print_message {
         println!("Log is written to {}", &p_path.display());
@@ -519,23 +1157,66 @@ fn open_log_file(
         self::platform::create_symlink_if_possible(link, &p_path);
     }
 
-    let log_file = OpenOptions::new()
+    let mut options = OpenOptions::new();
+    options
         .write(true)
         .create(true)
-        .append(config.append)
-        .truncate(!config.append)
-        .open(&p_path)?;
+        .append(append)
+        .truncate(!append);
+    #[cfg(unix)]
+    {
+        if let Some(mode) = config.o_file_permissions {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(mode);
+        }
+    }
+    let mut log_file = with_retry("opening file", &p_path, config.o_open_retry, || {
+        options.open(&p_path)
+    })?;
+    if config.write_bom && log_file.metadata()?.len() == 0 {
+        log_file.write_all(UTF8_BOM)?;
+    }
 
-    Ok((log_file, get_creation_date(&p_path)?, p_path))
+    Ok((log_file, get_creation_date(&p_path, &config.clock)?, p_path))
+}
+
+// Runs the file system operation on path, with the retries of
+// FileLogWriterBuilder::open_retry().
+fn with_retry<T, F: FnMut() -> std::io::Result<T>>(
+    what: &str,
+    path: &Path,
+    o_open_retry: Option<(u32, std::time::Duration)>,
+    mut operation: F,
+) -> std::io::Result<T> {
+    let (attempts, delay) = o_open_retry.unwrap_or((1, std::time::Duration::from_secs(0)));
+    let mut attempt = 1;
+    loop {
+        match operation() {
+            Err(e) if attempt < attempts => {
+                if attempt == 1 {
+                    eprintln!(
+                        "[flexi_logger] {} {} failed with {}, retrying",
+                        what,
+                        path.display(),
+                        e
+                    );
+                }
+                attempt += 1;
+                std::thread::sleep(delay);
+            }
+            result => return result,
+        }
+    }
 }
 
 fn get_highest_rotate_idx(filename_config: &FilenameConfig) -> IdxState {
-    match list_of_log_and_zip_files(filename_config) {
+    match list_of_log_and_zip_files(filename_config, false) {
         Err(e) => {
             eprintln!("[flexi_logger] listing rotated log files failed with {}", e);
             IdxState::Start // hope and pray ...??
         }
         Ok(globresults) => {
+            let infix_start = format!("{}r", filename_config.separator);
             let mut highest_idx = IdxState::Start;
             for globresult in globresults {
                 match globresult {
@@ -544,9 +1225,17 @@ fn get_highest_rotate_idx(filename_config: &FilenameConfig) -> IdxState {
                         e
                     ),
                     Ok(pathbuf) => {
-                        let filename = pathbuf.file_stem().unwrap().to_string_lossy();
-                        let mut it = filename.rsplit("_r");
-                        let idx: u32 = it.next().unwrap().parse().unwrap_or(0);
+                        // the file name can have the suffix, or `zip`, or no extension
+                        let filename = pathbuf.file_name().unwrap().to_string_lossy();
+                        let mut it = filename.rsplit(&infix_start);
+                        let idx: u32 = it
+                            .next()
+                            .unwrap()
+                            .split('.')
+                            .next()
+                            .unwrap()
+                            .parse()
+                            .unwrap_or(0);
                         highest_idx = match highest_idx {
                             IdxState::Start => IdxState::Idx(idx),
                             IdxState::Idx(prev) => IdxState::Idx(max(prev, idx)),
@@ -559,18 +1248,28 @@ fn get_highest_rotate_idx(filename_config: &FilenameConfig) -> IdxState {
     }
 }
 
+// With dates, the files of Naming::Dates are listed, otherwise the rotated files
+// of the other namings.
 fn list_of_log_and_zip_files(
     filename_config: &FilenameConfig,
+    dates: bool,
 ) -> Result<std::iter::Chain<glob::Paths, glob::Paths>, FlexiLoggerError> {
     let fn_pattern = String::with_capacity(180)
         .add(&filename_config.file_basename)
-        .add("_r[0-9]*")
-        .add(".");
+        .add(&filename_config.separator)
+        .add(if dates { DATE_INFIX_PATTERN } else { "r[0-9]*" });
 
     let mut log_pattern = filename_config.directory.clone();
-    log_pattern.push(fn_pattern.clone().add(&filename_config.suffix));
+    if dates && filename_config.suffix.is_empty() {
+        log_pattern.push(fn_pattern.clone());
+    } else if filename_config.suffix.is_empty() {
+        // the rotated files end with a digit, which excludes the zip files
+        log_pattern.push(fn_pattern.clone().add("[0-9]"));
+    } else {
+        log_pattern.push(fn_pattern.clone().add(".").add(&filename_config.suffix));
+    }
     let mut zip_pattern = filename_config.directory.clone();
-    zip_pattern.push(fn_pattern.clone().add("zip"));
+    zip_pattern.push(fn_pattern.clone().add(".zip"));
     Ok(glob::glob(&log_pattern.as_os_str().to_string_lossy())?
         .chain(glob::glob(&zip_pattern.as_os_str().to_string_lossy())?))
 }
@@ -578,6 +1277,8 @@ fn list_of_log_and_zip_files(
 fn remove_or_zip_too_old_logfiles(
     cleanup_config: &Cleanup,
     filename_config: &FilenameConfig,
+    dates: bool,
+    current_path: &Path,
 ) -> Result<(), FlexiLoggerError> {
     let (log_limit, zip_limit) = match *cleanup_config {
         Cleanup::Never => {
@@ -590,8 +1291,10 @@ fn remove_or_zip_too_old_logfiles(
         Cleanup::KeepLogAndZipFiles(log_limit, zip_limit) => (log_limit, zip_limit),
     };
     // list files by name, in ascending order
-    let mut file_list: Vec<_> = list_of_log_and_zip_files(&filename_config)?
+    // (with Naming::Dates, the current file matches the pattern, but must not be touched)
+    let mut file_list: Vec<_> = list_of_log_and_zip_files(&filename_config, dates)?
         .filter_map(Result::ok)
+        .filter(|file| file != current_path)
         .collect();
     file_list.sort_unstable();
     let total_number_of_files = file_list.len();
@@ -605,26 +1308,33 @@ fn remove_or_zip_too_old_logfiles(
             // zip, if not yet zipped
             #[cfg(feature = "ziplogs")]
             {
-                if let Some(extension) = file.extension() {
-                    if extension != "zip" {
-                        let mut old_file = File::open(file)?;
-                        let mut zip_file = file.clone();
+                if file
+                    .extension()
+                    .map_or(true, |extension| extension != "zip")
+                {
+                    let mut old_file = File::open(file)?;
+                    let mut zip_file = file.clone();
+                    if filename_config.suffix.is_empty() {
+                        let mut name = zip_file.into_os_string();
+                        name.push(".zip");
+                        zip_file = PathBuf::from(name);
+                    } else {
                         zip_file.set_extension("zip");
-                        let mut zip = zip::ZipWriter::new(File::create(zip_file)?);
-
-                        let options = zip::write::FileOptions::default()
-                            .compression_method(zip::CompressionMethod::Bzip2);
-                        zip.start_file(file.file_name().unwrap().to_string_lossy(), options)?;
-                        {
-                            // streaming does not work easily :-(
-                            // std::io::copy(&mut old_file, &mut zip)?;
-                            let mut buf = Vec::<u8>::new();
-                            old_file.read_to_end(&mut buf)?;
-                            zip.write_all(&buf)?;
-                        }
-                        zip.finish()?;
-                        std::fs::remove_file(&file)?;
                     }
+                    let mut zip = zip::ZipWriter::new(File::create(zip_file)?);
+
+                    let options = zip::write::FileOptions::default()
+                        .compression_method(zip::CompressionMethod::Bzip2);
+                    zip.start_file(file.file_name().unwrap().to_string_lossy(), options)?;
+                    {
+                        // streaming does not work easily :-(
+                        // std::io::copy(&mut old_file, &mut zip)?;
+                        let mut buf = Vec::<u8>::new();
+                        old_file.read_to_end(&mut buf)?;
+                        zip.write_all(&buf)?;
+                    }
+                    zip.finish()?;
+                    std::fs::remove_file(&file)?;
                 }
             }
         }
@@ -641,7 +1351,7 @@ fn rotate_output_file_to_date(
     let current_path = get_filepath(Some(CURRENT_INFIX), &config.filename_config);
 
     let mut rotated_path = get_filepath(
-        Some(&creation_date.format("_r%Y-%m-%d_%H-%M-%S").to_string()),
+        Some(&creation_date.format("r%Y-%m-%d_%H-%M-%S").to_string()),
         &config.filename_config,
     );
     // Check that the target of rename does not yet exist
@@ -650,7 +1360,7 @@ fn rotate_output_file_to_date(
         rotated_path = get_filepath(
             Some(
                 &creation_date
-                    .format("_r%Y-%m-%d_%H-%M-%S")
+                    .format("r%Y-%m-%d_%H-%M-%S")
                     .to_string()
                     .add(&format!("-restart-{}", i)),
             ),
@@ -701,23 +1411,23 @@ fn rotate_output_file_to_idx(
 
 // See documentation of Criterion::Age.
 #[allow(unused_variables)]
-fn get_creation_date(path: &PathBuf) -> Result<DateTime<Local>, FlexiLoggerError> {
+fn get_creation_date(path: &PathBuf, clock: &Clock) -> Result<DateTime<Local>, FlexiLoggerError> {
     // On windows, we know that try_get_creation_date() returns a result, but it is wrong.
     // On linux, we know that try_get_creation_date() returns an error.
     #[cfg(any(target_os = "windows", target_os = "linux"))]
-    return get_fake_creation_date();
+    return get_fake_creation_date(clock);
 
     // On all others of the many platforms, we give the real creation date a try,
-    // and fall back to the fake if it is not available.
+    // unless the clock was replaced, and fall back to the fake if it is not available.
     #[cfg(not(any(target_os = "windows", target_os = "linux")))]
-    match try_get_creation_date(path) {
-        Ok(d) => Ok(d),
-        Err(e) => get_fake_creation_date(),
+    match (&clock.0, try_get_creation_date(path)) {
+        (None, Ok(d)) => Ok(d),
+        _ => get_fake_creation_date(clock),
     }
 }
 
-fn get_fake_creation_date() -> Result<DateTime<Local>, FlexiLoggerError> {
-    Ok(Local::now())
+fn get_fake_creation_date(clock: &Clock) -> Result<DateTime<Local>, FlexiLoggerError> {
+    Ok(clock.now())
 }
 
 #[cfg(not(any(target_os = "windows", target_os = "linux")))]
@@ -725,6 +1435,25 @@ fn try_get_creation_date(path: &PathBuf) -> Result<DateTime<Local>, FlexiLoggerE
     Ok(std::fs::metadata(path)?.created()?.into())
 }
 
+/// Describes where a `FileLogWriter` writes its log files.
+///
+/// Can be retrieved with [`FileLogWriter::log_file_info()`](struct.FileLogWriter.html#method.log_file_info)
+/// or [`ReconfigurationHandle::log_file_info()`](../struct.ReconfigurationHandle.html#method.log_file_info).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LogFileInfo {
+    /// The directory of the log files; it is an absolute path, unless it cannot be resolved.
+    pub directory: PathBuf,
+    /// The first part of the file names, i.e. the program name, followed by the discriminant
+    /// and the timestamp, if these are used.
+    pub basename: String,
+    /// The suffix of the log files.
+    pub suffix: String,
+    /// Whether the log files are rotated.
+    pub rotate: bool,
+    /// The log file that was written when the info was retrieved.
+    pub current_file: PathBuf,
+}
+
 /// A configurable `LogWriter` implementation that writes to a file or a sequence of files.
 ///
 /// See the [module description](index.html) for usage guidance.
@@ -741,44 +1470,114 @@ impl FileLogWriter {
     pub fn builder() -> FileLogWriterBuilder {
         FileLogWriterBuilder {
             discriminant: None,
+            o_basename: None,
             o_rotation_config: None,
             config: FileLogWriterConfig::default(),
             max_log_level: log::LevelFilter::Trace,
         }
     }
 
-    /// Returns a reference to its configured output format function.
+    /// Returns its configured output format.
     #[inline]
-    pub fn format(&self) -> FormatFunction {
-        self.config.format
+    pub fn format(&self) -> Format {
+        self.config.format.clone()
+    }
+
+    /// Describes where the log files are written.
+    pub fn log_file_info(&self) -> LogFileInfo {
+        let filename_config = &self.config.filename_config;
+        let state_guard = self.state.lock().unwrap();
+        let current_file = get_filepath(state_guard.current_infix().as_deref(), filename_config);
+        LogFileInfo {
+            directory: std::fs::canonicalize(&filename_config.directory)
+                .unwrap_or_else(|_| filename_config.directory.clone()),
+            basename: filename_config.file_basename.clone(),
+            suffix: filename_config.suffix.clone(),
+            rotate: state_guard.o_rotation_state.is_some(),
+            current_file: std::fs::canonicalize(&current_file).unwrap_or(current_file),
+        }
+    }
+
+    /// Rotates the log file immediately, regardless of the rotation criterion.
+    ///
+    /// This allows testing the rotation and the cleanup deterministically; see also
+    /// [`FileLogWriterBuilder::clock()`](struct.FileLogWriterBuilder.html#method.clock).
+    ///
+    /// Only available with feature `testing`.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Io` if the writer was built without `rotate()` or with
+    /// `Naming::Dates`, whose files can only change with the day,
+    /// or if renaming or opening the files fails.
+    #[cfg(any(test, feature = "testing"))]
+    pub fn rotate_now(&self) -> Result<(), FlexiLoggerError> {
+        let mut state_guard = self.state.lock().unwrap();
+        match state_guard.o_rotation_state {
+            None => {
+                return Err(FlexiLoggerError::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "rotate_now() needs a FileLogWriter with rotation",
+                )));
+            }
+            Some(RotationState {
+                naming_state: NamingState::Dates,
+                ..
+            }) => {
+                return Err(FlexiLoggerError::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "rotate_now() cannot rotate with Naming::Dates",
+                )));
+            }
+            Some(_) => {}
+        }
+        state_guard.rotate(&self.config)
     }
 
     #[doc(hidden)]
     pub fn current_filename(&self) -> PathBuf {
-        let o_infix = if self
-            .state
-            .lock()
-            .unwrap()
-            .deref()
-            .o_rotation_state
-            .is_some()
-        {
-            Some(CURRENT_INFIX)
-        } else {
-            None
-        };
-        let p_path = get_filepath(o_infix, &self.config.filename_config);
-        p_path.clone()
+        let o_infix = self.state.lock().unwrap().deref().current_infix();
+        get_filepath(o_infix.as_deref(), &self.config.filename_config)
+    }
+}
+
+impl Drop for FileLogWriter {
+    fn drop(&mut self) {
+        if let Ok(state) = self.state.get_mut() {
+            state.close(&self.config);
+        }
     }
 }
 
 impl LogWriter for FileLogWriter {
     #[inline]
     fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        formats::set_current_output(self as *const FileLogWriter as usize, None);
+        let format = self.config.level_formats[record.level() as usize - 1]
+            .as_ref()
+            .unwrap_or(&self.config.format);
+        let matched = self
+            .config
+            .o_rotate_on_match
+            .as_ref()
+            .is_some_and(|pattern| match record.args().as_str() {
+                Some(message) => pattern.is_match(message),
+                None => pattern.is_match(&record.args().to_string()),
+            });
         buffer_with(|tl_buf| match tl_buf.try_borrow_mut() {
             Ok(mut buffer) => {
-                (self.config.format)(&mut *buffer, now, record)
-                    .unwrap_or_else(|e| write_err(ERR_1, e));
+                if !format_with_policy(
+                    format,
+                    self.config.format_error_policy,
+                    self.config.o_max_line_length,
+                    self.config.strip_ansi,
+                    &mut buffer,
+                    now,
+                    record,
+                ) {
+                    buffer.clear();
+                    return;
+                }
 
                 let mut state_guard = self.state.lock().unwrap();
                 let state = state_guard.deref_mut();
@@ -794,19 +1593,39 @@ impl LogWriter for FileLogWriter {
                         eprintln!("[flexi_logger] opening file failed with {}", e);
                     });
 
-                state
-                    .write_buffer(&*buffer)
-                    .unwrap_or_else(|e| write_err(ERR_2, e));
+                state.write_buffer_or_fallback(&buffer, self.config.write_error_policy);
                 buffer.clear();
+                if record.level() == log::Level::Error {
+                    state.write_pending(self.config.write_error_policy);
+                }
+                if matched {
+                    state.rotate_before_next_write();
+                }
+
+                if self.config.sync_on_error && record.level() == log::Level::Error {
+                    state.sync_data().unwrap_or_else(|e| write_err(ERR_SYNC, e));
+                }
             }
             Err(_e) => {
                 // We arrive here in the rare cases of recursive logging
                 // (e.g. log calls in Debug or Display implementations)
                 // we print the inner calls, in chronological order, before finally the
                 // outer most message is printed
+                if !on_recursive_logging() {
+                    return;
+                }
                 let mut tmp_buf = Vec::<u8>::with_capacity(200);
-                (self.config.format)(&mut tmp_buf, now, record)
-                    .unwrap_or_else(|e| write_err(ERR_1, e));
+                if !format_with_policy(
+                    format,
+                    self.config.format_error_policy,
+                    self.config.o_max_line_length,
+                    self.config.strip_ansi,
+                    &mut tmp_buf,
+                    now,
+                    record,
+                ) {
+                    return;
+                }
 
                 let mut state_guard = self.state.lock().unwrap();
                 let state = state_guard.deref_mut();
@@ -815,9 +1634,17 @@ impl LogWriter for FileLogWriter {
                     .write_all(state.line_ending)
                     .unwrap_or_else(|e| write_err(ERR_2, e));
 
-                state
-                    .write_buffer(&tmp_buf)
-                    .unwrap_or_else(|e| write_err(ERR_2, e));
+                state.write_buffer_or_fallback(&tmp_buf, self.config.write_error_policy);
+                if record.level() == log::Level::Error {
+                    state.write_pending(self.config.write_error_policy);
+                }
+                if matched {
+                    state.rotate_before_next_write();
+                }
+
+                if self.config.sync_on_error && record.level() == log::Level::Error {
+                    state.sync_data().unwrap_or_else(|e| write_err(ERR_SYNC, e));
+                }
             }
         });
 
@@ -827,7 +1654,9 @@ impl LogWriter for FileLogWriter {
     #[inline]
     fn flush(&self) -> std::io::Result<()> {
         let mut state_guard = self.state.lock().unwrap();
-        if let Some(file) = state_guard.deref_mut().o_log_file.as_mut() {
+        let state = state_guard.deref_mut();
+        state.write_pending(self.config.write_error_policy);
+        if let Some(file) = state.o_log_file.as_mut() {
             file.flush()
         } else {
             Ok(())
@@ -839,16 +1668,20 @@ impl LogWriter for FileLogWriter {
         self.max_log_level
     }
 
+    fn dropped_count(&self) -> u64 {
+        self.state.lock().unwrap().dropped
+    }
+
+    fn shutdown(&self) {
+        self.state.lock().unwrap().close(&self.config);
+    }
+
     #[doc(hidden)]
     fn validate_logs(&self, expected: &[(&'static str, &'static str, &'static str)]) {
         let mut state_guard = self.state.lock().unwrap(); // : MutexGuard<FileLogWriterState>
 
         let path = get_filepath(
-            state_guard
-                .borrow_mut()
-                .o_rotation_state
-                .as_ref()
-                .map(|_| CURRENT_INFIX),
+            state_guard.borrow_mut().current_infix().as_deref(),
             &self.config.filename_config,
         );
         let f = File::open(path).unwrap();
@@ -873,8 +1706,10 @@ impl LogWriter for FileLogWriter {
     }
 }
 
-const ERR_1: &str = "FileLogWriter: formatting failed with ";
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+
 const ERR_2: &str = "FileLogWriter: writing failed with ";
+const ERR_SYNC: &str = "FileLogWriter: syncing failed";
 
 fn write_err(msg: &str, err: std::io::Error) {
     eprintln!("[flexi_logger] {} with {}", msg, err);
@@ -917,7 +1752,7 @@ mod platform {
 #[cfg(test)]
 mod test {
     use crate::writers::LogWriter;
-    use crate::{Cleanup, Criterion, DeferredNow, Naming};
+    use crate::{Age, Cleanup, Criterion, DeferredNow, Naming};
     use chrono::Local;
 
     use std::ops::Add;
@@ -1025,6 +1860,63 @@ mod test {
         assert!(contains("CURRENT", &ts, NINE));
     }
 
+    #[test]
+    fn test_rotate_with_append_continues_numbering() {
+        let ts = timestamped("true-numbers-continue");
+        let naming = Naming::Numbers;
+
+        // simulate a previous program run that left some rotated files
+        std::fs::create_dir_all(DIRECTORY).unwrap();
+        for (idx, text) in &[("00000", ONE), ("00001", TWO), ("00002", THREE)] {
+            std::fs::write(get_hackyfilepath(idx, &ts), text).unwrap();
+        }
+
+        // ensure this produces 0/1/2/45/6
+        write_loglines(true, naming, &ts, &[FOUR, FIVE, SIX]);
+        assert!(contains("00000", &ts, ONE));
+        assert!(contains("00001", &ts, TWO));
+        assert!(contains("00002", &ts, THREE));
+        assert!(contains("00003", &ts, FOUR));
+        assert!(contains("00003", &ts, FIVE));
+        assert!(contains("CURRENT", &ts, SIX));
+    }
+
+    #[test]
+    fn test_rotate_current_on_startup() {
+        let ts = timestamped("rotate-current");
+        let build = |rotate: bool| {
+            super::FileLogWriter::builder()
+                .directory(DIRECTORY)
+                .discriminant(ts.clone())
+                .rotate(Criterion::Size(100), Naming::Numbers, Cleanup::Never)
+                .append()
+                .rotate_current_on_startup(rotate)
+                .try_build()
+                .unwrap()
+        };
+        let write = |flw: &super::FileLogWriter, text: &str| {
+            write_lines(flw, &[text]);
+            flw.flush().unwrap();
+        };
+
+        // simulate a previous program run that left a CURRENT file
+        std::fs::create_dir_all(DIRECTORY).unwrap();
+        std::fs::write(get_hackyfilepath("CURRENT", &ts), format!("{}\n", ONE)).unwrap();
+
+        // without rotation, the existing CURRENT file is continued
+        write(&build(false), TWO);
+        assert!(contains("CURRENT", &ts, ONE));
+        assert!(contains("CURRENT", &ts, TWO));
+        assert!(not_exists("00000", &ts));
+
+        // with rotation, the existing CURRENT file is rotated away first
+        write(&build(true), THREE);
+        assert!(contains("00000", &ts, ONE));
+        assert!(contains("00000", &ts, TWO));
+        assert!(!contains("CURRENT", &ts, ONE));
+        assert!(contains("CURRENT", &ts, THREE));
+    }
+
     #[test]
     fn test_rotate_no_append_timestamps() {
         // we use timestamp as discriminant to allow repeated runs
@@ -1096,6 +1988,599 @@ mod test {
         // assert!(contains("CURRENT", &ts, NINE));
     }
 
+    #[test]
+    fn test_rotate_without_suffix() {
+        let ts = timestamped("no-suffix");
+        let write = |texts: &[&str]| {
+            let flw = super::FileLogWriter::builder()
+                .directory(DIRECTORY)
+                .discriminant(&ts)
+                .suffix("")
+                .rotate(
+                    Criterion::Size(10),
+                    Naming::Numbers,
+                    Cleanup::KeepLogFiles(2),
+                )
+                .try_build()
+                .unwrap();
+            write_lines(&flw, texts);
+        };
+        let files = || {
+            let mut files: Vec<String> = std::fs::read_dir(DIRECTORY)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+                .filter(|name| name.contains(&ts))
+                .map(|name| name.rsplit("_r").next().unwrap().to_string())
+                .collect();
+            files.sort();
+            files
+        };
+
+        // each line is longer than the maximum size, the cleanup keeps two rotated files
+        write(&[ONE, TWO, THREE, FOUR, FIVE]);
+        assert_eq!(files(), vec!["00002", "00003", "CURRENT"]);
+
+        // the numbering continues after a restart
+        write(&[SIX]);
+        assert_eq!(files(), vec!["00002", "00003", "00004", "CURRENT"]);
+    }
+
+    #[test]
+    fn test_next_scheduled_rotation() {
+        use chrono::TimeZone;
+        let local = |y, mo, d, h, mi, s| Local.with_ymd_and_hms(y, mo, d, h, mi, s).unwrap();
+
+        let created_at = local(2019, 10, 17, 10, 0, 0);
+        assert_eq!(
+            super::next_scheduled_rotation(&created_at, 0, 0),
+            local(2019, 10, 18, 0, 0, 0)
+        );
+        assert_eq!(
+            super::next_scheduled_rotation(&created_at, 12, 30),
+            local(2019, 10, 17, 12, 30, 0)
+        );
+        assert_eq!(
+            super::next_scheduled_rotation(&local(2019, 10, 17, 12, 30, 0), 12, 30),
+            local(2019, 10, 18, 12, 30, 0)
+        );
+        assert_eq!(
+            super::next_scheduled_rotation(&local(2019, 12, 31, 23, 59, 59), 0, 0),
+            local(2020, 1, 1, 0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_rotate_dates() {
+        use crate::Age;
+        use chrono::TimeZone;
+        use std::sync::{Arc, Mutex};
+        let local = |y, mo, d, h, mi, s| Local.with_ymd_and_hms(y, mo, d, h, mi, s).unwrap();
+
+        let ts = timestamped("dates");
+        let now = Arc::new(Mutex::new(local(2019, 10, 17, 10, 0, 0)));
+        let build = |criterion| {
+            let clock_now = Arc::clone(&now);
+            super::FileLogWriter::builder()
+                .directory(DIRECTORY)
+                .discriminant(&ts)
+                .rotate(criterion, Naming::Dates, Cleanup::KeepLogFiles(1))
+                .end_marker("=== END ===")
+                .clock(move || *clock_now.lock().unwrap())
+                .try_build()
+        };
+        let arg0 = std::env::args().next().unwrap();
+        let stem = Path::new(&arg0).file_stem().unwrap().to_string_lossy();
+        let path =
+            |date: &str| PathBuf::from(format!("{}/{}_{}_{}.log", DIRECTORY, stem, ts, date));
+        let content = |date: &str| std::fs::read_to_string(path(date)).unwrap_or_default();
+
+        write_lines(&build(Criterion::Age(Age::Day)).unwrap(), &[ONE]);
+        // a restart continues the file of the day, also without append
+        let flw = build(Criterion::Age(Age::At(0, 0))).unwrap();
+        write_lines(&flw, &[TWO]);
+        assert_eq!(&*flw.current_filename(), &*path("2019-10-17"));
+        assert!(content("2019-10-17").contains(ONE));
+        assert!(content("2019-10-17").contains(TWO));
+        assert!(not_exists("CURRENT", &ts));
+
+        // the file of the day cannot be rotated before the day ends
+        assert!(flw.rotate_now().is_err());
+        assert_eq!(content("2019-10-17").matches("=== END ===").count(), 1);
+
+        // the day changes
+        *now.lock().unwrap() = local(2019, 10, 18, 9, 0, 0);
+        write_lines(&flw, &[THREE]);
+        assert!(content("2019-10-17").ends_with("=== END ===\n"));
+        assert!(content("2019-10-18").contains(THREE));
+
+        // the cleanup finds the files of the previous days
+        *now.lock().unwrap() = local(2019, 10, 19, 9, 0, 0);
+        write_lines(&flw, &[FOUR]);
+        assert!(!path("2019-10-17").exists());
+        assert!(content("2019-10-18").contains(THREE));
+        assert!(content("2019-10-19").contains(FOUR));
+
+        assert!(build(Criterion::Size(100)).is_err());
+        assert!(build(Criterion::Age(Age::Hour)).is_err());
+        // the files are named by the day, so the day must start at midnight
+        assert!(build(Criterion::Age(Age::At(6, 0))).is_err());
+    }
+
+    #[test]
+    fn test_filename_separator() {
+        let ts = timestamped("sep");
+        let flw = super::FileLogWriter::builder()
+            .directory(DIRECTORY)
+            .discriminant(&ts)
+            .filename_separator(".")
+            .rotate(Criterion::Size(10), Naming::Numbers, Cleanup::Never)
+            .try_build()
+            .unwrap();
+        write_lines(&flw, &[ONE, TWO, THREE]);
+
+        let arg0 = std::env::args().next().unwrap();
+        let stem = Path::new(&arg0).file_stem().unwrap().to_string_lossy();
+        let path =
+            |infix: &str| PathBuf::from(format!("{}/{}.{}.{}.log", DIRECTORY, stem, ts, infix));
+        assert_eq!(&*flw.current_filename(), &*path("rCURRENT"));
+        assert!(path("r00000").exists());
+        assert!(path("r00001").exists());
+        assert!(!path("r00002").exists());
+    }
+
+    #[test]
+    fn test_write_bom() {
+        let ts = timestamped("bom");
+        let write_lines = |append: bool, texts: &[&str]| {
+            let flw = super::FileLogWriter::builder()
+                .directory(DIRECTORY)
+                .discriminant(&ts)
+                .rotate(Criterion::Size(40), Naming::Numbers, Cleanup::Never)
+                .o_append(append)
+                .write_bom()
+                .try_build()
+                .unwrap();
+            write_lines(&flw, texts);
+        };
+        let read = |infix: &str| std::fs::read(get_hackyfilepath(infix, &ts)).unwrap();
+
+        write_lines(false, &[ONE]);
+        // a restart with append continues the CURRENT file without a second BOM,
+        // the file that is started with the rotation gets its own
+        write_lines(true, &[TWO, THREE]);
+
+        assert_eq!(
+            read("00000"),
+            b"\xef\xbb\xbfERROR [server] ONE\nERROR [server] TWO\n"
+        );
+        assert_eq!(read("CURRENT"), b"\xef\xbb\xbfERROR [server] THREE\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let ts = timestamped("perm");
+        let build = |append: bool| {
+            super::FileLogWriter::builder()
+                .directory(DIRECTORY)
+                .discriminant(&ts)
+                .rotate(Criterion::Size(10), Naming::Numbers, Cleanup::Never)
+                .o_append(append)
+                .file_permissions(0o600)
+                .try_build()
+                .unwrap()
+        };
+        let mode = |infix: &str| {
+            std::fs::metadata(get_hackyfilepath(infix, &ts))
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o777
+        };
+
+        let flw = build(false);
+        assert_eq!(mode("CURRENT"), 0o600);
+        write_lines(&flw, &[ONE]);
+        drop(flw);
+
+        // a continued file keeps its permissions, a file started with rotation gets them
+        let current = get_hackyfilepath("CURRENT", &ts);
+        std::fs::set_permissions(&current, std::fs::Permissions::from_mode(0o640)).unwrap();
+        let flw = build(true);
+        assert_eq!(mode("CURRENT"), 0o640);
+        write_lines(&flw, &[TWO]);
+        assert_eq!(mode("00000"), 0o640);
+        assert_eq!(mode("CURRENT"), 0o600);
+    }
+
+    #[test]
+    fn test_flush_every() {
+        let ts = timestamped("flush");
+        let flw = super::FileLogWriter::builder()
+            .directory(DIRECTORY)
+            .discriminant(&ts)
+            .rotate(Criterion::Size(1000), Naming::Numbers, Cleanup::Never)
+            .flush_every(3)
+            .try_build()
+            .unwrap();
+        let write = |level: log::Level, text: &str| {
+            write_line(&flw, level, text);
+        };
+        let written_lines = || {
+            std::fs::read_to_string(get_hackyfilepath("CURRENT", &ts))
+                .unwrap()
+                .lines()
+                .count()
+        };
+
+        write(log::Level::Info, ONE);
+        write(log::Level::Info, TWO);
+        assert_eq!(written_lines(), 0);
+        write(log::Level::Info, THREE);
+        assert_eq!(written_lines(), 3);
+
+        // an error-level record is written immediately, with the records before it
+        write(log::Level::Info, FOUR);
+        write(log::Level::Error, FIVE);
+        assert_eq!(written_lines(), 5);
+
+        write(log::Level::Info, SIX);
+        assert_eq!(written_lines(), 5);
+        flw.flush().unwrap();
+        assert_eq!(written_lines(), 6);
+
+        write(log::Level::Info, SEVEN);
+        drop(flw);
+        assert_eq!(written_lines(), 7);
+    }
+
+    #[test]
+    fn test_end_marker() {
+        let ts = timestamped("marker");
+        let write_lines = |texts: &[&str]| {
+            let flw = super::FileLogWriter::builder()
+                .directory(DIRECTORY)
+                .discriminant(&ts)
+                .rotate(Criterion::Size(10), Naming::Numbers, Cleanup::Never)
+                .end_marker("--- END ---")
+                .try_build()
+                .unwrap();
+            write_lines(&flw, texts);
+            flw
+        };
+        let read = |infix: &str| std::fs::read_to_string(get_hackyfilepath(infix, &ts)).unwrap();
+
+        // the rotated file gets the marker, and so does the current file on shutdown
+        let flw = write_lines(&[ONE, TWO]);
+        assert_eq!(read("00000"), "ERROR [server] ONE\n--- END ---\n");
+        assert_eq!(read("CURRENT"), "ERROR [server] TWO\n");
+        flw.shutdown();
+        assert_eq!(read("CURRENT"), "ERROR [server] TWO\n--- END ---\n");
+        drop(flw);
+        assert_eq!(read("CURRENT"), "ERROR [server] TWO\n--- END ---\n");
+
+        // dropping the writer also writes the marker
+        drop(write_lines(&[]));
+        assert_eq!(read("00001"), "ERROR [server] TWO\n--- END ---\n");
+        assert_eq!(read("CURRENT"), "--- END ---\n");
+    }
+
+    #[test]
+    fn test_log_file_info() {
+        let flw = super::FileLogWriter::builder()
+            .directory(DIRECTORY)
+            .discriminant("info")
+            .suffix("txt")
+            .rotate(Criterion::Size(1000), Naming::Numbers, Cleanup::Never)
+            .try_build()
+            .unwrap();
+        let info = flw.log_file_info();
+        assert!(info.directory.is_absolute());
+        assert!(info.directory.ends_with(DIRECTORY));
+        assert!(info.basename.ends_with("_info"), "{}", info.basename);
+        assert_eq!(info.suffix, "txt");
+        assert!(info.rotate);
+        assert_eq!(
+            info.current_file,
+            info.directory
+                .join(format!("{}_rCURRENT.txt", info.basename))
+        );
+    }
+
+    #[test]
+    fn test_sync_on_error() {
+        for sync_on_error in &[false, true] {
+            let mut builder = super::FileLogWriter::builder()
+                .directory(DIRECTORY)
+                .discriminant(timestamped("sync"));
+            if *sync_on_error {
+                builder = builder.sync_on_error();
+            }
+            let flw = builder.try_build().unwrap();
+            write_line(&flw, log::Level::Info, ONE);
+            write_line(&flw, log::Level::Error, TWO);
+            write_line(&flw, log::Level::Warn, THREE);
+            write_line(&flw, log::Level::Error, FOUR);
+
+            // only the error records are synced
+            let expected_syncs = if *sync_on_error { 2 } else { 0 };
+            assert_eq!(flw.state.lock().unwrap().syncs, expected_syncs);
+            assert_eq!(
+                std::fs::read_to_string(flw.current_filename())
+                    .unwrap()
+                    .lines()
+                    .count(),
+                4
+            );
+        }
+    }
+
+    #[test]
+    fn test_rotate_over_lines() {
+        let ts = timestamped("lines");
+        let flw = super::FileLogWriter::builder()
+            .directory(DIRECTORY)
+            .discriminant(&ts)
+            .rotate(Criterion::Size(100_000), Naming::Numbers, Cleanup::Never)
+            .rotate_over_lines(100)
+            .try_build()
+            .unwrap();
+        for i in 0..250 {
+            write_line(&flw, log::Level::Info, &format!("line {}", i));
+        }
+
+        let lines = |infix: &str| {
+            std::fs::read_to_string(get_hackyfilepath(infix, &ts))
+                .unwrap()
+                .lines()
+                .count()
+        };
+        assert_eq!(lines("00000"), 100);
+        assert_eq!(lines("00001"), 100);
+        assert_eq!(lines("CURRENT"), 50);
+        assert!(!get_hackyfilepath("00002", &ts).exists());
+    }
+
+    #[test]
+    fn test_open_retry() {
+        let ts = timestamped("retry");
+        let build = |o_retry: Option<u32>| {
+            let builder = super::FileLogWriter::builder()
+                .directory(DIRECTORY)
+                .discriminant(ts.clone())
+                .rotate(Criterion::Size(1000), Naming::Numbers, Cleanup::Never)
+                .append();
+            match o_retry {
+                Some(attempts) => builder
+                    .open_retry(attempts, std::time::Duration::from_millis(50))
+                    .try_build(),
+                None => builder.try_build(),
+            }
+        };
+
+        // a directory in place of the CURRENT file blocks opening it
+        let blocker = get_hackyfilepath("CURRENT", &ts);
+        std::fs::create_dir_all(&blocker).unwrap();
+        assert!(build(None).is_err());
+
+        // it is removed while the writer is retrying
+        let remover = {
+            let blocker = blocker.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(std::time::Duration::from_millis(100));
+                std::fs::remove_dir(blocker).unwrap();
+            })
+        };
+        let flw = build(Some(40)).unwrap();
+        remover.join().unwrap();
+        write_lines(&flw, &[ONE]);
+        flw.flush().unwrap();
+        assert!(contains("CURRENT", &ts, ONE));
+    }
+
+    #[test]
+    fn test_open_retry_missing_directory() {
+        let ts = timestamped("retry_dir");
+        let directory = format!("{}/missing_{}", DIRECTORY, ts);
+        let build = |o_retry: Option<u32>| {
+            let builder = super::FileLogWriter::builder()
+                .directory(&directory)
+                .discriminant(ts.clone());
+            match o_retry {
+                Some(attempts) => builder
+                    .open_retry(attempts, std::time::Duration::from_millis(50))
+                    .try_build(),
+                None => builder.try_build(),
+            }
+        };
+
+        // a file in place of the directory blocks creating it
+        std::fs::create_dir_all(DIRECTORY).unwrap();
+        std::fs::write(&directory, "").unwrap();
+        assert!(build(None).is_err());
+
+        // it is removed while the writer is retrying
+        let remover = {
+            let directory = directory.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(std::time::Duration::from_millis(100));
+                std::fs::remove_file(directory).unwrap();
+            })
+        };
+        let flw = build(Some(40)).unwrap();
+        remover.join().unwrap();
+        write_lines(&flw, &[ONE]);
+        flw.flush().unwrap();
+        let files: Vec<_> = std::fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(files.len(), 1);
+        assert!(std::fs::read_to_string(&files[0]).unwrap().contains(ONE));
+    }
+
+    #[test]
+    fn test_rotate_on_match() {
+        let ts = timestamped("match");
+        let flw = super::FileLogWriter::builder()
+            .directory(DIRECTORY)
+            .discriminant(&ts)
+            .rotate(Criterion::Size(100_000), Naming::Numbers, Cleanup::Never)
+            .rotate_on_match(regex::Regex::new("=== END OF SESSION ===").unwrap())
+            .try_build()
+            .unwrap();
+        for text in &[
+            "session 1",
+            "=== END OF SESSION ===",
+            "session 2",
+            "more of session 2",
+            "=== END OF SESSION ===",
+            "session 3",
+        ] {
+            write_line(&flw, log::Level::Info, text);
+        }
+
+        let lines = |infix: &str| {
+            std::fs::read_to_string(get_hackyfilepath(infix, &ts))
+                .unwrap()
+                .lines()
+                .count()
+        };
+        assert_eq!(lines("00000"), 2);
+        assert!(contains("00000", &ts, "session 1"));
+        assert_eq!(lines("00001"), 3);
+        assert!(contains("00001", &ts, "more of session 2"));
+        assert_eq!(lines("CURRENT"), 1);
+        assert!(contains("CURRENT", &ts, "session 3"));
+    }
+
+    #[test]
+    fn test_clock_and_rotate_now() {
+        use chrono::TimeZone;
+        use std::sync::{Arc, Mutex};
+        let local = |y, mo, d, h, mi, s| Local.with_ymd_and_hms(y, mo, d, h, mi, s).unwrap();
+
+        let ts = timestamped("clock");
+        let now = Arc::new(Mutex::new(local(2019, 10, 17, 10, 0, 0)));
+        let clock_now = Arc::clone(&now);
+        let flw = super::FileLogWriter::builder()
+            .directory(DIRECTORY)
+            .discriminant(&ts)
+            .rotate(Criterion::Age(Age::Day), Naming::Timestamps, Cleanup::Never)
+            .clock(move || *clock_now.lock().unwrap())
+            .try_build()
+            .unwrap();
+        let write = |message: &str| {
+            write_line(&flw, log::Level::Info, message);
+        };
+
+        write(ONE);
+        *now.lock().unwrap() = local(2019, 10, 17, 23, 59, 59);
+        write(TWO);
+        assert!(!get_hackyfilepath("2019-10-17_10-00-00", &ts).exists());
+
+        // the day changes
+        *now.lock().unwrap() = local(2019, 10, 18, 9, 0, 0);
+        write(THREE);
+        assert!(contains("2019-10-17_10-00-00", &ts, ONE));
+        assert!(contains("2019-10-17_10-00-00", &ts, TWO));
+        assert!(contains("CURRENT", &ts, THREE));
+
+        flw.rotate_now().unwrap();
+        write(FOUR);
+        assert!(contains("2019-10-18_09-00-00", &ts, THREE));
+        assert!(contains("CURRENT", &ts, FOUR));
+        assert!(!contains("CURRENT", &ts, THREE));
+
+        assert!(super::FileLogWriter::builder()
+            .directory(DIRECTORY)
+            .discriminant(&ts)
+            .try_build()
+            .unwrap()
+            .rotate_now()
+            .is_err());
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        fn colored(
+            w: &mut dyn std::io::Write,
+            _now: &mut DeferredNow,
+            record: &log::Record,
+        ) -> Result<(), std::io::Error> {
+            write!(
+                w,
+                "\x1b[1;38;5;196m{}\x1b[0m \x1b(B{}",
+                record.level(),
+                record.args()
+            )
+        }
+        let ts = timestamped("ansi");
+        for strip_ansi in &[false, true] {
+            let discr = format!("{}-{}", ts, strip_ansi);
+            let mut builder = super::FileLogWriter::builder()
+                .directory(DIRECTORY)
+                .discriminant(&discr)
+                .format(colored);
+            if *strip_ansi {
+                builder = builder.strip_ansi();
+            }
+            let flw = builder.try_build().unwrap();
+            write_lines(&flw, &["Größe"]);
+            flw.flush().unwrap();
+            let content = std::fs::read_to_string(flw.current_filename()).unwrap();
+            if *strip_ansi {
+                assert_eq!(content, "ERROR Größe\n");
+            } else {
+                assert_eq!(content, "\x1b[1;38;5;196mERROR\x1b[0m \x1b(BGröße\n");
+            }
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_write_error_policy() {
+        use crate::WriteErrorPolicy;
+        use std::ops::Deref;
+
+        // writing to /dev/full fails like writing to a full disk
+        let discr = timestamped("full");
+        let flw = super::FileLogWriter::builder()
+            .directory(DIRECTORY)
+            .discriminant(&discr)
+            .suppress_timestamp()
+            .try_build()
+            .unwrap();
+        let path = flw.current_filename();
+        std::fs::remove_file(&path).unwrap();
+        std::os::unix::fs::symlink("/dev/full", &path).unwrap();
+        let flw = super::FileLogWriter::builder()
+            .directory(DIRECTORY)
+            .discriminant(&discr)
+            .suppress_timestamp()
+            .append()
+            .write_error_policy(WriteErrorPolicy::StdErr)
+            .try_build()
+            .unwrap();
+
+        write_lines(&flw, &[ONE, TWO, THREE]);
+        assert_eq!(flw.state.lock().unwrap().deref().failed_writes, 3);
+        assert_eq!(flw.dropped_count(), 0);
+
+        let flw = super::FileLogWriter::builder()
+            .directory(DIRECTORY)
+            .discriminant(&discr)
+            .suppress_timestamp()
+            .append()
+            .write_error_policy(WriteErrorPolicy::Drop)
+            .try_build()
+            .unwrap();
+        write_lines(&flw, &[ONE, TWO]);
+        assert_eq!(flw.dropped_count(), 2);
+        std::fs::remove_file(&path).unwrap();
+    }
+
     fn remove(s: &str, discr: &str) {
         std::fs::remove_file(get_hackyfilepath(s, discr)).unwrap();
     }
@@ -1112,7 +2597,7 @@ mod test {
     }
 
     fn get_hackyfilepath(infix: &str, discr: &str) -> Box<Path> {
-        let arg0 = std::env::args().nth(0).unwrap();
+        let arg0 = std::env::args().next().unwrap();
         let mut s_filename = Path::new(&arg0)
             .file_stem()
             .unwrap()
@@ -1128,24 +2613,36 @@ mod test {
         path_buf.into_boxed_path()
     }
 
-    fn write_loglines(append: bool, naming: Naming, discr: &str, texts: &[&'static str]) {
-        let flw = get_file_log_writer(append, naming, discr);
+    // A discriminant with a timestamp, to allow repeated runs.
+    fn timestamped(name: &str) -> String {
+        format!("{}-{}", name, Local::now().format("%Y-%m-%d_%H-%M-%S%.6f"))
+    }
+
+    fn write_loglines(append: bool, naming: Naming, discr: &str, texts: &[&str]) {
+        write_lines(&get_file_log_writer(append, naming, discr), texts);
+    }
+
+    fn write_lines(flw: &crate::writers::FileLogWriter, texts: &[&str]) {
         for text in texts {
-            flw.write(
-                &mut DeferredNow::new(),
-                &log::Record::builder()
-                    .args(format_args!("{}", text))
-                    .level(log::Level::Error)
-                    .target("myApp")
-                    .file(Some("server.rs"))
-                    .line(Some(144))
-                    .module_path(Some("server"))
-                    .build(),
-            )
-            .unwrap();
+            write_line(flw, log::Level::Error, text);
         }
     }
 
+    fn write_line(flw: &crate::writers::FileLogWriter, level: log::Level, text: &str) {
+        flw.write(
+            &mut DeferredNow::new(),
+            &log::Record::builder()
+                .args(format_args!("{}", text))
+                .level(level)
+                .target("myApp")
+                .file(Some("server.rs"))
+                .line(Some(144))
+                .module_path(Some("server"))
+                .build(),
+        )
+        .unwrap();
+    }
+
     fn get_file_log_writer(
         append: bool,
         naming: Naming,
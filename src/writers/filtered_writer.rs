@@ -0,0 +1,139 @@
+use crate::deferred_now::DeferredNow;
+use crate::writers::LogWriter;
+use log::Record;
+use regex::Regex;
+
+/// A `LogWriter` that forwards only those records to another `LogWriter`
+/// whose message matches a regular expression.
+///
+/// The filter is independent of the text filter of the log specification.
+///
+/// ## Example
+///
+/// A writer for audit events that only gets the messages starting with `AUDIT:`:
+///
+/// ```rust
+/// use flexi_logger::writers::{FileLogWriter, FilteredWriter};
+/// use flexi_logger::Logger;
+/// use regex::Regex;
+///
+/// let audit_writer = FilteredWriter::new(
+///     Box::new(
+///         FileLogWriter::builder()
+///             .discriminant("audit")
+/// #           .directory("log_files/filtered_writer")
+///             .try_build()
+///             .unwrap(),
+///     ),
+///     Regex::new("^AUDIT:").unwrap(),
+/// );
+///
+/// Logger::with_str("info")
+///     .add_writer("Audit", Box::new(audit_writer))
+///     .start()
+///     .unwrap();
+/// ```
+pub struct FilteredWriter {
+    inner: Box<dyn LogWriter>,
+    regex: Regex,
+}
+impl FilteredWriter {
+    /// Creates a `FilteredWriter` that forwards the records with matching messages
+    /// to the given writer.
+    pub fn new(inner: Box<dyn LogWriter>, regex: Regex) -> FilteredWriter {
+        FilteredWriter { inner, regex }
+    }
+
+    fn is_match(&self, record: &Record) -> bool {
+        match record.args().as_str() {
+            Some(s) => self.regex.is_match(s),
+            None => self.regex.is_match(&record.args().to_string()),
+        }
+    }
+}
+
+impl LogWriter for FilteredWriter {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        if self.is_match(record) {
+            self.inner.write(now, record)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.inner.max_log_level()
+    }
+
+    fn dropped_count(&self) -> u64 {
+        self.inner.dropped_count()
+    }
+
+    fn shutdown(&self) {
+        self.inner.shutdown();
+    }
+
+    #[doc(hidden)]
+    fn validate_logs(&self, expected: &[(&'static str, &'static str, &'static str)]) {
+        self.inner.validate_logs(expected);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::writers::{FilteredWriter, LogWriter};
+    use crate::DeferredNow;
+    use regex::Regex;
+    use std::sync::{Arc, Mutex};
+
+    // Collects the messages of the records it gets.
+    struct Collector(Arc<Mutex<Vec<String>>>);
+    impl LogWriter for Collector {
+        fn write(&self, _now: &mut DeferredNow, record: &log::Record) -> std::io::Result<()> {
+            self.0.lock().unwrap().push(record.args().to_string());
+            Ok(())
+        }
+        fn flush(&self) -> std::io::Result<()> {
+            Ok(())
+        }
+        fn max_log_level(&self) -> log::LevelFilter {
+            log::LevelFilter::Debug
+        }
+    }
+
+    #[test]
+    fn test_filtered_writer() {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let writer = FilteredWriter::new(
+            Box::new(Collector(Arc::clone(&messages))),
+            Regex::new("^AUDIT:").unwrap(),
+        );
+        assert_eq!(writer.max_log_level(), log::LevelFilter::Debug);
+
+        let user = "alice";
+        for args in &[
+            format_args!("AUDIT: login"),
+            format_args!("AUDIT: logout of {}", user),
+            format_args!("login of {} (AUDIT:)", user),
+            format_args!("something else"),
+        ] {
+            writer
+                .write(
+                    &mut DeferredNow::new(),
+                    &log::Record::builder()
+                        .args(*args)
+                        .level(log::Level::Info)
+                        .build(),
+                )
+                .unwrap();
+        }
+        assert_eq!(
+            *messages.lock().unwrap(),
+            vec!["AUDIT: login", "AUDIT: logout of alice"]
+        );
+    }
+}
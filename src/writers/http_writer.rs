@@ -0,0 +1,444 @@
+use crate::deferred_now::DeferredNow;
+use crate::flexi_error::FlexiLoggerError;
+use crate::formats::{self, ndjson_nested_format};
+use crate::writers::log_writer::LogWriter;
+use crate::writers::QueuePolicy;
+use crate::Format;
+use log::Record;
+
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Builder for `HttpWriter`.
+pub struct HttpWriterBuilder {
+    url: String,
+    format: Format,
+    o_auth_header: Option<String>,
+    max_batch_size: usize,
+    max_batch_delay: Duration,
+    max_retries: u32,
+    retry_interval: Duration,
+    queue_size: usize,
+    queue_policy: QueuePolicy,
+    max_log_level: log::LevelFilter,
+}
+
+impl HttpWriterBuilder {
+    /// Makes the `HttpWriter` use the provided format function for the records,
+    /// rather than the default ([formats::ndjson_nested_format](../fn.ndjson_nested_format.html)).
+    ///
+    /// The format function must produce one JSON value per record.
+    pub fn format<F: Into<Format>>(mut self, format: F) -> HttpWriterBuilder {
+        self.format = format.into();
+        self
+    }
+
+    /// Sets the value of the `Authorization` header of the requests,
+    /// like `Bearer 8a3b2c`.
+    pub fn auth_header<S: Into<String>>(mut self, auth_header: S) -> HttpWriterBuilder {
+        self.o_auth_header = Some(auth_header.into());
+        self
+    }
+
+    /// Sets the number of records that are sent at most with a single request;
+    /// the default is 100.
+    ///
+    /// A batch is sent as soon as it is full.
+    pub fn max_batch_size(mut self, max_batch_size: usize) -> HttpWriterBuilder {
+        self.max_batch_size = std::cmp::max(1, max_batch_size);
+        self
+    }
+
+    /// Sets how long a record waits at most for its batch to be filled up;
+    /// the default is five seconds.
+    pub fn max_batch_delay(mut self, max_batch_delay: Duration) -> HttpWriterBuilder {
+        self.max_batch_delay = max_batch_delay;
+        self
+    }
+
+    /// Sets how often sending a batch is retried after a failure; the default is 3.
+    ///
+    /// The interval between the attempts starts with the
+    /// [`retry_interval`](struct.HttpWriterBuilder.html#method.retry_interval)
+    /// and is doubled with each retry.
+    /// A batch that cannot be sent is dropped, and the failure is reported on stderr.
+    pub fn max_retries(mut self, max_retries: u32) -> HttpWriterBuilder {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets the interval before the first retry; the default is half a second.
+    pub fn retry_interval(mut self, retry_interval: Duration) -> HttpWriterBuilder {
+        self.retry_interval = retry_interval;
+        self
+    }
+
+    /// Sets the number of records that can wait for being sent; the default is 10,000.
+    pub fn queue_size(mut self, queue_size: usize) -> HttpWriterBuilder {
+        self.queue_size = std::cmp::max(1, queue_size);
+        self
+    }
+
+    /// Defines what happens with records that arrive while the queue is full;
+    /// the default is `QueuePolicy::Block`.
+    pub fn queue_policy(mut self, queue_policy: QueuePolicy) -> HttpWriterBuilder {
+        self.queue_policy = queue_policy;
+        self
+    }
+
+    /// Sets the maximum log level that is to be written.
+    pub fn max_log_level(mut self, max_log_level: log::LevelFilter) -> HttpWriterBuilder {
+        self.max_log_level = max_log_level;
+        self
+    }
+
+    /// Produces the `HttpWriter` and starts its sender thread.
+    pub fn try_build(self) -> Result<HttpWriter, FlexiLoggerError> {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(QueueState {
+                queue: VecDeque::new(),
+                dropped: 0,
+                sending: false,
+                flush_requested: false,
+                shutdown: false,
+            }),
+            changed: Condvar::new(),
+            dropped_total: AtomicU64::new(0),
+        });
+        let sender = Sender {
+            url: self.url,
+            o_auth_header: self.o_auth_header,
+            max_batch_size: self.max_batch_size,
+            max_batch_delay: self.max_batch_delay,
+            max_retries: self.max_retries,
+            retry_interval: self.retry_interval,
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(30))
+                .build(),
+            shared: Arc::clone(&shared),
+        };
+        let handle = thread::Builder::new()
+            .name("flexi_logger-http".to_string())
+            .spawn(move || sender.run())?;
+        Ok(HttpWriter {
+            format: self.format,
+            queue_size: self.queue_size,
+            queue_policy: self.queue_policy,
+            max_log_level: self.max_log_level,
+            shared,
+            o_sender_thread: Mutex::new(Some(handle)),
+        })
+    }
+}
+
+/// A `LogWriter` that posts the records in batches to an HTTP endpoint,
+/// e.g. to the REST interface of a cloud log collector.
+///
+/// The records are formatted in the logging thread and handed over to a sender thread,
+/// which collects them and posts each batch as JSON array,
+/// with content type `application/json`.
+/// A batch is sent when it is full, when its oldest record has waited for
+/// the maximum delay, and when the writer is flushed or shut down.
+/// Failed requests are retried; see [`HttpWriterBuilder`](struct.HttpWriterBuilder.html)
+/// for the details of the batching and retry policy.
+///
+/// Only plain `http` URLs are supported; use e.g. a local proxy for TLS.
+///
+/// Only available with feature `http_writer`.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use flexi_logger::writers::HttpWriter;
+/// use flexi_logger::Logger;
+///
+/// let http_writer = HttpWriter::builder("http://localhost:8080/logs")
+///     .auth_header("Bearer 8a3b2c")
+///     .try_build()
+///     .unwrap();
+///
+/// let handle = Logger::with_str("info")
+///     .add_writer("Cloud", Box::new(http_writer))
+///     .start()
+///     .unwrap();
+/// // ...
+/// handle.shutdown();
+/// ```
+///
+/// Call [`ReconfigurationHandle::shutdown()`](../struct.ReconfigurationHandle.html#method.shutdown)
+/// at the end of the program, otherwise the records that are still queued are lost.
+pub struct HttpWriter {
+    format: Format,
+    queue_size: usize,
+    queue_policy: QueuePolicy,
+    max_log_level: log::LevelFilter,
+    shared: Arc<Shared>,
+    o_sender_thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+// The state that is shared between the writer and its sender thread.
+struct Shared {
+    state: Mutex<QueueState>,
+    // is notified with each change of the state
+    changed: Condvar,
+    // the number of records that were dropped so far, for any reason
+    dropped_total: AtomicU64,
+}
+
+struct QueueState {
+    queue: VecDeque<Vec<u8>>,
+    dropped: u64,
+    sending: bool,
+    flush_requested: bool,
+    shutdown: bool,
+}
+
+thread_local! {
+    // Is set in the sender thread, whose own log calls (e.g. of the HTTP client)
+    // must not be fed back into the queue.
+    static IN_SENDER_THREAD: Cell<bool> = const { Cell::new(false) };
+}
+
+impl HttpWriter {
+    /// Instantiates a builder for an `HttpWriter` that posts to the given URL.
+    pub fn builder<S: Into<String>>(url: S) -> HttpWriterBuilder {
+        HttpWriterBuilder {
+            url: url.into(),
+            format: ndjson_nested_format.into(),
+            o_auth_header: None,
+            max_batch_size: 100,
+            max_batch_delay: Duration::from_secs(5),
+            max_retries: 3,
+            retry_interval: Duration::from_millis(500),
+            queue_size: 10_000,
+            queue_policy: QueuePolicy::default(),
+            max_log_level: log::LevelFilter::Trace,
+        }
+    }
+
+    fn enqueue(&self, record: Vec<u8>) {
+        let mut state = self.shared.state.lock().unwrap();
+        if state.shutdown {
+            return;
+        }
+        if state.queue.len() >= self.queue_size {
+            match self.queue_policy {
+                QueuePolicy::Block => {
+                    while state.queue.len() >= self.queue_size && !state.shutdown {
+                        state = self.shared.changed.wait(state).unwrap();
+                    }
+                }
+                QueuePolicy::DropNewest => {
+                    self.shared.dropped_total.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+                QueuePolicy::DropOldest => {
+                    state.queue.pop_front();
+                    self.shared.dropped_total.fetch_add(1, Ordering::Relaxed);
+                }
+                QueuePolicy::DropAndCount => {
+                    state.dropped += 1;
+                    self.shared.dropped_total.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+            }
+        }
+        if state.dropped > 0 {
+            eprintln!(
+                "[flexi_logger] HttpWriter dropped {} records because its queue was full",
+                state.dropped
+            );
+            state.dropped = 0;
+        }
+        state.queue.push_back(record);
+        self.shared.changed.notify_all();
+    }
+}
+
+impl LogWriter for HttpWriter {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        if IN_SENDER_THREAD.with(Cell::get) {
+            return Ok(());
+        }
+        let mut buf = Vec::with_capacity(200);
+        formats::set_current_output(self as *const HttpWriter as usize, None);
+        self.format.write(&mut buf, now, record)?;
+        self.enqueue(buf);
+        Ok(())
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        let mut state = self.shared.state.lock().unwrap();
+        state.flush_requested = true;
+        self.shared.changed.notify_all();
+        while (!state.queue.is_empty() || state.sending) && !state.shutdown {
+            state = self.shared.changed.wait(state).unwrap();
+        }
+        Ok(())
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.max_log_level
+    }
+
+    fn dropped_count(&self) -> u64 {
+        self.shared.dropped_total.load(Ordering::Relaxed)
+    }
+
+    fn shutdown(&self) {
+        {
+            let mut state = self.shared.state.lock().unwrap();
+            state.shutdown = true;
+            self.shared.changed.notify_all();
+        }
+        if let Some(handle) = self.o_sender_thread.lock().unwrap().take() {
+            handle.join().ok();
+        }
+    }
+}
+
+impl Drop for HttpWriter {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+// Runs in the sender thread and posts the batches.
+struct Sender {
+    url: String,
+    o_auth_header: Option<String>,
+    max_batch_size: usize,
+    max_batch_delay: Duration,
+    max_retries: u32,
+    retry_interval: Duration,
+    agent: ureq::Agent,
+    shared: Arc<Shared>,
+}
+impl Sender {
+    fn run(self) {
+        IN_SENDER_THREAD.with(|in_sender_thread| in_sender_thread.set(true));
+        // the time when the oldest record of the current batch arrived
+        let mut o_batch_start: Option<Instant> = None;
+        loop {
+            let (batch, shutdown) = {
+                let mut state = self.shared.state.lock().unwrap();
+                loop {
+                    if state.queue.is_empty() {
+                        o_batch_start = None;
+                        if state.flush_requested {
+                            state.flush_requested = false;
+                            self.shared.changed.notify_all();
+                        }
+                    } else if o_batch_start.is_none() {
+                        o_batch_start = Some(Instant::now());
+                    }
+                    let due = match o_batch_start {
+                        Some(batch_start) => {
+                            state.queue.len() >= self.max_batch_size
+                                || state.flush_requested
+                                || state.shutdown
+                                || batch_start.elapsed() >= self.max_batch_delay
+                        }
+                        None => state.shutdown,
+                    };
+                    if due {
+                        break;
+                    }
+                    state = match o_batch_start {
+                        Some(batch_start) => {
+                            let remaining = self
+                                .max_batch_delay
+                                .checked_sub(batch_start.elapsed())
+                                .unwrap_or_default();
+                            self.shared
+                                .changed
+                                .wait_timeout(state, remaining)
+                                .unwrap()
+                                .0
+                        }
+                        None => self.shared.changed.wait(state).unwrap(),
+                    };
+                }
+                let len = std::cmp::min(state.queue.len(), self.max_batch_size);
+                let batch: Vec<Vec<u8>> = state.queue.drain(..len).collect();
+                state.sending = !batch.is_empty();
+                // there is space in the queue again
+                self.shared.changed.notify_all();
+                (batch, state.shutdown)
+            };
+            o_batch_start = None;
+
+            if !batch.is_empty() {
+                self.send_with_retries(&batch);
+            }
+
+            let mut state = self.shared.state.lock().unwrap();
+            state.sending = false;
+            self.shared.changed.notify_all();
+            if shutdown && state.queue.is_empty() {
+                return;
+            }
+        }
+    }
+
+    fn send_with_retries(&self, batch: &[Vec<u8>]) {
+        let body = json_array(batch);
+        let mut retry_interval = self.retry_interval;
+        let mut attempt = 0;
+        loop {
+            match self.send(&body) {
+                Ok(()) => return,
+                Err(e) => {
+                    if attempt >= self.max_retries {
+                        self.shared
+                            .dropped_total
+                            .fetch_add(batch.len() as u64, Ordering::Relaxed);
+                        eprintln!(
+                            "[flexi_logger] HttpWriter dropped a batch of {} records, \
+                             sending it failed with {}",
+                            batch.len(),
+                            e
+                        );
+                        return;
+                    }
+                    attempt += 1;
+                    thread::sleep(retry_interval);
+                    retry_interval *= 2;
+                }
+            }
+        }
+    }
+
+    fn send(&self, body: &[u8]) -> Result<(), Box<ureq::Error>> {
+        let mut request = self
+            .agent
+            .post(&self.url)
+            .set("Content-Type", "application/json");
+        if let Some(ref auth_header) = self.o_auth_header {
+            request = request.set("Authorization", auth_header);
+        }
+        request
+            .send_bytes(body)
+            .map(|_response| ())
+            .map_err(Box::new)
+    }
+}
+
+// Joins the formatted records to a JSON array.
+fn json_array(batch: &[Vec<u8>]) -> Vec<u8> {
+    let mut body =
+        Vec::with_capacity(batch.iter().map(|record| record.len() + 1).sum::<usize>() + 2);
+    body.push(b'[');
+    for (idx, record) in batch.iter().enumerate() {
+        if idx > 0 {
+            body.push(b',');
+        }
+        body.extend_from_slice(record);
+    }
+    body.push(b']');
+    body
+}
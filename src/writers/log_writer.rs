@@ -13,8 +13,30 @@ pub trait LogWriter: Sync + Send {
     fn flush(&self) -> io::Result<()>;
 
     /// Provides the maximum log level that is to be written.
+    ///
+    /// Records with a more detailed level are not handed to the writer, even if
+    /// the log specification or other writers accept them.
     fn max_log_level(&self) -> log::LevelFilter;
 
+    /// Returns the number of records that the writer has dropped so far,
+    /// e.g. because its queue was full or sending them failed.
+    ///
+    /// Is aggregated by [`ReconfigurationHandle::dropped_count()`](
+    /// ../struct.ReconfigurationHandle.html#method.dropped_count).
+    /// The default implementation returns 0, which is right for writers that never drop records.
+    fn dropped_count(&self) -> u64 {
+        0
+    }
+
+    /// Finishes the output, e.g. by writing buffered records and a final line.
+    ///
+    /// Is called by [`ReconfigurationHandle::shutdown()`](
+    /// ../struct.ReconfigurationHandle.html#method.shutdown).
+    /// The default implementation just flushes.
+    fn shutdown(&self) {
+        self.flush().ok();
+    }
+
     /// Takes a vec with three patterns per line that represent the log out,
     /// compares the written log with the expected lines,
     /// and asserts that both are in sync.
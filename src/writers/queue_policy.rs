@@ -0,0 +1,19 @@
+/// Defines what a `LogWriter` with a bounded queue does when a record arrives
+/// while the queue is full.
+///
+/// Such writers hand the records over to a background thread, which does the actual output.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum QueuePolicy {
+    /// The logging thread waits until there is space in the queue.
+    ///
+    /// No record is lost, but a slow output slows down the program.
+    #[default]
+    Block,
+    /// The new record is dropped.
+    DropNewest,
+    /// The oldest record in the queue is dropped to make space for the new one.
+    DropOldest,
+    /// The new record is dropped, and the number of dropped records is reported
+    /// with the next record that can be written.
+    DropAndCount,
+}
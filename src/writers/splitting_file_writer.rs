@@ -0,0 +1,336 @@
+use crate::deferred_now::DeferredNow;
+use crate::flexi_error::FlexiLoggerError;
+use crate::formats::{self, default_format};
+use crate::primary_writer::strip_ansi_escapes;
+use crate::writers::log_writer::LogWriter;
+use crate::Format;
+use log::Record;
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+type KeyFunction = dyn Fn(&Record) -> Option<String> + Send + Sync;
+
+/// Builder for `SplittingFileWriter`.
+pub struct SplittingFileWriterBuilder {
+    directory: PathBuf,
+    suffix: String,
+    format: Format,
+    strip_ansi: bool,
+    key_function: Box<KeyFunction>,
+    max_open_files: usize,
+    max_log_level: log::LevelFilter,
+}
+
+impl SplittingFileWriterBuilder {
+    /// Makes the `SplittingFileWriter` use the provided format function for the log entries,
+    /// rather than the default ([formats::default_format](../fn.default_format.html)).
+    pub fn format<F: Into<Format>>(mut self, format: F) -> SplittingFileWriterBuilder {
+        self.format = format.into();
+        self
+    }
+
+    /// Makes the `SplittingFileWriter` remove ANSI escape sequences, like the color codes
+    /// of the colored format functions, from the lines it writes.
+    pub fn strip_ansi(mut self) -> SplittingFileWriterBuilder {
+        self.strip_ansi = true;
+        self
+    }
+
+    /// Specifies a folder for the log files.
+    ///
+    /// If the specified folder does not exist, it is created.
+    /// By default, the log files are created in the folder where the program was started.
+    pub fn directory<P: Into<PathBuf>>(mut self, directory: P) -> SplittingFileWriterBuilder {
+        self.directory = directory.into();
+        self
+    }
+
+    /// Specifies a suffix for the log files. The default is "log".
+    ///
+    /// With an empty suffix, the file names have no extension at all.
+    pub fn suffix<S: Into<String>>(mut self, suffix: S) -> SplittingFileWriterBuilder {
+        self.suffix = suffix.into();
+        self
+    }
+
+    /// Limits the number of files that are kept open simultaneously; the default is 20.
+    ///
+    /// If a record is to be written to a file that is not open while the limit is reached,
+    /// the least recently used file is closed.
+    /// Closed files are reopened in append mode when they are needed again.
+    pub fn max_open_files(mut self, max_open_files: usize) -> SplittingFileWriterBuilder {
+        self.max_open_files = std::cmp::max(1, max_open_files);
+        self
+    }
+
+    /// Sets the maximum log level that is to be written.
+    pub fn max_log_level(mut self, max_log_level: log::LevelFilter) -> SplittingFileWriterBuilder {
+        self.max_log_level = max_log_level;
+        self
+    }
+
+    /// Produces the `SplittingFileWriter`.
+    pub fn try_build(self) -> Result<SplittingFileWriter, FlexiLoggerError> {
+        std::fs::create_dir_all(&self.directory)?;
+        if !std::fs::metadata(&self.directory)?.is_dir() {
+            return Err(FlexiLoggerError::BadDirectory);
+        };
+
+        Ok(SplittingFileWriter {
+            directory: self.directory,
+            suffix: self.suffix,
+            format: self.format,
+            strip_ansi: self.strip_ansi,
+            key_function: self.key_function,
+            max_open_files: self.max_open_files,
+            max_log_level: self.max_log_level,
+            state: Mutex::new(SplittingFileWriterState {
+                open_files: HashMap::new(),
+                tick: 0,
+            }),
+        })
+    }
+}
+
+struct OpenFile {
+    file: File,
+    last_used: u64,
+}
+
+// The mutable state of a SplittingFileWriter.
+struct SplittingFileWriterState {
+    open_files: HashMap<String, OpenFile>,
+    tick: u64,
+}
+impl SplittingFileWriterState {
+    fn file_for(
+        &mut self,
+        key: &str,
+        path: &Path,
+        max_open_files: usize,
+    ) -> std::io::Result<&mut File> {
+        self.tick += 1;
+        let tick = self.tick;
+
+        if !self.open_files.contains_key(key) {
+            if self.open_files.len() >= max_open_files {
+                self.close_least_recently_used();
+            }
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            self.open_files.insert(
+                key.to_string(),
+                OpenFile {
+                    file,
+                    last_used: tick,
+                },
+            );
+        }
+
+        let open_file = self.open_files.get_mut(key).unwrap(/*was inserted above*/);
+        open_file.last_used = tick;
+        Ok(&mut open_file.file)
+    }
+
+    fn close_least_recently_used(&mut self) {
+        let o_lru_key = self
+            .open_files
+            .iter()
+            .min_by_key(|(_, open_file)| open_file.last_used)
+            .map(|(key, _)| key.clone());
+        if let Some(lru_key) = o_lru_key {
+            if let Some(mut open_file) = self.open_files.remove(&lru_key) {
+                open_file
+                    .file
+                    .flush()
+                    .unwrap_or_else(|e| write_err(ERR_FLUSHING, &e));
+            }
+        }
+    }
+}
+
+/// A `LogWriter` that writes each record to a file whose name is derived from the record.
+///
+/// This is useful e.g. for multi-tenant services that want a separate log file per tenant.
+/// A function that you provide determines for each record the key,
+/// which is used as the name of the file (without suffix);
+/// characters that are not allowed in file names are replaced with `_`.
+/// Records for which the function returns `None` or an empty key are ignored
+/// (an empty key would result in a hidden file like `.log`).
+///
+/// To avoid running out of file handles, only a limited number of files are kept open
+/// (see [`max_open_files()`](struct.SplittingFileWriterBuilder.html#method.max_open_files)).
+///
+/// ## Example
+///
+/// Records with targets like `tenant:acme` are written to `acme.log`:
+///
+/// ```rust
+/// use flexi_logger::writers::SplittingFileWriter;
+///
+/// let writer = SplittingFileWriter::builder(|record| {
+///     record
+///         .target()
+///         .strip_prefix("tenant:")
+///         .map(|tenant| tenant.to_string())
+/// })
+/// .directory("log_files/tenants")
+/// .try_build()
+/// .unwrap();
+/// ```
+pub struct SplittingFileWriter {
+    directory: PathBuf,
+    suffix: String,
+    format: Format,
+    strip_ansi: bool,
+    key_function: Box<KeyFunction>,
+    max_open_files: usize,
+    max_log_level: log::LevelFilter,
+    state: Mutex<SplittingFileWriterState>,
+}
+impl SplittingFileWriter {
+    /// Instantiates a builder for `SplittingFileWriter`, with the function that derives
+    /// the key from each record.
+    pub fn builder<F>(key_function: F) -> SplittingFileWriterBuilder
+    where
+        F: Fn(&Record) -> Option<String> + Send + Sync + 'static,
+    {
+        SplittingFileWriterBuilder {
+            directory: PathBuf::from("."),
+            suffix: "log".to_string(),
+            format: default_format.into(),
+            strip_ansi: false,
+            key_function: Box::new(key_function),
+            max_open_files: 20,
+            max_log_level: log::LevelFilter::Trace,
+        }
+    }
+
+    fn path_for(&self, key: &str) -> PathBuf {
+        let mut s_filename: String = key
+            .chars()
+            .map(|c| match c {
+                '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+                c if c.is_control() => '_',
+                c => c,
+            })
+            .collect();
+        if s_filename.starts_with('.') {
+            s_filename.replace_range(..1, "_");
+        }
+        if !self.suffix.is_empty() {
+            s_filename += ".";
+            s_filename += &self.suffix;
+        }
+        let mut p_path = self.directory.clone();
+        p_path.push(s_filename);
+        p_path
+    }
+}
+
+impl LogWriter for SplittingFileWriter {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        let key = match (self.key_function)(record) {
+            Some(key) if !key.is_empty() => key,
+            _ => return Ok(()),
+        };
+
+        let mut buf = Vec::<u8>::with_capacity(200);
+        formats::set_current_output(self as *const SplittingFileWriter as usize, None);
+        if let Err(e) = self.format.write(&mut buf, now, record) {
+            write_err(ERR_FORMATTING, &e);
+            return Ok(());
+        }
+        if self.strip_ansi {
+            strip_ansi_escapes(&mut buf, 0);
+        }
+        buf.push(b'\n');
+
+        let path = self.path_for(&key);
+        let mut state = self.state.lock().unwrap();
+        state
+            .file_for(&key, &path, self.max_open_files)
+            .and_then(|file| file.write_all(&buf))
+            .unwrap_or_else(|e| write_err(ERR_WRITING, &e));
+        Ok(())
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        for open_file in state.open_files.values_mut() {
+            open_file.file.flush()?;
+        }
+        Ok(())
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.max_log_level
+    }
+}
+
+const ERR_FORMATTING: &str = "SplittingFileWriter: formatting failed";
+const ERR_WRITING: &str = "SplittingFileWriter: writing failed";
+const ERR_FLUSHING: &str = "SplittingFileWriter: flushing failed";
+
+fn write_err(msg: &str, err: &std::io::Error) {
+    eprintln!("[flexi_logger] {} with {}", msg, err);
+}
+
+#[cfg(test)]
+mod test {
+    use crate::writers::{LogWriter, SplittingFileWriter};
+    use crate::DeferredNow;
+    use chrono::Local;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_splitting_file_writer() {
+        // we use a timestamp in the directory name to allow repeated runs
+        let directory = PathBuf::from(
+            Local::now()
+                .format("log_files/splitting/%Y-%m-%d_%H-%M-%S%.6f")
+                .to_string(),
+        );
+        let writer = SplittingFileWriter::builder(|record| {
+            record
+                .target()
+                .strip_prefix("tenant:")
+                .map(|tenant| tenant.to_string())
+        })
+        .directory(&directory)
+        .max_open_files(2)
+        .try_build()
+        .unwrap();
+
+        for (target, text) in &[
+            ("tenant:acme", "ONE"),
+            ("tenant:emca", "TWO"),
+            ("tenant:../evil", "THREE"),
+            ("tenant:acme", "FOUR"), // reopens the file that was closed
+            ("tenant:", "EMPTY"),    // empty key is ignored
+            ("other", "FIVE"),
+        ] {
+            writer
+                .write(
+                    &mut DeferredNow::new(),
+                    &log::Record::builder()
+                        .args(format_args!("{}", text))
+                        .level(log::Level::Info)
+                        .target(target)
+                        .module_path(Some("server"))
+                        .build(),
+                )
+                .unwrap();
+        }
+        writer.flush().unwrap();
+
+        let read = |name: &str| std::fs::read_to_string(directory.join(name)).unwrap();
+        assert_eq!(read("acme.log"), "INFO [server] ONE\nINFO [server] FOUR\n");
+        assert_eq!(read("emca.log"), "INFO [server] TWO\n");
+        assert_eq!(read("_._evil.log"), "INFO [server] THREE\n");
+        assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 3);
+    }
+}
@@ -0,0 +1,162 @@
+use crate::deferred_now::DeferredNow;
+use crate::formats;
+use crate::primary_writer::strip_ansi_escapes;
+use crate::writers::LogWriter;
+use crate::Format;
+use log::Record;
+use std::io::Write;
+use std::net::Shutdown;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// A `LogWriter` that writes the log lines, separated by newlines, to a Unix domain socket,
+/// e.g. to a log collector that runs as a sidecar.
+///
+/// With feature `kv`, the lines are by default formatted with
+/// [`ndjson_nested_format`](../fn.ndjson_nested_format.html),
+/// otherwise with [`default_format`](../fn.default_format.html);
+/// use [`format()`](struct.UnixSocketWriter.html#method.format) to choose another format.
+///
+/// If the connection breaks, e.g. because the collector is restarted, the writer reconnects
+/// to the socket with the next record. While the socket cannot be reached, the records
+/// are dropped, and only the first failure is reported.
+///
+/// Only available on unix.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use flexi_logger::writers::UnixSocketWriter;
+/// use flexi_logger::Logger;
+///
+/// Logger::with_str("info")
+///     .add_writer(
+///         "Collector",
+///         Box::new(
+///             UnixSocketWriter::try_new("/run/collector.sock", log::LevelFilter::Info).unwrap(),
+///         ),
+///     )
+///     .start()
+///     .unwrap();
+/// ```
+pub struct UnixSocketWriter {
+    path: PathBuf,
+    format: Format,
+    strip_ansi: bool,
+    max_log_level: log::LevelFilter,
+    state: Mutex<SocketState>,
+}
+
+struct SocketState {
+    o_stream: Option<UnixStream>,
+    // true if a failure was reported and no record could be written since
+    failure_reported: bool,
+    dropped: u64,
+}
+
+impl UnixSocketWriter {
+    /// Connects to the socket at the given path.
+    ///
+    /// ## Errors
+    ///
+    /// If the socket cannot be connected.
+    pub fn try_new<P: AsRef<Path>>(
+        path: P,
+        max_log_level: log::LevelFilter,
+    ) -> std::io::Result<UnixSocketWriter> {
+        let path = path.as_ref().to_path_buf();
+        let stream = UnixStream::connect(&path)?;
+        Ok(UnixSocketWriter {
+            path,
+            #[cfg(feature = "kv")]
+            format: crate::formats::ndjson_nested_format.into(),
+            #[cfg(not(feature = "kv"))]
+            format: crate::formats::default_format.into(),
+            strip_ansi: false,
+            max_log_level,
+            state: Mutex::new(SocketState {
+                o_stream: Some(stream),
+                failure_reported: false,
+                dropped: 0,
+            }),
+        })
+    }
+
+    /// Makes the writer use the given format function for the log lines.
+    pub fn format<F: Into<Format>>(mut self, format: F) -> UnixSocketWriter {
+        self.format = format.into();
+        self
+    }
+
+    /// Makes the writer remove ANSI escape sequences, like the color codes
+    /// of the colored format functions, from the log lines.
+    pub fn strip_ansi(mut self) -> UnixSocketWriter {
+        self.strip_ansi = true;
+        self
+    }
+
+    // Writes the line, with one attempt to reconnect if the connection is broken.
+    fn write_line(&self, state: &mut SocketState, line: &[u8]) -> std::io::Result<()> {
+        if let Some(ref mut stream) = state.o_stream {
+            if stream.write_all(line).is_ok() {
+                return Ok(());
+            }
+        }
+        state.o_stream = None;
+        let mut stream = UnixStream::connect(&self.path)?;
+        stream.write_all(line)?;
+        state.o_stream = Some(stream);
+        Ok(())
+    }
+}
+
+impl LogWriter for UnixSocketWriter {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        let mut line = Vec::with_capacity(200);
+        formats::set_current_output(self as *const UnixSocketWriter as usize, None);
+        self.format.write(&mut line, now, record)?;
+        if self.strip_ansi {
+            strip_ansi_escapes(&mut line, 0);
+        }
+        line.push(b'\n');
+
+        let mut state = self.state.lock().unwrap();
+        match self.write_line(&mut state, &line) {
+            Ok(()) => {
+                state.failure_reported = false;
+                Ok(())
+            }
+            Err(e) => {
+                state.dropped += 1;
+                if state.failure_reported {
+                    Ok(())
+                } else {
+                    state.failure_reported = true;
+                    Err(e)
+                }
+            }
+        }
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        match self.state.lock().unwrap().o_stream {
+            Some(ref mut stream) => stream.flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.max_log_level
+    }
+
+    fn dropped_count(&self) -> u64 {
+        self.state.lock().unwrap().dropped
+    }
+
+    fn shutdown(&self) {
+        if let Some(stream) = self.state.lock().unwrap().o_stream.take() {
+            stream.shutdown(Shutdown::Both).ok();
+        }
+    }
+}
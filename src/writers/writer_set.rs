@@ -0,0 +1,71 @@
+use crate::writers::LogWriter;
+
+use std::collections::HashMap;
+
+/// A set of named `LogWriter`s, which can be assembled e.g. from the application's
+/// configuration before the logger is started, and is then registered in one go with
+/// [`Logger::add_writers()`](../struct.Logger.html#method.add_writers).
+///
+/// ```rust
+/// use flexi_logger::writers::{FileLogWriter, WriterSet};
+/// use flexi_logger::Logger;
+///
+/// let writer = |name: &str| {
+///     Box::new(
+///         FileLogWriter::builder()
+///             .discriminant(name)
+/// #           .directory("log_files/writer_set")
+///             .try_build()
+///             .unwrap(),
+///     )
+/// };
+/// let writers = WriterSet::new()
+///     .add("Audit", writer("audit"))
+///     .add("Metrics", writer("metrics"));
+///
+/// Logger::with_str("info")
+///     .add_writers(writers)
+///     .start()
+///     .unwrap();
+/// ```
+#[derive(Default)]
+pub struct WriterSet(HashMap<String, Box<dyn LogWriter>>);
+impl WriterSet {
+    /// Creates an empty set.
+    pub fn new() -> WriterSet {
+        WriterSet(HashMap::new())
+    }
+
+    /// Adds a writer under the given target name; a writer that was added before
+    /// with the same name is replaced.
+    ///
+    /// The target name must not start with an underscore.
+    pub fn add<S: Into<String>>(mut self, target_name: S, writer: Box<dyn LogWriter>) -> WriterSet {
+        self.0.insert(target_name.into(), writer);
+        self
+    }
+
+    /// Returns the number of writers in the set.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if the set contains no writers.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl IntoIterator for WriterSet {
+    type Item = (String, Box<dyn LogWriter>);
+    type IntoIter = std::collections::hash_map::IntoIter<String, Box<dyn LogWriter>>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl From<WriterSet> for HashMap<String, Box<dyn LogWriter>> {
+    fn from(writer_set: WriterSet) -> HashMap<String, Box<dyn LogWriter>> {
+        writer_set.0
+    }
+}
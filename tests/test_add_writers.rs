@@ -0,0 +1,39 @@
+use flexi_logger::writers::{FileLogWriter, LogWriter, WriterSet};
+use flexi_logger::Logger;
+use log::*;
+
+use std::collections::HashMap;
+
+#[test]
+fn test_add_writers() {
+    let mut metrics = HashMap::<String, Box<dyn LogWriter>>::new();
+    metrics.insert("Metrics".to_string(), writer("metrics"));
+
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory("log_files/add_writers")
+        .add_writer("Audit", writer("first_audit"))
+        .add_writers(
+            WriterSet::new()
+                .add("Audit", writer("audit"))
+                .add("Sec", writer("sec")),
+        )
+        .add_writers(metrics)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    assert_eq!(handle.writer_names(), vec!["Audit", "Metrics", "Sec"]);
+
+    error!(target: "{Audit,Metrics,_Default}", "This goes to three writers");
+    handle.validate_logs(&[("ERROR", "test_add_writers", "three writers")]);
+}
+
+fn writer(discriminant: &str) -> Box<dyn LogWriter> {
+    Box::new(
+        FileLogWriter::builder()
+            .directory("log_files/add_writers")
+            .discriminant(discriminant)
+            .try_build()
+            .unwrap(),
+    )
+}
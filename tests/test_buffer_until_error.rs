@@ -0,0 +1,55 @@
+use flexi_logger::{with_sequence_number, Logger};
+use log::*;
+
+#[test]
+fn test_buffer_until_error() {
+    // we use a timestamp in the directory name to allow repeated runs
+    let dir = chrono::Local::now()
+        .format("log_files/buffer_until_error/%Y-%m-%d_%H-%M-%S%.6f")
+        .to_string();
+
+    Logger::with_str("debug")
+        .format(with_sequence_number)
+        .sequence_numbers()
+        .buffer_until_error(3)
+        .log_to_file()
+        .directory(&dir)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("1 is evicted from the buffer");
+    debug!("2 is held back");
+    trace!("not enabled");
+    warn!("3 is held back");
+    info!("4 is held back");
+    log::logger().flush();
+    assert_eq!(read_lines(&dir), Vec::<String>::new());
+
+    error!("5 releases the buffer");
+    debug!("6 is written immediately");
+    log::logger().flush();
+
+    assert_eq!(
+        read_lines(&dir),
+        vec![
+            "#000002 DEBUG [test_buffer_until_error] 2 is held back",
+            "#000003 WARN [test_buffer_until_error] 3 is held back",
+            "#000004 INFO [test_buffer_until_error] 4 is held back",
+            "#000005 ERROR [test_buffer_until_error] 5 releases the buffer",
+            "#000006 DEBUG [test_buffer_until_error] 6 is written immediately",
+        ]
+    );
+}
+
+fn read_lines(dir: &str) -> Vec<String> {
+    let files: Vec<_> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(files.len(), 1);
+    std::fs::read_to_string(&files[0])
+        .unwrap()
+        .lines()
+        .map(ToOwned::to_owned)
+        .collect()
+}
@@ -0,0 +1,26 @@
+#[cfg(feature = "testing")]
+mod test {
+    use flexi_logger::testing;
+    use log::*;
+
+    #[test]
+    fn test_capture() {
+        let (mut handle, lines) = testing::capture();
+
+        error!("This is an error message");
+        trace!("This is a trace message");
+        handle.parse_new_spec("info");
+        debug!("This is a debug message - you must not see it!");
+        info!("The answer is {}", 42);
+
+        assert_eq!(
+            lines(),
+            vec![
+                "ERROR [test_capture::test] This is an error message",
+                "TRACE [test_capture::test] This is a trace message",
+                "INFO [test_capture::test] The answer is 42",
+            ]
+        );
+        assert!(lines().contains(&"INFO [test_capture::test] The answer is 42".to_string()));
+    }
+}
@@ -0,0 +1,56 @@
+#[cfg(all(feature = "kv", feature = "testing"))]
+mod test {
+    use chrono::{Local, TimeZone};
+    use flexi_logger::{clf_format, DeferredNow};
+    use log::kv::Value;
+
+    #[test]
+    fn test_clf_format() {
+        let timestamp = Local.with_ymd_and_hms(2016, 1, 13, 15, 25, 1).unwrap();
+        let expected_ts = timestamp.format("%d/%b/%Y:%H:%M:%S %z").to_string();
+
+        let key_values: &[(&str, Value)] = &[
+            ("remote_addr", Value::from("127.0.0.1")),
+            ("method", Value::from("GET")),
+            ("path", Value::from("/index.html")),
+            ("status", Value::from(200_u16)),
+            ("bytes", Value::from(2326_u64)),
+        ];
+        assert_eq!(
+            format(timestamp, key_values),
+            format!(
+                "127.0.0.1 - - [{}] \"GET /index.html\" 200 2326",
+                expected_ts
+            )
+        );
+
+        // absent fields are written as `-`
+        let key_values: &[(&str, Value)] = &[
+            ("path", Value::from("/say \"hello\"")),
+            ("status", Value::from(404_u16)),
+        ];
+        assert_eq!(
+            format(timestamp, key_values),
+            format!("- - - [{}] \"/say \\\"hello\\\"\" 404 -", expected_ts)
+        );
+        assert_eq!(
+            format(timestamp, &[]),
+            format!("- - - [{}] \"-\" - -", expected_ts)
+        );
+    }
+
+    fn format(timestamp: chrono::DateTime<Local>, key_values: &[(&str, Value)]) -> String {
+        let mut buf = Vec::<u8>::new();
+        clf_format(
+            &mut buf,
+            &mut DeferredNow::with_fixed(timestamp),
+            &log::Record::builder()
+                .args(format_args!("request done"))
+                .level(log::Level::Info)
+                .key_values(&key_values)
+                .build(),
+        )
+        .unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+}
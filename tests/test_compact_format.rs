@@ -0,0 +1,49 @@
+use flexi_logger::writers::LogWriter;
+use flexi_logger::{compact_format, DeferredNow, LogTarget, Logger, Record};
+use log::*;
+
+use std::io;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_compact_format() {
+    let lines = Arc::new(Mutex::new(Vec::<String>::new()));
+    Logger::with_str("info")
+        .log_target(LogTarget::Writer(Box::new(LineWriter(Arc::clone(&lines)))))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("This is an error message");
+    warn!("This is a warning");
+    info!("This is an info message");
+    debug!("This is a debug message - you must not see it!");
+
+    let lines = lines.lock().unwrap();
+    assert_eq!(lines.len(), 3);
+    let pattern = regex::Regex::new(r"^[EWI] \d\d:\d\d:\d\d\.\d{3} This is an? \w+").unwrap();
+    for (line, level) in lines.iter().zip(&["E", "W", "I"]) {
+        assert!(pattern.is_match(line), "unexpected line: {}", line);
+        assert!(
+            line.starts_with(level),
+            "unexpected level in line: {}",
+            line
+        );
+    }
+    assert!(lines[2].ends_with(" This is an info message"));
+}
+
+struct LineWriter(Arc<Mutex<Vec<String>>>);
+impl LogWriter for LineWriter {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> io::Result<()> {
+        let mut buf = Vec::<u8>::new();
+        compact_format(&mut buf, now, record)?;
+        self.0.lock().unwrap().push(String::from_utf8(buf).unwrap());
+        Ok(())
+    }
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+    fn max_log_level(&self) -> log::LevelFilter {
+        log::LevelFilter::Trace
+    }
+}
@@ -0,0 +1,30 @@
+use flexi_logger::Logger;
+use log::*;
+
+#[test]
+fn test_count_levels() {
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory("log_files")
+        .count_levels()
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("This is an error message");
+    error!("This is another error message");
+    warn!("This is a warning");
+    info!("This is an info message");
+    debug!("This is a debug message - you must not see it!");
+    trace!("This is a trace message - you must not see it!");
+
+    assert_eq!(handle.level_counts(), [2, 1, 1, 0, 0]);
+    assert_eq!(handle.summary(), [2, 1, 1, 0, 0]);
+
+    handle.validate_logs(&[
+        ("ERROR", "test_count_levels", "error"),
+        ("ERROR", "test_count_levels", "another error"),
+        ("WARN", "test_count_levels", "warning"),
+        ("INFO", "test_count_levels", "info"),
+        ("INFO", "flexi_logger", "completed with 2 errors, 1 warning"),
+    ]);
+}
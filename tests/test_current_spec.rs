@@ -0,0 +1,32 @@
+use flexi_logger::{LogSpecification, Logger, ModuleFilter};
+use log::*;
+
+#[test]
+fn test_current_spec() {
+    let mut log_handle = Logger::with_str("info")
+        .log_to_file()
+        .directory("log_files/current_spec")
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    assert_eq!(
+        log_handle.current_spec().module_filters(),
+        &vec![ModuleFilter {
+            module_name: None,
+            level_filter: LevelFilter::Info,
+        }]
+    );
+
+    let new_spec = LogSpecification::parse("warn, crate1::mod1=debug/foo").unwrap();
+    log_handle.set_new_spec(new_spec.clone());
+    let current_spec = log_handle.current_spec();
+    assert_eq!(current_spec.module_filters(), new_spec.module_filters());
+    assert_eq!(
+        current_spec
+            .text_filter()
+            .as_ref()
+            .map(|regex| regex.as_str()),
+        Some("foo")
+    );
+    assert_eq!(current_spec.describe(), new_spec.describe());
+}
@@ -0,0 +1,72 @@
+use flexi_logger::{default_format_dedup_timestamp, Duplicate, Logger};
+use log::*;
+
+use std::process::Command;
+
+const CHILD_DIR: &str = "FLEXI_LOGGER_TEST_DEDUP_TIMESTAMP_DIR";
+
+// The test runs itself in a child process, to check what the child writes to stderr.
+#[test]
+fn test_dedup_timestamp() {
+    if let Ok(dir) = std::env::var(CHILD_DIR) {
+        Logger::with_str("info")
+            .format(default_format_dedup_timestamp)
+            .log_to_file()
+            .directory(&dir)
+            .duplicate_to_stderr(Duplicate::Info)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        for i in 0..10 {
+            info!("This is info message {}", i);
+        }
+        log::logger().flush();
+        return;
+    }
+
+    // we use a timestamp in the directory name to allow repeated runs
+    let dir = chrono::Local::now()
+        .format("log_files/dedup_timestamp/%Y-%m-%d_%H-%M-%S%.6f")
+        .to_string();
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(["test_dedup_timestamp", "--exact", "--nocapture"])
+        .env(CHILD_DIR, &dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let files: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(files.len(), 1);
+    // the file and the duplicates on stderr each get their own first timestamp
+    check_lines(&std::fs::read_to_string(&files[0]).unwrap());
+    check_lines(&String::from_utf8(output.stderr).unwrap());
+}
+
+fn check_lines(content: &str) {
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 10, "{}", content);
+
+    // like "[2016-01-13 15:25:01] ", or spaces if the timestamp did not change
+    let mut previous = "";
+    let mut repeated = 0;
+    for (i, line) in lines.iter().enumerate() {
+        let (timestamp, rest) = line.split_at(22);
+        assert_eq!(
+            rest,
+            format!("INFO [test_dedup_timestamp] This is info message {}", i)
+        );
+        if timestamp.trim().is_empty() {
+            assert!(i > 0, "{}", content);
+            repeated += 1;
+        } else {
+            assert!(timestamp.starts_with('[') && timestamp.ends_with("] "));
+            assert_ne!(timestamp, previous);
+            previous = timestamp;
+        }
+    }
+    // the lines were written within at most two seconds
+    assert!(repeated >= 8, "{}", content);
+}
@@ -0,0 +1,48 @@
+#[cfg(feature = "kv")]
+mod test {
+    use flexi_logger::writers::LogWriter;
+    use flexi_logger::{default_format_json_kv, DeferredNow, Logger, Record};
+    use log::*;
+
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_default_format_json_kv() {
+        let lines = Arc::new(Mutex::new(Vec::<String>::new()));
+        Logger::with_str("info")
+            .add_writer("JsonKv", Box::new(JsonKvWriter(Arc::clone(&lines))))
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        warn!(target: "{JsonKv}", req_id = "a\"bc", ms = 12, ok = true; "Request handled");
+        info!(target: "{JsonKv}", "no fields");
+
+        let lines = lines.lock().unwrap();
+        assert_eq!(
+            *lines,
+            vec![
+                "WARN [test_default_format_json_kv::test] Request handled \
+                 {\"req_id\":\"a\\\"bc\",\"ms\":12,\"ok\":true}"
+                    .to_string(),
+                "INFO [test_default_format_json_kv::test] no fields".to_string(),
+            ]
+        );
+    }
+
+    struct JsonKvWriter(Arc<Mutex<Vec<String>>>);
+    impl LogWriter for JsonKvWriter {
+        fn write(&self, now: &mut DeferredNow, record: &Record) -> io::Result<()> {
+            let mut buf = Vec::<u8>::new();
+            default_format_json_kv(&mut buf, now, record)?;
+            self.0.lock().unwrap().push(String::from_utf8(buf).unwrap());
+            Ok(())
+        }
+        fn flush(&self) -> io::Result<()> {
+            Ok(())
+        }
+        fn max_log_level(&self) -> log::LevelFilter {
+            log::LevelFilter::Info
+        }
+    }
+}
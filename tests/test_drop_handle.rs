@@ -0,0 +1,35 @@
+use flexi_logger::writers::{FileLogWriter, LogWriter};
+use flexi_logger::Logger;
+use log::*;
+
+#[test]
+fn test_drop_handle() {
+    // drop the handle immediately
+    Logger::with_str("info")
+        .log_to_file()
+        .directory("log_files")
+        .discriminant("drop_handle")
+        .suppress_timestamp()
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("This is an error message");
+    warn!("This is a warning");
+    info!("This is an info message");
+    debug!("This is a debug message - you must not see it!");
+    trace!("This is a trace message - you must not see it!");
+
+    // reopen the log file with a separate writer to check that logging went on
+    let w = FileLogWriter::builder()
+        .directory("log_files")
+        .discriminant("drop_handle")
+        .suppress_timestamp()
+        .append()
+        .try_build()
+        .unwrap();
+    w.validate_logs(&[
+        ("ERROR", "test_drop_handle", "error"),
+        ("WARN", "test_drop_handle", "warning"),
+        ("INFO", "test_drop_handle", "info"),
+    ]);
+}
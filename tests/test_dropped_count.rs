@@ -0,0 +1,49 @@
+use flexi_logger::writers::LogWriter;
+use flexi_logger::{DeferredNow, Logger, Record};
+use log::*;
+
+use std::io;
+
+#[test]
+fn test_dropped_count() {
+    let handle = Logger::with_str("debug")
+        .log_to_file()
+        .directory("log_files/dropped_count")
+        .sample(Level::Debug, 3)
+        .add_writer("Lossy", Box::new(LossyWriter))
+        .add_writer_with_level("LossyWarn", Box::new(LossyWriter), LevelFilter::Warn)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    assert_eq!(handle.dropped_count(), 10);
+
+    info!("This is an info message");
+    for i in 0..6 {
+        debug!("This is debug message {}", i);
+    }
+
+    // four debug messages were dropped by sampling
+    assert_eq!(handle.dropped_count(), 14);
+    handle.validate_logs(&[
+        ("INFO", "test_dropped_count", "info message"),
+        ("DEBUG", "test_dropped_count", "debug message 0"),
+        ("DEBUG", "test_dropped_count", "debug message 3"),
+    ]);
+}
+
+// pretends to have lost some records
+struct LossyWriter;
+impl LogWriter for LossyWriter {
+    fn write(&self, _now: &mut DeferredNow, _record: &Record) -> io::Result<()> {
+        Ok(())
+    }
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+    fn max_log_level(&self) -> log::LevelFilter {
+        log::LevelFilter::Trace
+    }
+    fn dropped_count(&self) -> u64 {
+        5
+    }
+}
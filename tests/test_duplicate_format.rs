@@ -0,0 +1,77 @@
+use flexi_logger::{default_format, DeferredNow, Duplicate, DuplicateFormat, Logger, Record};
+use log::*;
+
+use std::process::Command;
+
+const CHILD_FORMAT: &str = "FLEXI_LOGGER_TEST_DUPLICATE_FORMAT";
+const CHILD_DIR: &str = "FLEXI_LOGGER_TEST_DUPLICATE_FORMAT_DIR";
+
+// The test runs itself in child processes, to check what the children write to stderr.
+#[test]
+fn test_duplicate_format() {
+    if let Ok(duplicate_format) = std::env::var(CHILD_FORMAT) {
+        Logger::with_str("info")
+            .format_for_files(default_format)
+            .format_for_stderr(stderr_format)
+            .log_to_file()
+            .directory(std::env::var(CHILD_DIR).unwrap())
+            .duplicate_to_stderr(Duplicate::Warn)
+            .duplicate_format(match duplicate_format.as_str() {
+                "MatchFile" => DuplicateFormat::MatchFile,
+                _ => DuplicateFormat::UseStderrFormat,
+            })
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        error!("This is an error message");
+        info!("This is an info message");
+        return;
+    }
+
+    // we use a timestamp in the directory name to allow repeated runs
+    let ts = chrono::Local::now()
+        .format("log_files/duplicate_format/%Y-%m-%d_%H-%M-%S%.6f")
+        .to_string();
+    for (duplicate_format, expected_stderr) in &[
+        (
+            "MatchFile",
+            "ERROR [test_duplicate_format] This is an error message\n",
+        ),
+        ("UseStderrFormat", "stderr: This is an error message\n"),
+    ] {
+        let dir = format!("{}/{}", ts, duplicate_format);
+        let output = Command::new(std::env::current_exe().unwrap())
+            .args(["test_duplicate_format", "--exact", "--nocapture"])
+            .env(CHILD_FORMAT, duplicate_format)
+            .env(CHILD_DIR, &dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8(output.stderr).unwrap(),
+            *expected_stderr,
+            "{}",
+            duplicate_format
+        );
+
+        // the file is not affected
+        let files: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(files.len(), 1);
+        assert_eq!(
+            std::fs::read_to_string(&files[0]).unwrap(),
+            "ERROR [test_duplicate_format] This is an error message\n\
+             INFO [test_duplicate_format] This is an info message\n"
+        );
+    }
+}
+
+fn stderr_format(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    write!(w, "stderr: {}", record.args())
+}
@@ -0,0 +1,32 @@
+use flexi_logger::{detailed_format, DuplicateTarget, Logger};
+use log::*;
+use std::collections::HashMap;
+
+#[test]
+fn test_duplicate_map() {
+    let mut duplicate_map = HashMap::new();
+    duplicate_map.insert(Level::Error, DuplicateTarget::StdErr);
+    duplicate_map.insert(Level::Warn, DuplicateTarget::StdErr);
+    duplicate_map.insert(Level::Info, DuplicateTarget::StdOut);
+    duplicate_map.insert(Level::Debug, DuplicateTarget::None);
+
+    let handle = Logger::with_str("debug")
+        .format(detailed_format)
+        .log_to_file()
+        .duplicate_map(duplicate_map)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("This is an error message, also on stderr");
+    warn!("This is a warning, also on stderr");
+    info!("This is an info message, also on stdout");
+    debug!("This is a debug message, only in the file");
+    trace!("This is a trace message - you must not see it!");
+
+    handle.validate_logs(&[
+        ("ERROR", "test_duplicate_map", "error"),
+        ("WARN", "test_duplicate_map", "warning"),
+        ("INFO", "test_duplicate_map", "info"),
+        ("DEBUG", "test_duplicate_map", "debug"),
+    ]);
+}
@@ -0,0 +1,69 @@
+use flexi_logger::{default_format, Logger};
+use log::*;
+
+#[test]
+fn test_error_file() {
+    // we use a timestamp in the directory name to allow repeated runs
+    let dir = chrono::Local::now()
+        .format("log_files/error_file/%Y-%m-%d_%H-%M-%S%.6f")
+        .to_string();
+
+    let handle = Logger::with_str("info")
+        .format(default_format)
+        .log_to_file()
+        .directory(&dir)
+        .discriminant("app")
+        .error_file(None)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("This is an error message");
+    warn!("This is a warning");
+    info!("This is an info message");
+    error!("This is another error message");
+    log::logger().flush();
+
+    // the error file validates only the errors
+    handle.validate_logs(&[
+        ("ERROR", "test_error_file", "an error message"),
+        ("WARN", "test_error_file", "a warning"),
+        ("INFO", "test_error_file", "an info message"),
+        ("ERROR", "test_error_file", "another error message"),
+    ]);
+
+    let mut files: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    files.sort();
+    assert_eq!(files.len(), 2);
+    let file_name = |idx: usize| {
+        files[idx]
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .to_string()
+    };
+    assert!(file_name(0).starts_with("test_error_file-"));
+    assert!(file_name(0).contains("_app_"));
+    assert!(file_name(1).contains("_app_error_"));
+
+    let main_content = std::fs::read_to_string(&files[0]).unwrap();
+    assert_eq!(
+        main_content.lines().collect::<Vec<_>>(),
+        vec![
+            "ERROR [test_error_file] This is an error message",
+            "WARN [test_error_file] This is a warning",
+            "INFO [test_error_file] This is an info message",
+            "ERROR [test_error_file] This is another error message",
+        ]
+    );
+    let error_content = std::fs::read_to_string(&files[1]).unwrap();
+    assert_eq!(
+        error_content.lines().collect::<Vec<_>>(),
+        vec![
+            "ERROR [test_error_file] This is an error message",
+            "ERROR [test_error_file] This is another error message",
+        ]
+    );
+}
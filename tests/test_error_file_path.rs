@@ -0,0 +1,49 @@
+use flexi_logger::{default_format, Logger};
+use log::*;
+
+#[test]
+fn test_error_file_path() {
+    // we use a timestamp in the directory name to allow repeated runs
+    let dir = chrono::Local::now()
+        .format("log_files/error_file_path/%Y-%m-%d_%H-%M-%S%.6f")
+        .to_string();
+    let error_file = std::path::Path::new(&dir)
+        .join("errors")
+        .join("app.error.log");
+
+    Logger::with_str("info")
+        .format(default_format)
+        .log_to_file()
+        .directory(&dir)
+        .error_file(Some(error_file.clone()))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("This is an error message");
+    info!("This is an info message");
+    log::logger().flush();
+
+    let main_files: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_file())
+        .collect();
+    assert_eq!(main_files.len(), 1);
+    assert_eq!(
+        std::fs::read_to_string(&main_files[0])
+            .unwrap()
+            .lines()
+            .collect::<Vec<_>>(),
+        vec![
+            "ERROR [test_error_file_path] This is an error message",
+            "INFO [test_error_file_path] This is an info message",
+        ]
+    );
+    assert_eq!(
+        std::fs::read_to_string(&error_file)
+            .unwrap()
+            .lines()
+            .collect::<Vec<_>>(),
+        vec!["ERROR [test_error_file_path] This is an error message"]
+    );
+}
@@ -0,0 +1,29 @@
+#[cfg(feature = "testing")]
+mod test {
+    use flexi_logger::testing::expect_no_logs_above;
+    use log::*;
+
+    #[test]
+    fn test_expect_no_logs_above() {
+        {
+            let _guard = expect_no_logs_above(Level::Warn);
+            info!("This is an info message");
+            debug!("This is a debug message");
+        }
+
+        let result = std::panic::catch_unwind(|| {
+            let _guard = expect_no_logs_above(Level::Warn);
+            info!("This is an info message");
+            warn!("This is an unexpected warning");
+        });
+        let panic = result.unwrap_err();
+        assert_eq!(
+            panic.downcast_ref::<String>().unwrap(),
+            "1 records with level WARN or a more severe level were logged"
+        );
+
+        // only the records during the lifetime of the guard count
+        let _guard = expect_no_logs_above(Level::Error);
+        warn!("This is a warning");
+    }
+}
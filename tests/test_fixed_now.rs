@@ -0,0 +1,44 @@
+#[cfg(feature = "testing")]
+mod test {
+    use chrono::{Local, TimeZone};
+    use flexi_logger::{detailed_format, opt_format, DeferredNow, FormatFunction};
+
+    #[test]
+    fn test_fixed_now() {
+        let timestamp = Local.with_ymd_and_hms(2019, 10, 24, 8, 30, 0).unwrap();
+        let offset = timestamp.format("%:z");
+
+        assert_eq!(
+            format_with(opt_format, &mut DeferredNow::with_fixed(timestamp)),
+            format!(
+                "[2019-10-24 08:30:00.000000 {}] INFO [src/foo/bar.rs:26] Task successfully read",
+                offset
+            )
+        );
+        assert_eq!(
+            format_with(detailed_format, &mut DeferredNow::with_fixed(timestamp)),
+            format!(
+                "[2019-10-24 08:30:00.000000 {}] INFO [foo::bar] src/foo/bar.rs:26: \
+                 Task successfully read",
+                offset
+            )
+        );
+    }
+
+    fn format_with(format: FormatFunction, now: &mut DeferredNow) -> String {
+        let mut buf = Vec::<u8>::new();
+        format(
+            &mut buf,
+            now,
+            &log::Record::builder()
+                .args(format_args!("Task successfully read"))
+                .level(log::Level::Info)
+                .module_path(Some("foo::bar"))
+                .file(Some("src/foo/bar.rs"))
+                .line(Some(26))
+                .build(),
+        )
+        .unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+}
@@ -0,0 +1,47 @@
+use flexi_logger::writers::LogWriter;
+use flexi_logger::{DeferredNow, Logger, Record};
+use log::*;
+
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[test]
+fn test_flush_guard() {
+    let flushes = Arc::new(AtomicUsize::new(0));
+    let handle = Logger::with_str("info")
+        .do_not_log()
+        .add_writer("Counter", Box::new(FlushCounter(Arc::clone(&flushes))))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    {
+        let _guard = handle.flush_guard();
+        info!(target: "{Counter}", "This is an info message");
+        assert_eq!(flushes.load(Ordering::SeqCst), 0);
+    }
+    assert_eq!(flushes.load(Ordering::SeqCst), 1);
+
+    let result = std::panic::catch_unwind(|| {
+        let _guard = handle.flush_guard();
+        info!(target: "{Counter}", "This is an info message before a panic");
+        panic!("unwinding drops the guard");
+    });
+    assert!(result.is_err());
+    assert_eq!(flushes.load(Ordering::SeqCst), 2);
+}
+
+// counts the calls of flush()
+struct FlushCounter(Arc<AtomicUsize>);
+impl LogWriter for FlushCounter {
+    fn write(&self, _now: &mut DeferredNow, _record: &Record) -> io::Result<()> {
+        Ok(())
+    }
+    fn flush(&self) -> io::Result<()> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+    fn max_log_level(&self) -> log::LevelFilter {
+        log::LevelFilter::Trace
+    }
+}
@@ -0,0 +1,90 @@
+use flexi_logger::writers::{FileLogWriter, LogWriter};
+use flexi_logger::{DeferredNow, FormatErrorPolicy, Logger, Record};
+use log::*;
+
+// fails for all messages that contain "bad"
+fn picky_format(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    let msg = record.args().to_string();
+    if msg.contains("bad") {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "bad message",
+        ))
+    } else {
+        write!(w, "PICKY {} {}", record.level(), msg)
+    }
+}
+
+#[test]
+fn test_format_error_policy() {
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory("log_files")
+        .format(picky_format)
+        .format_error_policy(FormatErrorPolicy::Fallback)
+        .add_writer(
+            "Drop",
+            Box::new(picky_writer("policy_drop", FormatErrorPolicy::Drop)),
+        )
+        .add_writer(
+            "Panic",
+            Box::new(picky_writer("policy_panic", FormatErrorPolicy::Panic)),
+        )
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    // Fallback
+    error!("This is an error message");
+    warn!("This is a bad warning");
+    info!("This is an info message");
+
+    handle.validate_logs(&[
+        ("PICKY", "ERROR", "error"),
+        ("WARN", "[test_format_error_policy]", "bad warning"),
+        ("PICKY", "INFO", "info"),
+    ]);
+
+    // Drop
+    error!(target: "{Drop}", "This is an error message");
+    warn!(target: "{Drop}", "This is a bad warning");
+    info!(target: "{Drop}", "This is an info message");
+
+    handle.flush();
+    reopen("policy_drop").validate_logs(&[("PICKY", "ERROR", "error"), ("PICKY", "INFO", "info")]);
+
+    // Panic
+    error!(target: "{Panic}", "This is an error message");
+    let result = std::panic::catch_unwind(|| {
+        warn!(target: "{Panic}", "This is a bad warning");
+    });
+    assert!(result.is_err());
+
+    handle.flush();
+    reopen("policy_panic").validate_logs(&[("PICKY", "ERROR", "error")]);
+}
+
+fn picky_writer(discriminant: &str, policy: FormatErrorPolicy) -> FileLogWriter {
+    FileLogWriter::builder()
+        .directory("log_files")
+        .discriminant(discriminant)
+        .suppress_timestamp()
+        .format(picky_format)
+        .format_error_policy(policy)
+        .try_build()
+        .unwrap()
+}
+
+// opens the log file of a picky_writer again, without truncating it
+fn reopen(discriminant: &str) -> FileLogWriter {
+    FileLogWriter::builder()
+        .directory("log_files")
+        .discriminant(discriminant)
+        .suppress_timestamp()
+        .append()
+        .try_build()
+        .unwrap()
+}
@@ -0,0 +1,38 @@
+#[cfg(feature = "kv")]
+mod test {
+    use flexi_logger::Logger;
+    use log::*;
+
+    #[test]
+    fn test_format_json() {
+        let handle = Logger::with_str("info")
+            .log_to_file()
+            .directory("log_files/format_json")
+            .format_json()
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        error!("This is an error message");
+        warn!(count = 3; "This is a warning");
+        info!("This is an info message");
+        debug!("This is a debug message - you must not see it!");
+
+        handle.validate_logs(&[
+            (
+                "{\"ts\":\"",
+                "\"level\":\"ERROR\",\"module\":\"test_format_json::test\"",
+                "\"msg\":\"This is an error message\",\"fields\":{}}",
+            ),
+            (
+                "{\"ts\":\"",
+                "\"level\":\"WARN\",\"module\":\"test_format_json::test\"",
+                "\"msg\":\"This is a warning\",\"fields\":{\"count\":3}}",
+            ),
+            (
+                "{\"ts\":\"",
+                "\"level\":\"INFO\",\"module\":\"test_format_json::test\"",
+                "\"msg\":\"This is an info message\",\"fields\":{}}",
+            ),
+        ]);
+    }
+}
@@ -0,0 +1,50 @@
+use flexi_logger::{DeferredNow, Logger, Record};
+use log::*;
+use std::fmt;
+
+// panics for all messages that contain "bad"
+fn buggy_format(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    let msg = record.args().to_string();
+    if msg.contains("bad") {
+        panic!("cannot format {}", msg);
+    }
+    write!(w, "BUGGY {} {}", record.level(), msg)
+}
+
+// panics when it is displayed
+struct Explosive;
+impl fmt::Display for Explosive {
+    fn fmt(&self, _f: &mut fmt::Formatter) -> fmt::Result {
+        panic!("boom");
+    }
+}
+
+#[test]
+fn test_format_panic() {
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory("log_files")
+        .format(buggy_format)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("This is an error message");
+    warn!("This is a bad warning");
+    warn!("This is an {} warning", Explosive);
+    info!("This is an info message");
+
+    handle.validate_logs(&[
+        ("BUGGY", "ERROR", "error"),
+        ("WARN", "[test_format_panic]", "bad warning"),
+        (
+            "WARN",
+            "[test_format_panic]",
+            "<formatting the message panicked>",
+        ),
+        ("BUGGY", "INFO", "info"),
+    ]);
+}
@@ -0,0 +1,50 @@
+use flexi_logger::{default_format, detailed_format, Format, Logger};
+use log::*;
+use std::collections::HashMap;
+
+#[test]
+fn test_format_per_level() {
+    // we use a timestamp in the directory name to allow repeated runs
+    let dir = chrono::Local::now()
+        .format("log_files/format_per_level/%Y-%m-%d_%H-%M-%S%.6f")
+        .to_string();
+
+    let mut formats = HashMap::<Level, Format>::new();
+    formats.insert(Level::Error, detailed_format.into());
+
+    Logger::with_str("info")
+        .format(default_format)
+        .format_per_level(formats)
+        .log_to_file()
+        .directory(&dir)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("This is an error message");
+    warn!("This is a warning");
+    info!("This is an info message");
+    log::logger().flush();
+
+    let files: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(files.len(), 1);
+    let content = std::fs::read_to_string(&files[0]).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with('['), "{}", lines[0]);
+    assert!(
+        lines[0].ends_with(
+            "ERROR [test_format_per_level] tests/test_format_per_level.rs:23: \
+             This is an error message"
+        ),
+        "{}",
+        lines[0]
+    );
+    assert_eq!(lines[1], "WARN [test_format_per_level] This is a warning");
+    assert_eq!(
+        lines[2],
+        "INFO [test_format_per_level] This is an info message"
+    );
+}
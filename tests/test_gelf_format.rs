@@ -0,0 +1,65 @@
+#[cfg(feature = "gelf")]
+mod test {
+    use flexi_logger::writers::LogWriter;
+    use flexi_logger::{gelf_format, DeferredNow, Logger, Record};
+    use log::*;
+
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_gelf_format() {
+        let lines = Arc::new(Mutex::new(Vec::<String>::new()));
+        Logger::with_str("info")
+            .add_writer("Gelf", Box::new(GelfWriter(Arc::clone(&lines))))
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        error!(target: "{Gelf}", user = "karl", count = 42_i64; "Task \"failed\"");
+        info!(target: "{Gelf}", "hello");
+
+        let lines = lines.lock().unwrap();
+        assert_eq!(lines.len(), 2);
+        for line in lines.iter() {
+            assert!(line.starts_with("{\"version\":\"1.1\",\"host\":\""));
+            assert!(line.ends_with('}'));
+            assert!(!line.contains('\n'));
+        }
+
+        // the timestamp has seconds with milliseconds
+        let ts = lines[0]
+            .split("\"timestamp\":")
+            .nth(1)
+            .unwrap()
+            .split(',')
+            .next()
+            .unwrap();
+        let (secs, millis) = ts.split_at(ts.find('.').unwrap());
+        assert!(secs.parse::<i64>().unwrap() > 1_500_000_000);
+        assert_eq!(millis.len(), 4);
+
+        assert!(lines[0].contains(",\"short_message\":\"Task \\\"failed\\\"\","));
+        assert!(lines[0].ends_with(
+            ",\"level\":3,\"_module\":\"test_gelf_format::test\",\
+             \"_user\":\"karl\",\"_count\":42}"
+        ));
+        assert!(lines[1].contains(",\"short_message\":\"hello\","));
+        assert!(lines[1].ends_with(",\"level\":6,\"_module\":\"test_gelf_format::test\"}"));
+    }
+
+    struct GelfWriter(Arc<Mutex<Vec<String>>>);
+    impl LogWriter for GelfWriter {
+        fn write(&self, now: &mut DeferredNow, record: &Record) -> io::Result<()> {
+            let mut buf = Vec::<u8>::new();
+            gelf_format(&mut buf, now, record)?;
+            self.0.lock().unwrap().push(String::from_utf8(buf).unwrap());
+            Ok(())
+        }
+        fn flush(&self) -> io::Result<()> {
+            Ok(())
+        }
+        fn max_log_level(&self) -> log::LevelFilter {
+            log::LevelFilter::Info
+        }
+    }
+}
@@ -0,0 +1,43 @@
+#[cfg(feature = "colors")]
+mod test {
+    use flexi_logger::{colored_default_format, Logger};
+    use log::*;
+    use regex::Regex;
+    use yansi::{Color, Style};
+
+    #[test]
+    fn test_highlight() {
+        let handle = Logger::with_str("info")
+            .format(colored_default_format)
+            .log_to_file()
+            .directory("log_files")
+            .highlight(Regex::new("PANIC").unwrap(), Style::new(Color::Red))
+            .highlight(Regex::new("PAN").unwrap(), Style::new(Color::Green))
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        info!("all good");
+        info!("PANIC in worker 3");
+        warn!("PANIC in worker 4");
+        info!("PANDA spotted");
+
+        handle.validate_logs(&[
+            ("INFO", "test_highlight", "] all good"),
+            (
+                "INFO",
+                "test_highlight",
+                "] \u{1b}[31mPANIC in worker 3\u{1b}[0m",
+            ),
+            (
+                "WARN",
+                "test_highlight",
+                "] \u{1b}[31mPANIC in worker 4\u{1b}[0m",
+            ),
+            (
+                "INFO",
+                "test_highlight",
+                "] \u{1b}[32mPANDA spotted\u{1b}[0m",
+            ),
+        ]);
+    }
+}
@@ -0,0 +1,49 @@
+#[cfg(feature = "hostname")]
+mod test {
+    use flexi_logger::{hostname, with_hostname, Logger};
+    use log::*;
+
+    #[test]
+    fn test_hostname() {
+        // we use a timestamp in the directory name to allow repeated runs
+        let dir = chrono::Local::now()
+            .format("log_files/hostname/%Y-%m-%d_%H-%M-%S%.6f")
+            .to_string();
+
+        assert_eq!(hostname(), None);
+        Logger::with_str("info")
+            .format(with_hostname)
+            .with_hostname()
+            .log_to_file()
+            .directory(&dir)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+        let host = hostname().unwrap();
+        assert!(!host.is_empty());
+
+        error!("This is an error message");
+        info!("This is an info message");
+        log::logger().flush();
+
+        let files: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(files.len(), 1);
+        let content = std::fs::read_to_string(&files[0]).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                format!(
+                    "{} ERROR [test_hostname::test] This is an error message",
+                    host
+                ),
+                format!(
+                    "{} INFO [test_hostname::test] This is an info message",
+                    host
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,103 @@
+#[cfg(feature = "http_writer")]
+mod test {
+    use flexi_logger::writers::HttpWriter;
+    use flexi_logger::Logger;
+    use log::*;
+
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::mpsc::{channel, Sender};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_http_writer() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/logs", listener.local_addr().unwrap());
+        let (sender, requests) = channel();
+        thread::spawn(move || serve(listener, sender));
+
+        let handle = Logger::with_str("info")
+            .do_not_log()
+            .add_writer(
+                "Http",
+                Box::new(
+                    HttpWriter::builder(url)
+                        .auth_header("Bearer 8a3b2c")
+                        .max_batch_size(2)
+                        .max_batch_delay(Duration::from_secs(60))
+                        .retry_interval(Duration::from_millis(10))
+                        .try_build()
+                        .unwrap(),
+                ),
+            )
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        // the first request is answered with an error, so that the batch is retried
+        info!(target: "{Http}", "first message");
+        warn!(target: "{Http}", "second message");
+        let (auth, body) = requests.recv().unwrap();
+        assert_eq!(auth, "Bearer 8a3b2c");
+        let (_, retried_body) = requests.recv().unwrap();
+        assert_eq!(body, retried_body);
+        assert!(body.starts_with("[{\"ts\":"), "{}", body);
+        assert!(body.contains("\"msg\":\"first message\""), "{}", body);
+        assert!(body.contains("},{"), "{}", body);
+        assert!(body.ends_with("\"msg\":\"second message\",\"fields\":{}}]"));
+
+        // a batch that is not full is sent with the shutdown
+        error!(target: "{Http}", "third message");
+        handle.shutdown();
+        let (_, body) = requests.recv().unwrap();
+        assert!(body.starts_with("[{"), "{}", body);
+        assert!(body.ends_with("\"msg\":\"third message\",\"fields\":{}}]"));
+    }
+
+    // Answers the first request with status 500, all further requests with status 200,
+    // and forwards the authorization header and the body of each request.
+    fn serve(listener: TcpListener, sender: Sender<(String, String)>) {
+        for (idx, stream) in listener.incoming().enumerate() {
+            let mut stream = stream.unwrap();
+            let request = read_request(&mut stream);
+            let status = if idx == 0 {
+                "500 Internal Server Error"
+            } else {
+                "200 OK"
+            };
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                status
+            )
+            .unwrap();
+            sender.send(request).unwrap();
+        }
+    }
+
+    fn read_request(stream: &mut TcpStream) -> (String, String) {
+        let mut reader = BufReader::new(stream);
+        let mut auth = String::new();
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            let (name, value) = match line.find(':') {
+                Some(idx) => (&line[..idx], line[idx + 1..].trim()),
+                None => continue,
+            };
+            if name.eq_ignore_ascii_case("authorization") {
+                auth = value.to_string();
+            } else if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.parse().unwrap();
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        (auth, String::from_utf8(body).unwrap())
+    }
+}
@@ -0,0 +1,69 @@
+#[cfg(feature = "kv")]
+mod test {
+    use flexi_logger::writers::FileLogWriter;
+    use flexi_logger::{default_format, ndjson_nested_format, LogTarget, Logger};
+    use log::*;
+
+    #[test]
+    fn test_human_and_json_files() {
+        // we use a timestamp in the directory name to allow repeated runs
+        let dir = chrono::Local::now()
+            .format("log_files/human_and_json_files/%Y-%m-%d_%H-%M-%S%.6f")
+            .to_string();
+
+        let json_writer = FileLogWriter::builder()
+            .format(ndjson_nested_format)
+            .directory(&dir)
+            .discriminant("machine")
+            .try_build()
+            .unwrap();
+
+        Logger::with_str("info")
+            .log_target(LogTarget::FileAndWriter(Box::new(json_writer)))
+            .directory(&dir)
+            .discriminant("readable")
+            .format_for_files(default_format)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        error!("This is an error message");
+        info!(count = 3; "This is an info message");
+        debug!("This is a debug message - you must not see it!");
+        log::logger().flush();
+
+        let read = |discriminant: &str| {
+            let files: Vec<_> = std::fs::read_dir(&dir)
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .filter(|path| {
+                    path.file_name()
+                        .unwrap()
+                        .to_string_lossy()
+                        .contains(discriminant)
+                })
+                .collect();
+            assert_eq!(files.len(), 1);
+            std::fs::read_to_string(&files[0]).unwrap()
+        };
+
+        assert_eq!(
+            read("_readable_").lines().collect::<Vec<_>>(),
+            vec![
+                "ERROR [test_human_and_json_files::test] This is an error message",
+                "INFO [test_human_and_json_files::test] This is an info message {count=3}",
+            ]
+        );
+        let json = read("_machine_");
+        let json_lines: Vec<_> = json.lines().collect();
+        assert_eq!(json_lines.len(), 2);
+        assert!(json_lines[0].starts_with("{\"ts\":\""));
+        assert!(json_lines[0].ends_with(
+            "\",\"level\":\"ERROR\",\"module\":\"test_human_and_json_files::test\",\
+             \"msg\":\"This is an error message\",\"fields\":{}}"
+        ));
+        assert!(json_lines[1].ends_with(
+            "\",\"level\":\"INFO\",\"module\":\"test_human_and_json_files::test\",\
+             \"msg\":\"This is an info message\",\"fields\":{\"count\":3}}"
+        ));
+    }
+}
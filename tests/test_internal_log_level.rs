@@ -0,0 +1,63 @@
+#[cfg(feature = "specfile")]
+mod a {
+    use flexi_logger::{default_format, LevelFilter, Logger};
+    use log::*;
+    use std::io::Write;
+
+    const WAIT: u64 = 1600;
+
+    #[test]
+    fn test_internal_log_level() {
+        // we use a timestamp in the directory name to allow repeated runs
+        let dir = chrono::Local::now()
+            .format("log_files/internal_log_level/%Y-%m-%d_%H-%M-%S%.6f")
+            .to_string();
+        let specfile = format!("{}/logspec.toml", dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        write_specfile(&specfile, "global_level = 'info'\n[modules]\n");
+
+        Logger::with_str("info")
+            .format(default_format)
+            .log_to_file()
+            .directory(format!("{}/logs", dir))
+            .internal_log_level(LevelFilter::Debug)
+            .start_with_specfile(&specfile)
+            .unwrap_or_else(|e| panic!("Logger initialization failed because: {}", e));
+        info!("This is an info message");
+
+        // the diagnostic messages are written although the spec does not enable them
+        write_specfile(&specfile, "global_level = 'error'\n[modules]\n");
+        std::thread::sleep(std::time::Duration::from_millis(WAIT));
+        write_specfile(&specfile, "global_level = 'no level'\n[modules]\n");
+        std::thread::sleep(std::time::Duration::from_millis(WAIT));
+        info!("This is another info message, you must not see it");
+
+        log::logger().flush();
+
+        let files: Vec<_> = std::fs::read_dir(format!("{}/logs", dir))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(files.len(), 1);
+        let content = std::fs::read_to_string(&files[0]).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 4, "{}", content);
+        assert_eq!(
+            lines[0],
+            "INFO [test_internal_log_level::a] This is an info message"
+        );
+        for line in &lines[1..3] {
+            assert!(line.starts_with(
+                "DEBUG [flexi_logger::spec_source] rereading the log specification file"
+            ));
+        }
+        assert!(lines[3].starts_with(
+            "ERROR [flexi_logger::spec_source] rereading the log specification file failed with"
+        ));
+    }
+
+    fn write_specfile(specfile: &str, content: &str) {
+        let mut file = std::fs::File::create(specfile).unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+    }
+}
@@ -0,0 +1,60 @@
+#[cfg(feature = "kv")]
+mod test {
+    use flexi_logger::writers::LogWriter;
+    use flexi_logger::{
+        json_format_ecs, json_format_with, DeferredNow, JsonField, JsonFieldNames, Logger, Record,
+    };
+    use log::*;
+
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    const REORDERED: JsonFieldNames =
+        JsonFieldNames(&[(JsonField::Message, "text"), (JsonField::Level, "severity")]);
+
+    #[test]
+    fn test_json_format_ecs() {
+        let lines = Arc::new(Mutex::new(Vec::<String>::new()));
+        Logger::with_str("info")
+            .add_writer("Json", Box::new(JsonWriter(Arc::clone(&lines))))
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        warn!(target: "{Json}", user = "karl", count = 42_i64; "hello");
+        error!(target: "{Json}", "say \"{}\"\nto {}", "hello", "karl");
+
+        let lines = lines.lock().unwrap();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("{\"@timestamp\":\""));
+        assert!(lines[0].ends_with(
+            "\",\"log.level\":\"warn\",\"log.logger\":\"test_json_format_ecs::test\",\
+             \"message\":\"hello\",\"labels\":{\"user\":\"karl\",\"count\":42}}"
+        ));
+        assert_eq!(lines[1], "{\"text\":\"hello\",\"severity\":\"WARN\"}");
+        assert!(lines[2].contains("\"log.level\":\"error\","));
+        assert_eq!(
+            lines[3],
+            "{\"text\":\"say \\\"hello\\\"\\nto karl\",\"severity\":\"ERROR\"}"
+        );
+    }
+
+    // writes each record with ECS field names, and with REORDERED
+    struct JsonWriter(Arc<Mutex<Vec<String>>>);
+    impl LogWriter for JsonWriter {
+        fn write(&self, now: &mut DeferredNow, record: &Record) -> io::Result<()> {
+            let mut buf = Vec::<u8>::new();
+            json_format_ecs(&mut buf, now, record)?;
+            self.0.lock().unwrap().push(String::from_utf8(buf).unwrap());
+            let mut buf = Vec::<u8>::new();
+            json_format_with(&mut buf, now, record, &REORDERED)?;
+            self.0.lock().unwrap().push(String::from_utf8(buf).unwrap());
+            Ok(())
+        }
+        fn flush(&self) -> io::Result<()> {
+            Ok(())
+        }
+        fn max_log_level(&self) -> log::LevelFilter {
+            log::LevelFilter::Info
+        }
+    }
+}
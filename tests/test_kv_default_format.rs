@@ -0,0 +1,54 @@
+#[cfg(feature = "kv")]
+mod test {
+    use flexi_logger::writers::LogWriter;
+    use flexi_logger::{default_format, DeferredNow, Logger, Record};
+    use log::*;
+
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_kv_default_format() {
+        let lines = Arc::new(Mutex::new(Vec::<String>::new()));
+        Logger::with_str("info")
+            .add_writer("Plain", Box::new(PlainWriter(Arc::clone(&lines))))
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        warn!(
+            target: "{Plain}",
+            file = "conf.json",
+            tasks = 3,
+            ok = true;
+            "Task successfully read"
+        );
+        info!(target: "{Plain}", "no fields");
+
+        let lines = lines.lock().unwrap();
+        assert_eq!(
+            *lines,
+            vec![
+                "WARN [test_kv_default_format::test] Task successfully read \
+                 {file=conf.json, tasks=3, ok=true}"
+                    .to_string(),
+                "INFO [test_kv_default_format::test] no fields".to_string(),
+            ]
+        );
+    }
+
+    struct PlainWriter(Arc<Mutex<Vec<String>>>);
+    impl LogWriter for PlainWriter {
+        fn write(&self, now: &mut DeferredNow, record: &Record) -> io::Result<()> {
+            let mut buf = Vec::<u8>::new();
+            default_format(&mut buf, now, record)?;
+            self.0.lock().unwrap().push(String::from_utf8(buf).unwrap());
+            Ok(())
+        }
+        fn flush(&self) -> io::Result<()> {
+            Ok(())
+        }
+        fn max_log_level(&self) -> log::LevelFilter {
+            log::LevelFilter::Info
+        }
+    }
+}
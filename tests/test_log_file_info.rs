@@ -0,0 +1,23 @@
+use flexi_logger::Logger;
+use log::*;
+
+#[test]
+fn test_log_file_info() {
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory("log_files/log_file_info")
+        .discriminant("dscr")
+        .suffix("trc")
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+    info!("This is an info message");
+
+    let info = handle.log_file_info().unwrap();
+    assert!(info.directory.ends_with("log_files/log_file_info"));
+    assert!(info.basename.starts_with("test_log_file_info"));
+    assert!(info.basename.contains("_dscr_"));
+    assert_eq!(info.suffix, "trc");
+    assert!(!info.rotate);
+    assert!(info.current_file.is_file());
+    assert_eq!(info.current_file.extension().unwrap(), "trc");
+}
@@ -0,0 +1,63 @@
+#[cfg(feature = "kv")]
+mod test {
+    use flexi_logger::writers::LogWriter;
+    use flexi_logger::{default_format, DeferredNow, Logger, Record};
+    use log::*;
+
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_log_on_change() {
+        let lines = Arc::new(Mutex::new(Vec::<String>::new()));
+        Logger::with_str("info")
+            .log_on_change("tracked", 2)
+            .do_not_log()
+            .add_writer("Plain", Box::new(PlainWriter(Arc::clone(&lines))))
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        for state in &["up", "up", "down", "down", "up"] {
+            info!(target: "{Plain}", tracked = "db"; "db is {}", state);
+            info!(target: "{Plain}", tracked = "cache"; "cache is up");
+            info!(target: "{Plain}", "untracked");
+        }
+        // the map is full, so further values are not tracked
+        info!(target: "{Plain}", tracked = "queue"; "queue is up");
+        info!(target: "{Plain}", tracked = "queue"; "queue is up");
+
+        let lines = lines.lock().unwrap();
+        assert_eq!(
+            *lines,
+            vec![
+                "INFO [test_log_on_change::test] db is up {tracked=db}",
+                "INFO [test_log_on_change::test] cache is up {tracked=cache}",
+                "INFO [test_log_on_change::test] untracked",
+                "INFO [test_log_on_change::test] untracked",
+                "INFO [test_log_on_change::test] db is down {tracked=db}",
+                "INFO [test_log_on_change::test] untracked",
+                "INFO [test_log_on_change::test] untracked",
+                "INFO [test_log_on_change::test] db is up {tracked=db}",
+                "INFO [test_log_on_change::test] untracked",
+                "INFO [test_log_on_change::test] queue is up {tracked=queue}",
+                "INFO [test_log_on_change::test] queue is up {tracked=queue}",
+            ]
+        );
+    }
+
+    struct PlainWriter(Arc<Mutex<Vec<String>>>);
+    impl LogWriter for PlainWriter {
+        fn write(&self, now: &mut DeferredNow, record: &Record) -> io::Result<()> {
+            let mut buf = Vec::<u8>::new();
+            default_format(&mut buf, now, record)?;
+            self.0.lock().unwrap().push(String::from_utf8(buf).unwrap());
+            Ok(())
+        }
+        fn flush(&self) -> io::Result<()> {
+            Ok(())
+        }
+        fn max_log_level(&self) -> log::LevelFilter {
+            log::LevelFilter::Info
+        }
+    }
+}
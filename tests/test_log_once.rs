@@ -0,0 +1,30 @@
+use flexi_logger::Logger;
+use log::*;
+
+#[test]
+fn test_log_once() {
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory("log_files")
+        .log_once(3)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    for _ in 0..3 {
+        warn!("The configuration file is missing");
+        info!("Using defaults");
+    }
+    warn!(target: "other", "The configuration file is missing");
+    // the limit is reached, so these are not remembered
+    error!("Overflow");
+    error!("Overflow");
+    info!("Using defaults");
+
+    handle.validate_logs(&[
+        ("WARN", "test_log_once", "configuration file is missing"),
+        ("INFO", "test_log_once", "Using defaults"),
+        ("WARN", "test_log_once", "configuration file is missing"),
+        ("ERROR", "test_log_once", "Overflow"),
+        ("ERROR", "test_log_once", "Overflow"),
+    ]);
+}
@@ -0,0 +1,39 @@
+use flexi_logger::Logger;
+use log::*;
+
+#[test]
+fn test_log_panics() {
+    // we want to see the panic message in a single line
+    std::env::set_var("RUST_BACKTRACE", "0");
+
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory("log_files")
+        .log_panics()
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("before the panic");
+    let result = std::thread::Builder::new()
+        .name("doomed".to_string())
+        .spawn(|| {
+            let v: Vec<u8> = Vec::new();
+            if v.is_empty() {
+                panic!("the vector is empty: {}", v.len());
+            }
+        })
+        .unwrap()
+        .join();
+    assert!(result.is_err());
+    info!("after the panic");
+
+    handle.validate_logs(&[
+        ("INFO", "test_log_panics", "before the panic"),
+        (
+            "ERROR",
+            "flexi_logger",
+            "thread 'doomed' panicked at 'the vector is empty: 0', tests/test_log_panics.rs:",
+        ),
+        ("INFO", "test_log_panics", "after the panic"),
+    ]);
+}
@@ -0,0 +1,40 @@
+use flexi_logger::{detailed_format, Level, Logger, OwnedRecord};
+use log::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+#[test]
+fn test_map_record() {
+    let handle = Logger::with_str("warn")
+        .format(detailed_format)
+        .log_to_file()
+        .map_record(Box::new(|record| {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            let mut owned_record = OwnedRecord::from(record);
+            if owned_record.message.contains("drop") {
+                return None;
+            }
+            if owned_record.message.contains("spurious") {
+                owned_record.level = Level::Warn;
+            } else if owned_record.message.contains("unimportant") {
+                owned_record.level = Level::Info;
+            }
+            owned_record.message = owned_record.message.replace("secret", "public");
+            Some(owned_record)
+        }))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("This is a spurious error, written as warning");
+    warn!("This is an unimportant warning, written as info and thus filtered out");
+    warn!("This is a warning to drop");
+    error!("This is an error with a secret");
+    info!("This is an info message, which does not reach the function");
+
+    assert_eq!(CALLS.load(Ordering::SeqCst), 4);
+    handle.validate_logs(&[
+        ("WARN", "test_map_record", "spurious error"),
+        ("ERROR", "test_map_record", "an error with a public"),
+    ]);
+}
@@ -0,0 +1,41 @@
+use flexi_logger::writers::LogWriter;
+use flexi_logger::{DeferredNow, Logger, OwnedRecord, Record};
+use log::*;
+
+use std::io;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_map_record_writer_level() {
+    let messages = Arc::new(Mutex::new(Vec::<String>::new()));
+    Logger::with_str("error")
+        .do_not_log()
+        .add_writer("Audit", Box::new(WarnWriter(Arc::clone(&messages))))
+        .map_record(Box::new(|record| Some(OwnedRecord::from(record))))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    // records at exactly the level of the writer pass the mapping
+    error!(target: "{Audit}", "This is an error message");
+    warn!(target: "{Audit}", "This is a warning");
+    info!(target: "{Audit}", "This is an info message - you must not see it!");
+
+    assert_eq!(
+        *messages.lock().unwrap(),
+        vec!["This is an error message", "This is a warning"]
+    );
+}
+
+struct WarnWriter(Arc<Mutex<Vec<String>>>);
+impl LogWriter for WarnWriter {
+    fn write(&self, _now: &mut DeferredNow, record: &Record) -> io::Result<()> {
+        self.0.lock().unwrap().push(record.args().to_string());
+        Ok(())
+    }
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+    fn max_log_level(&self) -> log::LevelFilter {
+        log::LevelFilter::Warn
+    }
+}
@@ -0,0 +1,25 @@
+use flexi_logger::{default_format, Logger};
+use log::*;
+
+#[test]
+fn test_max_line_length() {
+    // "INFO [test_max_line_length] " has 28 bytes
+    let handle = Logger::with_str("info")
+        .format(default_format)
+        .log_to_file()
+        .directory("log_files")
+        .max_line_length(33)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("ok");
+    warn!("{}", "x".repeat(10_000));
+    // the cut would be in the middle of the third 'Ä'
+    info!("ÄÄÄÄÄÄ");
+
+    handle.validate_logs(&[
+        ("ERROR", "test_max_line_length", "] ok"),
+        ("WARN", "test_max_line_length", "] xxxxx…"),
+        ("INFO", "test_max_line_length", "] ÄÄ…"),
+    ]);
+}
@@ -0,0 +1,34 @@
+use flexi_logger::{default_format, Logger};
+use log::*;
+
+#[test]
+fn test_max_records() {
+    let handle = Logger::with_str("info")
+        .format(default_format)
+        .log_to_file()
+        .directory("log_files")
+        .max_records(3)
+        .exempt_from_max_records(Level::Error)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("This is error message 1");
+    for i in 1..=5 {
+        info!("This is info message {}", i);
+    }
+    warn!("This is a warning");
+    error!("This is error message 2");
+
+    handle.validate_logs(&[
+        ("ERROR", "test_max_records", "error message 1"),
+        ("INFO", "test_max_records", "info message 1"),
+        ("INFO", "test_max_records", "info message 2"),
+        ("INFO", "test_max_records", "info message 3"),
+        (
+            "WARN",
+            "[flexi_logger::",
+            "the maximum of 3 records was reached, further records are dropped",
+        ),
+        ("ERROR", "test_max_records", "error message 2"),
+    ]);
+}
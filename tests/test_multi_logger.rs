@@ -28,6 +28,8 @@ fn test() {
         .start()
         .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
 
+    assert_eq!(log_handle.writer_names(), vec!["Alert", "Sec"]);
+
     // Explicitly send logs to different loggers
     error!(target : "{Sec}", "This is a security-relevant error message");
     error!(target : "{Sec,Alert}", "This is a security-relevant alert message");
@@ -0,0 +1,60 @@
+#[cfg(feature = "kv")]
+mod test {
+    use flexi_logger::writers::LogWriter;
+    use flexi_logger::{ndjson_nested_format, DeferredNow, Logger, Record};
+    use log::*;
+
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_ndjson_nested_format() {
+        let lines = Arc::new(Mutex::new(Vec::<String>::new()));
+        Logger::with_str("info")
+            .add_writer("Json", Box::new(JsonWriter(Arc::clone(&lines))))
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        warn!(
+            target: "{Json}",
+            user = "karl \"the\" heinz",
+            count = 42_i64,
+            size = 7_u64,
+            ratio = 0.5_f64,
+            ok = true,
+            level = 'x';
+            "line one\nline \\two\\"
+        );
+        info!(target: "{Json}", "hello");
+
+        let lines = lines.lock().unwrap();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("{\"ts\":\""));
+        assert!(lines[0].ends_with(
+            "\",\"level\":\"WARN\",\"module\":\"test_ndjson_format::test\",\
+             \"msg\":\"line one\\nline \\\\two\\\\\",\
+             \"fields\":{\"user\":\"karl \\\"the\\\" heinz\",\"count\":42,\"size\":7,\
+             \"ratio\":0.5,\"ok\":true,\"level\":\"x\"}}"
+        ));
+        assert!(lines[1].ends_with(
+            "\",\"level\":\"INFO\",\"module\":\"test_ndjson_format::test\",\
+             \"msg\":\"hello\",\"fields\":{}}"
+        ));
+    }
+
+    struct JsonWriter(Arc<Mutex<Vec<String>>>);
+    impl LogWriter for JsonWriter {
+        fn write(&self, now: &mut DeferredNow, record: &Record) -> io::Result<()> {
+            let mut buf = Vec::<u8>::new();
+            ndjson_nested_format(&mut buf, now, record)?;
+            self.0.lock().unwrap().push(String::from_utf8(buf).unwrap());
+            Ok(())
+        }
+        fn flush(&self) -> io::Result<()> {
+            Ok(())
+        }
+        fn max_log_level(&self) -> log::LevelFilter {
+            log::LevelFilter::Info
+        }
+    }
+}
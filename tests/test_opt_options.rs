@@ -0,0 +1,28 @@
+use flexi_logger::{opt_format, LogTarget, Logger};
+use log::*;
+
+#[test]
+fn test_opt_options() {
+    let handle = Logger::with_str("info")
+        .log_target(LogTarget::File)
+        .o_format(Some(opt_format))
+        .o_directory(Some("log_files"))
+        .o_discriminant(Some("opt_options"))
+        .o_suffix(Some("txt"))
+        .o_use_windows_line_ending(true)
+        .o_add_writer("foo", None)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("This is an error message");
+    warn!("This is a warning");
+    info!("This is an info message");
+    debug!("This is a debug message - you must not see it!");
+
+    assert!(handle.writer_names().is_empty());
+    handle.validate_logs(&[
+        ("] ERROR [", "test_opt_options.rs", "error"),
+        ("] WARN [", "test_opt_options.rs", "warning"),
+        ("] INFO [", "test_opt_options.rs", "info"),
+    ]);
+}
@@ -0,0 +1,38 @@
+use flexi_logger::{default_format, Logger};
+use log::*;
+
+#[test]
+fn test_pad_levels() {
+    // we use a timestamp in the directory name to allow repeated runs
+    let dir = chrono::Local::now()
+        .format("log_files/pad_levels/%Y-%m-%d_%H-%M-%S%.6f")
+        .to_string();
+
+    Logger::with_str("trace")
+        .format(default_format)
+        .pad_levels(true)
+        .log_to_file()
+        .directory(&dir)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("This is an error message");
+    warn!("This is a warning");
+    info!("This is an info message");
+    debug!("This is a debug message");
+    trace!("This is a trace message");
+    log::logger().flush();
+
+    let files: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(files.len(), 1);
+    let content = std::fs::read_to_string(&files[0]).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 5, "{}", content);
+    assert_eq!(lines[2], "INFO  [test_pad_levels] This is an info message");
+    for line in lines {
+        assert_eq!(line.find('['), Some(6), "{}", content);
+    }
+}
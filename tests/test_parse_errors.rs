@@ -1,4 +1,4 @@
-use flexi_logger::{FlexiLoggerError, LogSpecification, Logger};
+use flexi_logger::{FlexiLoggerError, LogSpecification, Logger, SpecParseError};
 use log::*;
 
 #[test]
@@ -44,19 +44,39 @@ fn parse_errors_logspec() {
         _ => panic!("Wrong error from parsing (3)"),
     }
 
-    match LogSpecification::parse("INFO, ene / mene / dubbedene")
+    // the text filter is taken verbatim, including its slashes and spaces
+    let logspec = LogSpecification::parse("INFO, ene / mene / dubbedene").unwrap();
+    assert_eq!(
+        logspec.text_filter().as_ref().unwrap().as_str(),
+        " mene / dubbedene"
+    );
+}
+
+#[test]
+fn parse_errors_typed() {
+    match LogSpecification::parse("info, foo=bar, ene mene, a=b=c/(")
         .err()
         .unwrap()
     {
-        FlexiLoggerError::Parse(_, logspec) => {
+        FlexiLoggerError::Parse(errors, _) => {
+            assert_eq!(errors.len(), 4);
+            assert_eq!(errors[0], SpecParseError::UnknownLevel("bar".to_string()));
             assert_eq!(
-                logspec.module_filters(),
-                LogSpecification::off().module_filters()
+                errors[1],
+                SpecParseError::InvalidModuleName("ene mene".to_string())
             );
-            assert!(logspec.text_filter().is_none());
+            assert_eq!(errors[2], SpecParseError::InvalidPart("a=b=c".to_string()));
+            match errors[3] {
+                SpecParseError::BadRegex(_) => {}
+                _ => panic!("Wrong error from parsing (5)"),
+            }
         }
-        _ => panic!("Wrong error from parsing (4)"),
+        _ => panic!("Wrong error from parsing (6)"),
     }
+
+    // everything after the first '/' is the text filter
+    let spec = LogSpecification::parse("info/a/b").unwrap();
+    assert_eq!(spec.text_filter().as_ref().unwrap().as_str(), "a/b");
 }
 
 #[test]
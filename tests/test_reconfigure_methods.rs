@@ -10,6 +10,7 @@ fn test_reconfigure_methods() {
 
     test_parse_new_spec(&mut log_handle);
     test_push_new_spec(&mut log_handle);
+    test_pause_resume(&mut log_handle);
     validate_logs(&mut log_handle);
 }
 
@@ -21,6 +22,7 @@ fn test_parse_new_spec(log_handle: &mut ReconfigurationHandle) {
     trace!("1-trace message - you must not see it!");
 
     log_handle.parse_new_spec("error");
+    assert_eq!(log_handle.max_level(), LevelFilter::Error);
     error!("1-error message");
     warn!("1-warning - you must not see it!");
     info!("1-info message - you must not see it!");
@@ -28,6 +30,7 @@ fn test_parse_new_spec(log_handle: &mut ReconfigurationHandle) {
     trace!("1-trace message - you must not see it!");
 
     log_handle.parse_new_spec("trace");
+    assert_eq!(log_handle.max_level(), LevelFilter::Trace);
     error!("1-error message");
     warn!("1-warning");
     info!("1-info message");
@@ -77,6 +80,16 @@ fn test_push_new_spec(log_handle: &mut ReconfigurationHandle) {
     log_handle.pop_temp_spec(); // should be a no-op
 }
 
+fn test_pause_resume(log_handle: &mut ReconfigurationHandle) {
+    log_handle.pause();
+    error!("3-error message - you must not see it!");
+    info!("3-info message - you must not see it!");
+
+    log_handle.resume();
+    error!("3-error message");
+    info!("3-info message");
+}
+
 #[allow(clippy::cognitive_complexity)]
 fn validate_logs(log_handle: &mut ReconfigurationHandle) {
     log_handle.validate_logs(&[
@@ -109,5 +122,8 @@ fn validate_logs(log_handle: &mut ReconfigurationHandle) {
         ("ERROR", "test_reconfigure_methods", "2-error"),
         ("WARN", "test_reconfigure_methods", "2-warning"),
         ("INFO", "test_reconfigure_methods", "2-info"),
+        // -----
+        ("ERROR", "test_reconfigure_methods", "3-error"),
+        ("INFO", "test_reconfigure_methods", "3-info"),
     ]);
 }
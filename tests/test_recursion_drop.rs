@@ -0,0 +1,33 @@
+use flexi_logger::{detailed_format, Logger, RecursionPolicy};
+use log::*;
+
+#[test]
+fn test_recursion_drop() {
+    let handle = Logger::with_str("info")
+        .format(detailed_format)
+        .log_to_file()
+        .directory("log_files/recursion_drop")
+        .recursion_policy(RecursionPolicy::Drop)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed because: {}", e));
+
+    let dummy = Dummy();
+    error!("This is an error message for {}", dummy);
+    warn!("This is a warning for {}", dummy);
+
+    // the inner messages are dropped and counted
+    assert_eq!(handle.dropped_count(), 2);
+    handle.validate_logs(&[
+        ("ERROR", "test_recursion_drop", "error message for Dummy!!"),
+        ("WARN", "test_recursion_drop", "warning for Dummy!!"),
+    ]);
+}
+
+struct Dummy();
+impl std::fmt::Display for Dummy {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        info!("Here comes the inner message - you must not see it!");
+        f.write_str("Dummy!!")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+use flexi_logger::{detailed_format, Logger, RecursionPolicy};
+use log::*;
+
+#[test]
+fn test_recursion_panic() {
+    let handle = Logger::with_str("info")
+        .format(detailed_format)
+        .log_to_file()
+        .directory("log_files/recursion_panic")
+        .recursion_policy(RecursionPolicy::PanicInDebug)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed because: {}", e));
+
+    info!("This is an info message without recursion");
+    let result = std::panic::catch_unwind(|| {
+        warn!("This is a warning for {}", Dummy());
+    });
+    if cfg!(debug_assertions) {
+        assert!(result.is_err());
+        handle.validate_logs(&[("INFO", "test_recursion_panic", "without recursion")]);
+    } else {
+        assert!(result.is_ok());
+    }
+}
+
+struct Dummy();
+impl std::fmt::Display for Dummy {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        info!("Here comes the inner message");
+        f.write_str("Dummy!!")?;
+        Ok(())
+    }
+}
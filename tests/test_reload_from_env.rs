@@ -0,0 +1,37 @@
+use flexi_logger::Logger;
+use log::*;
+
+#[test]
+fn test_reload_from_env() {
+    std::env::set_var("RUST_LOG", "info");
+    let mut log_handle = Logger::with_env()
+        .log_to_file()
+        .directory("log_files")
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("1-info message");
+    debug!("1-debug message - you must not see it!");
+
+    std::env::set_var("RUST_LOG", "debug");
+    log_handle.reload_from_env().unwrap();
+    assert_eq!(log_handle.max_level(), LevelFilter::Debug);
+    debug!("2-debug message");
+
+    // an invalid spec keeps the active one
+    std::env::set_var("RUST_LOG", "info, foo=bar");
+    assert!(log_handle.reload_from_env().is_err());
+    debug!("3-debug message");
+
+    std::env::set_var("RUST_LOG", "warn");
+    log_handle.reload_from_env().unwrap();
+    info!("4-info message - you must not see it!");
+    warn!("4-warning");
+
+    log_handle.validate_logs(&[
+        ("INFO", "test_reload_from_env", "1-info"),
+        ("DEBUG", "test_reload_from_env", "2-debug"),
+        ("DEBUG", "test_reload_from_env", "3-debug"),
+        ("WARN", "test_reload_from_env", "4-warning"),
+    ]);
+}
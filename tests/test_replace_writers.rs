@@ -0,0 +1,78 @@
+use flexi_logger::writers::{FileLogWriter, LogWriter};
+use flexi_logger::Logger;
+use log::*;
+
+use std::collections::HashMap;
+
+#[test]
+fn test_replace_writers() {
+    // we use a timestamp in the directory name to allow repeated runs
+    let directory = chrono::Local::now()
+        .format("log_files/replace_writers/%Y-%m-%d_%H-%M-%S%.6f")
+        .to_string();
+    let mut handle = Logger::with_str("info")
+        .log_to_file()
+        .directory(&directory)
+        .add_writer("Out", file_writer(&directory, "first"))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!(target: "{Out}", "This goes to the first writer");
+    warn!("This goes to the primary writer");
+
+    let mut old_writers = handle.replace_writers(writers("Out", &directory, "second"));
+    assert_eq!(handle.writer_names(), vec!["Out"]);
+    error!(target: "{Out}", "This goes to the second writer");
+    error!(target: "{Out,_Default}", "This goes to the second and the primary writer");
+
+    old_writers.remove("Out").unwrap().validate_logs(&[(
+        "ERROR",
+        "test_replace_writers",
+        "first writer",
+    )]);
+
+    // switching to another writer name
+    let mut old_writers = handle.replace_writers(writers("Net", &directory, "third"));
+    assert_eq!(handle.writer_names(), vec!["Net"]);
+    error!(target: "{Net}", "This goes to the third writer");
+
+    old_writers.remove("Out").unwrap().validate_logs(&[
+        ("ERROR", "test_replace_writers", "second writer"),
+        (
+            "ERROR",
+            "test_replace_writers",
+            "second and the primary writer",
+        ),
+    ]);
+    handle
+        .replace_writers(HashMap::new())
+        .remove("Net")
+        .unwrap()
+        .validate_logs(&[("ERROR", "test_replace_writers", "third writer")]);
+    assert!(handle.writer_names().is_empty());
+
+    handle.validate_logs(&[
+        ("WARN", "test_replace_writers", "primary writer"),
+        (
+            "ERROR",
+            "test_replace_writers",
+            "second and the primary writer",
+        ),
+    ]);
+}
+
+fn file_writer(directory: &str, discriminant: &str) -> Box<dyn LogWriter> {
+    Box::new(
+        FileLogWriter::builder()
+            .directory(directory)
+            .discriminant(discriminant)
+            .try_build()
+            .unwrap(),
+    )
+}
+
+fn writers(name: &str, directory: &str, discriminant: &str) -> HashMap<String, Box<dyn LogWriter>> {
+    let mut writers = HashMap::new();
+    writers.insert(name.to_string(), file_writer(directory, discriminant));
+    writers
+}
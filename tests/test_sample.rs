@@ -0,0 +1,40 @@
+use flexi_logger::Logger;
+use log::*;
+
+#[test]
+fn test_sample() {
+    let handle = Logger::with_str("trace")
+        .log_to_file()
+        .directory("log_files")
+        .count_levels()
+        .sample(Level::Debug, 100)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    for i in 0..300 {
+        if i % 100 == 0 {
+            error!("This is error {}", i);
+            info!("This is info {}", i);
+        }
+        debug!("This is debug {}", i);
+    }
+    // debug and trace records share the sampling counter
+    for i in 0..100 {
+        trace!("This is trace {}", i);
+    }
+
+    assert_eq!(handle.level_counts(), [3, 0, 3, 3, 1]);
+
+    handle.validate_logs(&[
+        ("ERROR", "test_sample", "error 0"),
+        ("INFO", "test_sample", "info 0"),
+        ("DEBUG", "test_sample", "debug 0"),
+        ("ERROR", "test_sample", "error 100"),
+        ("INFO", "test_sample", "info 100"),
+        ("DEBUG", "test_sample", "debug 100"),
+        ("ERROR", "test_sample", "error 200"),
+        ("INFO", "test_sample", "info 200"),
+        ("DEBUG", "test_sample", "debug 200"),
+        ("TRACE", "test_sample", "trace 0"),
+    ]);
+}
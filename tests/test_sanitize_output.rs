@@ -0,0 +1,40 @@
+use flexi_logger::{default_format, Logger};
+use log::*;
+
+#[test]
+fn test_sanitize_output() {
+    // we use a timestamp in the directory name to allow repeated runs
+    let dir = chrono::Local::now()
+        .format("log_files/sanitize_output/%Y-%m-%d_%H-%M-%S%.6f")
+        .to_string();
+
+    Logger::with_str("info")
+        .format(default_format)
+        .sanitize_output()
+        .log_to_file()
+        .directory(&dir)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    let user_input = "alice\nERROR [admin] forged line";
+    info!("login of {}", user_input);
+    warn!("terminal \x1b[31mred\x1b[0m and\r\ttab");
+    info!("harmless message with ümlauts");
+    log::logger().flush();
+
+    let files: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(files.len(), 1);
+    let content = std::fs::read_to_string(&files[0]).unwrap();
+    assert_eq!(
+        content.lines().collect::<Vec<_>>(),
+        vec![
+            r"INFO [test_sanitize_output] login of alice\nERROR [admin] forged line",
+            r"WARN [test_sanitize_output] terminal \u{1b}[31mred\u{1b}[0m and\r\ttab",
+            "INFO [test_sanitize_output] harmless message with ümlauts",
+        ]
+    );
+    assert!(!content.contains('\x1b'));
+}
@@ -0,0 +1,75 @@
+#[cfg(feature = "specfile")]
+mod a {
+    use flexi_logger::{LogSpecBuilder, LogSpecification, Logger};
+    use log::*;
+
+    #[test]
+    fn test_save_spec() {
+        // we use a timestamp in the file name to allow repeated runs
+        let specfile = chrono::Local::now()
+            .format("log_files/save_spec/%Y-%m-%d_%H-%M-%S%.6f.toml")
+            .to_string();
+        std::fs::create_dir_all("log_files/save_spec").unwrap();
+
+        let mut handle = Logger::with_str("info")
+            .log_to_file()
+            .directory("log_files")
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        handle.parse_new_spec("warn, karl=debug, hyper::client=trace");
+        handle.save_spec_to(&specfile).unwrap();
+        assert!(handle.save_spec_to("log_files/save_spec/spec.txt").is_err());
+
+        let saved =
+            LogSpecification::from_toml(&std::fs::read_to_string(&specfile).unwrap()).unwrap();
+        assert_eq!(
+            saved.module_filters(),
+            LogSpecification::parse("warn, karl=debug, hyper::client=trace")
+                .unwrap()
+                .module_filters()
+        );
+
+        // an existing output section is kept, the text filter is saved, too
+        std::fs::write(
+            &specfile,
+            "global_level = 'info'\n\
+             [modules]\n\
+             [output]\n\
+             target = 'file'\n\
+             directory = 'log_files/save_spec'\n",
+        )
+        .unwrap();
+        handle.set_new_spec(LogSpecification::parse("warn, karl=debug/it's a \"match\"").unwrap());
+        handle.save_spec_to(&specfile).unwrap();
+        let content = std::fs::read_to_string(&specfile).unwrap();
+        assert!(content.contains(
+            "[output]\n\
+             target = \"file\"\n\
+             directory = \"log_files/save_spec\"\n"
+        ));
+        let saved = LogSpecification::from_toml(&content).unwrap();
+        assert_eq!(
+            saved.text_filter().as_ref().unwrap().as_str(),
+            "it's a \"match\""
+        );
+
+        // the order of first_match_wins is lost
+        handle.set_new_spec(
+            LogSpecBuilder::new()
+                .first_match_wins()
+                .module("karl", LevelFilter::Debug)
+                .module("karl::heinz", LevelFilter::Error)
+                .default(LevelFilter::Warn)
+                .build(),
+        );
+        handle.save_spec_to(&specfile).unwrap();
+        let saved =
+            LogSpecification::from_toml(&std::fs::read_to_string(&specfile).unwrap()).unwrap();
+        assert!(!saved.enabled(Level::Debug, "karl::heinz"));
+        handle.parse_new_spec("warn");
+
+        warn!("saved to {}", specfile);
+        handle.validate_logs(&[("WARN", "test_save_spec", "saved to")]);
+    }
+}
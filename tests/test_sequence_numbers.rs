@@ -0,0 +1,40 @@
+use flexi_logger::{with_sequence_number, Logger};
+use log::*;
+
+#[test]
+fn test_sequence_numbers() {
+    // we use a timestamp in the directory name to allow repeated runs
+    let dir = chrono::Local::now()
+        .format("log_files/sequence_numbers/%Y-%m-%d_%H-%M-%S%.6f")
+        .to_string();
+
+    Logger::with_str("info")
+        .format(with_sequence_number)
+        .sequence_numbers()
+        .log_to_file()
+        .directory(&dir)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("This is an error message");
+    debug!("This is a debug message that is not written");
+    warn!("This is a warning");
+    info!("This is an info message");
+    log::logger().flush();
+
+    let files: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(files.len(), 1);
+    let content = std::fs::read_to_string(&files[0]).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "#000001 ERROR [test_sequence_numbers] This is an error message",
+            "#000002 WARN [test_sequence_numbers] This is a warning",
+            "#000003 INFO [test_sequence_numbers] This is an info message",
+        ]
+    );
+}
@@ -0,0 +1,63 @@
+use flexi_logger::{Logger, PollingSpecSource};
+use log::*;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[test]
+fn test_spec_source() {
+    // simulates a configuration service
+    let remote_spec = Arc::new(Mutex::new(Some("info".to_string())));
+    let provider_spec = Arc::clone(&remote_spec);
+
+    let log_handle = Logger::with_str("error")
+        .log_to_file()
+        .directory("log_files/spec_source")
+        .start_with_spec_source(Box::new(PollingSpecSource::new(
+            Duration::from_millis(50),
+            move || provider_spec.lock().unwrap().clone(),
+        )))
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    assert_eq!(log_handle.max_level(), LevelFilter::Info);
+    info!("1-info message");
+    debug!("1-debug message - you must not see it!");
+
+    *remote_spec.lock().unwrap() = Some("debug".to_string());
+    wait_for(&log_handle, LevelFilter::Debug);
+    debug!("2-debug message");
+
+    // an unavailable or invalid spec keeps the active one
+    *remote_spec.lock().unwrap() = None;
+    std::thread::sleep(Duration::from_millis(200));
+    *remote_spec.lock().unwrap() = Some("info, foo=bar".to_string());
+    std::thread::sleep(Duration::from_millis(200));
+    assert_eq!(log_handle.max_level(), LevelFilter::Debug);
+    debug!("3-debug message");
+
+    *remote_spec.lock().unwrap() = Some("warn".to_string());
+    wait_for(&log_handle, LevelFilter::Warn);
+    info!("4-info message - you must not see it!");
+    warn!("4-warning");
+
+    log_handle.validate_logs(&[
+        ("INFO", "test_spec_source", "1-info"),
+        ("DEBUG", "test_spec_source", "2-debug"),
+        (
+            "ERROR",
+            "flexi_logger::spec_source",
+            "parsing the polled log specification failed",
+        ),
+        ("DEBUG", "test_spec_source", "3-debug"),
+        ("WARN", "test_spec_source", "4-warning"),
+    ]);
+}
+
+fn wait_for(log_handle: &flexi_logger::ReconfigurationHandle, level: LevelFilter) {
+    for _ in 0..100 {
+        if log_handle.max_level() == level {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    panic!("the log specification was not updated to {}", level);
+}
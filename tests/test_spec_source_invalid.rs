@@ -0,0 +1,39 @@
+use flexi_logger::{Logger, PollingSpecSource};
+use log::*;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[test]
+fn test_spec_source_invalid() {
+    // simulates a configuration service that initially provides an invalid spec
+    let remote_spec = Arc::new(Mutex::new(Some("info, foo=bar".to_string())));
+    let provider_spec = Arc::clone(&remote_spec);
+
+    let log_handle = Logger::with_str("warn")
+        .log_to_file()
+        .directory("log_files/spec_source_invalid")
+        .start_with_spec_source(Box::new(PollingSpecSource::new(
+            Duration::from_millis(50),
+            move || provider_spec.lock().unwrap().clone(),
+        )))
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    // the spec of the Logger is used
+    assert_eq!(log_handle.max_level(), LevelFilter::Warn);
+    info!("1-info message - you must not see it!");
+    warn!("1-warning");
+
+    *remote_spec.lock().unwrap() = Some("info".to_string());
+    for _ in 0..100 {
+        if log_handle.max_level() == LevelFilter::Info {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    info!("2-info message");
+
+    log_handle.validate_logs(&[
+        ("WARN", "test_spec_source_invalid", "1-warning"),
+        ("INFO", "test_spec_source_invalid", "2-info"),
+    ]);
+}
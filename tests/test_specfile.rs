@@ -87,7 +87,7 @@ mod a {
         debug!("This is a debug message");
         trace!("This is a trace message");
 
-        let logfile = std::path::Path::new(&std::env::args().nth(0).unwrap())
+        let logfile = std::path::Path::new(&std::env::args().next().unwrap())
             .file_stem()
             .unwrap()
             .to_string_lossy()
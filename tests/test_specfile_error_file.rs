@@ -0,0 +1,62 @@
+#[cfg(feature = "specfile")]
+mod a {
+    use flexi_logger::Logger;
+    use log::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_specfile_error_file() {
+        // we use a timestamp in the directory names to allow repeated runs
+        let ts = chrono::Local::now()
+            .format("log_files/specfile_error_file/%Y-%m-%d_%H-%M-%S%.6f")
+            .to_string();
+        let directory = format!("{}/output", ts);
+        let specfile = format!("{}/logspec.toml", ts);
+        std::fs::create_dir_all(Path::new(&specfile).parent().unwrap()).unwrap();
+        std::fs::write(
+            &specfile,
+            format!(
+                "global_level = 'info'\n\
+                 [modules]\n\
+                 [output]\n\
+                 target = 'file'\n\
+                 directory = '{}'\n\
+                 format = 'default'\n",
+                directory
+            ),
+        )
+        .unwrap();
+
+        // the error file is kept when the output section is applied
+        Logger::with_str("info")
+            .log_to_file()
+            .error_file(None)
+            .start_with_specfile(&specfile)
+            .unwrap_or_else(|e| panic!("Logger initialization failed because: {}", e));
+
+        error!("This is an error message");
+        info!("This is an info message");
+        log::logger().flush();
+
+        let mut files: Vec<_> = std::fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        files.sort();
+        assert_eq!(files.len(), 2);
+        assert!(files[1]
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .contains("_error_"));
+        assert_eq!(
+            std::fs::read_to_string(&files[0]).unwrap(),
+            "ERROR [test_specfile_error_file::a] This is an error message\n\
+             INFO [test_specfile_error_file::a] This is an info message\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(&files[1]).unwrap(),
+            "ERROR [test_specfile_error_file::a] This is an error message\n"
+        );
+    }
+}
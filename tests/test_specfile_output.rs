@@ -0,0 +1,99 @@
+#[cfg(feature = "specfile")]
+mod a {
+    use flexi_logger::writers::LogWriter;
+    use flexi_logger::{DeferredNow, LogTarget, Logger, Record};
+    use log::*;
+    use std::io::Write;
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+
+    const WAIT: u64 = 1600;
+
+    #[test]
+    fn test_specfile_output() {
+        // we use a timestamp in the directory names to allow repeated runs
+        let ts = chrono::Local::now()
+            .format("log_files/specfile_output/%Y-%m-%d_%H-%M-%S%.6f")
+            .to_string();
+        let dir_1 = format!("{}/1", ts);
+        let dir_2 = format!("{}/2", ts);
+        let specfile = format!("{}/logspec.toml", ts);
+
+        write_specfile(&specfile, "info", &dir_1);
+        let messages = Arc::new(Mutex::new(Vec::<String>::new()));
+        Logger::with_str("info")
+            .log_target(LogTarget::FileAndWriter(Box::new(MessageWriter(
+                Arc::clone(&messages),
+            ))))
+            .start_with_specfile(&specfile)
+            .unwrap_or_else(|e| panic!("Logger initialization failed because: {}", e));
+
+        error!("This is an error message");
+        debug!("This is a debug message");
+
+        write_specfile(&specfile, "debug", &dir_2);
+        std::thread::sleep(std::time::Duration::from_millis(WAIT));
+
+        warn!("This is a warning");
+        debug!("This is another debug message");
+        log::logger().flush();
+
+        assert_eq!(
+            read_single_file(&dir_1),
+            "ERROR [test_specfile_output::a] This is an error message\n"
+        );
+        assert_eq!(
+            read_single_file(&dir_2),
+            "WARN [test_specfile_output::a] This is a warning\n\
+             DEBUG [test_specfile_output::a] This is another debug message\n"
+        );
+        // the writer of the log target is kept when the output section is applied
+        assert_eq!(
+            *messages.lock().unwrap(),
+            vec![
+                "This is an error message",
+                "This is a warning",
+                "This is another debug message"
+            ]
+        );
+    }
+
+    struct MessageWriter(Arc<Mutex<Vec<String>>>);
+    impl LogWriter for MessageWriter {
+        fn write(&self, _now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+            self.0.lock().unwrap().push(record.args().to_string());
+            Ok(())
+        }
+        fn flush(&self) -> std::io::Result<()> {
+            Ok(())
+        }
+        fn max_log_level(&self) -> log::LevelFilter {
+            log::LevelFilter::Trace
+        }
+    }
+
+    fn write_specfile(specfile: &str, level: &str, directory: &str) {
+        std::fs::create_dir_all(Path::new(specfile).parent().unwrap()).unwrap();
+        let mut file = std::fs::File::create(specfile).unwrap();
+        write!(
+            file,
+            "global_level = '{}'\n\
+             [modules]\n\
+             [output]\n\
+             target = 'file'\n\
+             directory = '{}'\n\
+             format = 'default'\n",
+            level, directory
+        )
+        .unwrap();
+    }
+
+    fn read_single_file(directory: &str) -> String {
+        let files: Vec<_> = std::fs::read_dir(directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(files.len(), 1);
+        std::fs::read_to_string(&files[0]).unwrap()
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(feature = "specfile")]
+mod a {
+    use flexi_logger::writers::LogWriter;
+    use flexi_logger::{DeferredNow, LogTarget, Logger, Record};
+    use log::*;
+    use std::process::Command;
+    use std::sync::{Arc, Mutex};
+
+    const CHILD: &str = "FLEXI_LOGGER_TEST_SPECFILE_OUTPUT_WRITER_CHILD";
+
+    // An output section without target must not start duplicating to stderr.
+    // The test runs itself in a child process, to check what the child writes to stderr.
+    #[test]
+    fn test_specfile_output_writer() {
+        if std::env::var(CHILD).is_ok() {
+            let specfile = chrono::Local::now()
+                .format("log_files/specfile_output_writer/%Y-%m-%d_%H-%M-%S%.6f/logspec.toml")
+                .to_string();
+            std::fs::create_dir_all(std::path::Path::new(&specfile).parent().unwrap()).unwrap();
+            std::fs::write(
+                &specfile,
+                "global_level = 'info'\n\
+                 [modules]\n\
+                 [output]\n\
+                 format = 'detailed'\n",
+            )
+            .unwrap();
+
+            let messages = Arc::new(Mutex::new(Vec::<String>::new()));
+            Logger::with_str("info")
+                .log_target(LogTarget::Writer(Box::new(MessageWriter(Arc::clone(
+                    &messages,
+                )))))
+                .start_with_specfile(&specfile)
+                .unwrap_or_else(|e| panic!("Logger initialization failed because: {}", e));
+
+            error!("This is an error message");
+            info!("This is an info message");
+            log::logger().flush();
+
+            assert_eq!(
+                *messages.lock().unwrap(),
+                vec!["This is an error message", "This is an info message"]
+            );
+            return;
+        }
+
+        let output = Command::new(std::env::current_exe().unwrap())
+            .args(["a::test_specfile_output_writer", "--exact", "--nocapture"])
+            .env(CHILD, "1")
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8(output.stderr).unwrap(), "");
+    }
+
+    struct MessageWriter(Arc<Mutex<Vec<String>>>);
+    impl LogWriter for MessageWriter {
+        fn write(&self, _now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+            self.0.lock().unwrap().push(record.args().to_string());
+            Ok(())
+        }
+        fn flush(&self) -> std::io::Result<()> {
+            Ok(())
+        }
+        fn max_log_level(&self) -> log::LevelFilter {
+            log::LevelFilter::Trace
+        }
+    }
+}
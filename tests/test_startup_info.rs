@@ -0,0 +1,26 @@
+use flexi_logger::{detailed_format, Logger, StartupInfo};
+use log::*;
+
+#[test]
+fn test_startup_info() {
+    let handle = Logger::with_str("info")
+        .format(detailed_format)
+        .log_to_file()
+        .directory("log_files/startup_info")
+        .log_startup_info(
+            StartupInfo::new("test-startup-info", "1.2.3").build_info("abc1234, release build"),
+        )
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("This is an info message");
+
+    handle.validate_logs(&[
+        (
+            "INFO",
+            "[test_startup_info]",
+            "test-startup-info 1.2.3 (abc1234, release build) started",
+        ),
+        ("INFO", "[test_startup_info]", "This is an info message"),
+    ]);
+}
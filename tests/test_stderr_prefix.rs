@@ -0,0 +1,72 @@
+use flexi_logger::{default_format, Logger};
+use log::*;
+
+use std::process::Command;
+
+const CHILD: &str = "FLEXI_LOGGER_TEST_STDERR_PREFIX_CHILD";
+const CHILD_MLL: &str = "FLEXI_LOGGER_TEST_STDERR_PREFIX_MLL_CHILD";
+
+// The test runs itself in a child process, to check what the child writes to stderr.
+#[test]
+fn test_stderr_prefix() {
+    if std::env::var(CHILD).is_ok() {
+        Logger::with_str("info")
+            .format(default_format)
+            .stderr_prefix("[app] ")
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        error!("This is an error message\nthat spans\nthree lines");
+        warn!("This is a warning");
+        debug!("This is a debug message - you must not see it!");
+        return;
+    }
+
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(["test_stderr_prefix", "--exact", "--nocapture"])
+        .env(CHILD, "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "[app] ERROR [test_stderr_prefix] This is an error message\n\
+         [app] that spans\n\
+         [app] three lines\n\
+         [app] WARN [test_stderr_prefix] This is a warning\n"
+    );
+}
+
+// The prefix counts against the maximum line length.
+#[test]
+fn test_stderr_prefix_max_line_length() {
+    if std::env::var(CHILD_MLL).is_ok() {
+        // "[app] WARN [test_stderr_prefix] " has 32 bytes
+        Logger::with_str("info")
+            .format(default_format)
+            .stderr_prefix("[app] ")
+            .max_line_length(38)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        error!("ok");
+        warn!("{}", "x".repeat(100));
+        return;
+    }
+
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args([
+            "test_stderr_prefix_max_line_length",
+            "--exact",
+            "--nocapture",
+        ])
+        .env(CHILD_MLL, "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "[app] ERROR [test_stderr_prefix] ok\n\
+         [app] WARN [test_stderr_prefix] xxxxxx…\n"
+    );
+}
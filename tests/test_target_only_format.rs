@@ -0,0 +1,42 @@
+use flexi_logger::writers::LogWriter;
+use flexi_logger::{target_only_format, DeferredNow, LogTarget, Logger, Record};
+use log::*;
+
+use std::io;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_target_only_format() {
+    let lines = Arc::new(Mutex::new(Vec::<String>::new()));
+    Logger::with_str("info")
+        .log_target(LogTarget::Writer(Box::new(LineWriter(Arc::clone(&lines)))))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!(target: "metrics", "requests: 17");
+    warn!("This is a warning");
+
+    assert_eq!(
+        *lines.lock().unwrap(),
+        vec![
+            "INFO [metrics] requests: 17",
+            "WARN [test_target_only_format] This is a warning",
+        ]
+    );
+}
+
+struct LineWriter(Arc<Mutex<Vec<String>>>);
+impl LogWriter for LineWriter {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> io::Result<()> {
+        let mut buf = Vec::<u8>::new();
+        target_only_format(&mut buf, now, record)?;
+        self.0.lock().unwrap().push(String::from_utf8(buf).unwrap());
+        Ok(())
+    }
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+    fn max_log_level(&self) -> log::LevelFilter {
+        log::LevelFilter::Trace
+    }
+}
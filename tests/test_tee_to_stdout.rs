@@ -0,0 +1,61 @@
+use flexi_logger::{detailed_format, Logger};
+use log::*;
+
+use std::process::Command;
+
+const CHILD: &str = "FLEXI_LOGGER_TEST_TEE_TO_STDOUT_CHILD";
+
+// The test runs itself in a child process, to check what the child writes to stdout.
+#[test]
+fn test_tee_to_stdout() {
+    if std::env::var(CHILD).is_ok() {
+        let handle = Logger::with_str("info")
+            .format(detailed_format)
+            .log_to_file()
+            .directory("log_files")
+            .tee_to_stdout()
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        error!("This is an error message");
+        warn!("This is a warning");
+        info!("This is an info message");
+        debug!("This is a debug message - you must not see it!");
+
+        handle.validate_logs(&[
+            ("ERROR", "test_tee_to_stdout", "error"),
+            ("WARN", "test_tee_to_stdout", "warning"),
+            ("INFO", "test_tee_to_stdout", "info"),
+        ]);
+        return;
+    }
+
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(["test_tee_to_stdout", "--exact", "--nocapture"])
+        .env(CHILD, "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    // the test harness writes to stdout, too
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout
+        .lines()
+        .filter(|line| line.contains("[test_tee_to_stdout]"))
+        .collect();
+    assert_eq!(lines.len(), 3, "unexpected stdout: {}", stdout);
+    for (line, (level, text)) in lines.iter().zip(&[
+        ("ERROR", "This is an error message"),
+        ("WARN", "This is a warning"),
+        ("INFO", "This is an info message"),
+    ]) {
+        assert!(line.contains(level), "unexpected line: {}", line);
+        assert!(line.ends_with(text), "unexpected line: {}", line);
+        // the file format is used, with source file and line
+        assert!(
+            line.contains("tests/test_tee_to_stdout.rs:"),
+            "unexpected line: {}",
+            line
+        );
+    }
+}
@@ -0,0 +1,42 @@
+#[cfg(feature = "specfile")]
+mod a {
+    use flexi_logger::{detailed_format, Logger};
+    use log::*;
+
+    const DEFAULT_SPEC: &str = r#"
+global_level = 'info'
+
+[modules]
+'test_toml_str::a::mymod' = 'debug'
+"#;
+
+    #[test]
+    fn test_toml_str() {
+        let handle = Logger::with_toml_str(DEFAULT_SPEC)
+            .check_parser_error()
+            .unwrap()
+            .format(detailed_format)
+            .log_to_file()
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        info!("This is an info message");
+        debug!("This is a debug message - you must not see it!");
+        mymod::test_traces();
+
+        handle.validate_logs(&[
+            ("INFO", "test_toml_str::a", "info"),
+            ("INFO", "test_toml_str::a::mymod", "info"),
+            ("DEBUG", "test_toml_str::a::mymod", "debug"),
+        ]);
+    }
+
+    mod mymod {
+        use log::*;
+        pub fn test_traces() {
+            info!("This is an info message");
+            debug!("This is a debug message");
+            trace!("This is a trace message - you must not see it!");
+        }
+    }
+}
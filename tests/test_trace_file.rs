@@ -0,0 +1,87 @@
+use flexi_logger::writers::LogWriter;
+use flexi_logger::{default_format, DeferredNow, Logger, Record};
+use log::*;
+
+use std::io;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_trace_file() {
+    // we use a timestamp in the directory name to allow repeated runs
+    let dir = chrono::Local::now()
+        .format("log_files/trace_file/%Y-%m-%d_%H-%M-%S%.6f")
+        .to_string();
+
+    let lines = Arc::new(Mutex::new(Vec::<String>::new()));
+    Logger::with_str("info")
+        .format(default_format)
+        .log_to_file()
+        .directory(&dir)
+        .discriminant("app")
+        .trace_file()
+        .add_writer("Collector", Box::new(Collector(Arc::clone(&lines))))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+    assert_eq!(log::max_level(), LevelFilter::Trace);
+
+    error!("This is an error message");
+    info!("This is an info message");
+    debug!("This is a debug message");
+    trace!("This is a trace message");
+    info!(target: "{Collector}", "This is an info message for the collector");
+    debug!(target: "{Collector}", "This is a debug message the collector does not accept");
+    log::logger().flush();
+
+    assert_eq!(
+        *lines.lock().unwrap(),
+        vec!["This is an info message for the collector".to_string()]
+    );
+
+    let mut files: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    files.sort();
+    assert_eq!(files.len(), 2);
+    assert!(files[1]
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .contains("_app_trace_"));
+
+    let main_content = std::fs::read_to_string(&files[0]).unwrap();
+    assert_eq!(
+        main_content.lines().collect::<Vec<_>>(),
+        vec![
+            "ERROR [test_trace_file] This is an error message",
+            "INFO [test_trace_file] This is an info message",
+        ]
+    );
+    let trace_content = std::fs::read_to_string(&files[1]).unwrap();
+    assert_eq!(
+        trace_content.lines().collect::<Vec<_>>(),
+        vec![
+            "ERROR [test_trace_file] This is an error message",
+            "INFO [test_trace_file] This is an info message",
+            "DEBUG [test_trace_file] This is a debug message",
+            "TRACE [test_trace_file] This is a trace message",
+            "INFO [test_trace_file] This is an info message for the collector",
+            "DEBUG [test_trace_file] This is a debug message the collector does not accept",
+        ]
+    );
+}
+
+// collects the messages of the records up to level info
+struct Collector(Arc<Mutex<Vec<String>>>);
+impl LogWriter for Collector {
+    fn write(&self, _now: &mut DeferredNow, record: &Record) -> io::Result<()> {
+        self.0.lock().unwrap().push(record.args().to_string());
+        Ok(())
+    }
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+    fn max_log_level(&self) -> log::LevelFilter {
+        log::LevelFilter::Info
+    }
+}
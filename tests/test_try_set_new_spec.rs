@@ -0,0 +1,46 @@
+use flexi_logger::{LogSpecification, Logger};
+use log::*;
+
+#[test]
+fn test_try_set_new_spec() {
+    let mut log_handle = Logger::with_str("info")
+        .log_to_file()
+        .directory("log_files/try_set_new_spec")
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("1-info message");
+    debug!("1-debug message - you must not see it!");
+
+    // without contention, the lock is acquired immediately
+    let mut new_spec = LogSpecification::parse("debug").unwrap();
+    while let Err(spec) = log_handle.try_set_new_spec(new_spec) {
+        std::thread::yield_now();
+        new_spec = spec;
+    }
+    assert_eq!(log_handle.max_level(), LevelFilter::Debug);
+    debug!("2-debug message");
+
+    // the spec can also be tried from another thread, while this one is logging
+    let mut other_handle = log_handle.clone();
+    let worker = std::thread::spawn(move || {
+        let mut new_spec = LogSpecification::parse("warn").unwrap();
+        while let Err(spec) = other_handle.try_set_new_spec(new_spec) {
+            std::thread::yield_now();
+            new_spec = spec;
+        }
+    });
+    for _ in 0..100 {
+        trace!("3-trace message - you must not see it!");
+    }
+    worker.join().unwrap();
+    assert_eq!(log_handle.max_level(), LevelFilter::Warn);
+    info!("4-info message - you must not see it!");
+    warn!("4-warning");
+
+    log_handle.validate_logs(&[
+        ("INFO", "test_try_set_new_spec", "1-info"),
+        ("DEBUG", "test_try_set_new_spec", "2-debug"),
+        ("WARN", "test_try_set_new_spec", "4-warning"),
+    ]);
+}
@@ -0,0 +1,70 @@
+#[cfg(unix)]
+mod test {
+    use flexi_logger::writers::UnixSocketWriter;
+    use flexi_logger::{default_format, Logger};
+    use log::*;
+
+    use std::io::{BufRead, BufReader};
+    use std::net::Shutdown;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::Path;
+
+    #[test]
+    fn test_unix_socket_writer() {
+        // we use a timestamp in the directory name to allow repeated runs
+        let dir = chrono::Local::now()
+            .format("log_files/unix_socket_writer/%Y-%m-%d_%H-%M-%S%.6f")
+            .to_string();
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = Path::new(&dir).join("collector.sock");
+
+        let listener = UnixListener::bind(&path).unwrap();
+        Logger::with_str("info")
+            .do_not_log()
+            .add_writer(
+                "Collector",
+                Box::new(
+                    UnixSocketWriter::try_new(&path, LevelFilter::Info)
+                        .unwrap()
+                        .format(default_format),
+                ),
+            )
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+        let (connection, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(connection.try_clone().unwrap());
+
+        info!(target: "{Collector}", "first message");
+        warn!(target: "{Collector}", "second message");
+        assert_eq!(
+            read_line(&mut reader),
+            "INFO [test_unix_socket_writer::test] first message"
+        );
+        assert_eq!(
+            read_line(&mut reader),
+            "WARN [test_unix_socket_writer::test] second message"
+        );
+
+        // the collector is restarted
+        connection.shutdown(Shutdown::Both).unwrap();
+        drop(reader);
+        drop(connection);
+        drop(listener);
+        std::fs::remove_file(&path).unwrap();
+        let listener = UnixListener::bind(&path).unwrap();
+
+        info!(target: "{Collector}", "third message");
+        let (connection, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(connection);
+        assert_eq!(
+            read_line(&mut reader),
+            "INFO [test_unix_socket_writer::test] third message"
+        );
+    }
+
+    fn read_line(reader: &mut BufReader<UnixStream>) -> String {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        line.trim_end().to_string()
+    }
+}
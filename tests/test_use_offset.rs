@@ -0,0 +1,22 @@
+use chrono::FixedOffset;
+use flexi_logger::{opt_format, Logger};
+use log::*;
+
+#[test]
+fn test_use_offset() {
+    let handle = Logger::with_str("info")
+        .format(opt_format)
+        .log_to_file()
+        .directory("log_files")
+        .use_offset(FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap())
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("This is an error message");
+    info!("This is an info message");
+
+    handle.validate_logs(&[
+        ("ERROR", " +05:30] ", "This is an error message"),
+        ("INFO", " +05:30] ", "This is an info message"),
+    ]);
+}
@@ -0,0 +1,61 @@
+use flexi_logger::writers::LogWriter;
+use flexi_logger::{default_format, DeferredNow, LogTarget, Logger, Record};
+use log::*;
+
+use std::io;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_writer_levels() {
+    let lines = Arc::new(Mutex::new(Vec::<String>::new()));
+    let handle = Logger::with_str("debug")
+        .format(default_format)
+        .log_target(LogTarget::FileAndWriter(Box::new(WarnCollector(
+            Arc::clone(&lines),
+        ))))
+        .directory("log_files/writer_levels")
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("This is an error message");
+    warn!("This is a warning");
+    info!("This is an info message");
+    debug!("This is a debug message");
+    log::logger().flush();
+
+    // the collector gets only the records up to its own level
+    assert_eq!(
+        *lines.lock().unwrap(),
+        vec![
+            "This is an error message".to_string(),
+            "This is a warning".to_string()
+        ]
+    );
+
+    // the file gets all records that pass the log specification
+    let content = std::fs::read_to_string(&handle.log_file_info().unwrap().current_file).unwrap();
+    assert_eq!(
+        content.lines().collect::<Vec<_>>(),
+        vec![
+            "ERROR [test_writer_levels] This is an error message",
+            "WARN [test_writer_levels] This is a warning",
+            "INFO [test_writer_levels] This is an info message",
+            "DEBUG [test_writer_levels] This is a debug message",
+        ]
+    );
+}
+
+// collects the messages of the records up to level warn
+struct WarnCollector(Arc<Mutex<Vec<String>>>);
+impl LogWriter for WarnCollector {
+    fn write(&self, _now: &mut DeferredNow, record: &Record) -> io::Result<()> {
+        self.0.lock().unwrap().push(record.args().to_string());
+        Ok(())
+    }
+    fn flush(&self) -> io::Result<()> {
+        Ok(())
+    }
+    fn max_log_level(&self) -> log::LevelFilter {
+        log::LevelFilter::Warn
+    }
+}
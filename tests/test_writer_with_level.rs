@@ -0,0 +1,51 @@
+use flexi_logger::writers::{FileLogWriter, LogWriter};
+use flexi_logger::{DeferredNow, Logger, Record};
+use log::*;
+
+use std::io;
+use std::sync::Arc;
+
+#[test]
+fn test_writer_with_level() {
+    let a_flw = Arc::new(
+        FileLogWriter::builder()
+            .discriminant("Audit")
+            .directory("log_files")
+            .try_build()
+            .unwrap(),
+    );
+    Logger::with_str("info")
+        .log_to_file()
+        .directory("log_files")
+        .add_writer_with_level(
+            "Audit",
+            Box::new(AuditWriter(Arc::clone(&a_flw))),
+            LevelFilter::Warn,
+        )
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!(target: "{Audit}", "This is an audited error message");
+    warn!(target: "{Audit}", "This is an audited warning");
+    info!(target: "{Audit}", "This is an info message - you must not see it!");
+    debug!(target: "{Audit}", "This is a debug message - you must not see it!");
+
+    a_flw.validate_logs(&[
+        ("ERROR", "test_writer_with_level", "audited error"),
+        ("WARN", "test_writer_with_level", "audited warning"),
+    ]);
+}
+
+// writes everything it gets
+struct AuditWriter(Arc<FileLogWriter>);
+impl LogWriter for AuditWriter {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> io::Result<()> {
+        self.0.write(now, record)
+    }
+    fn flush(&self) -> io::Result<()> {
+        self.0.flush()
+    }
+    fn max_log_level(&self) -> log::LevelFilter {
+        log::LevelFilter::Trace
+    }
+}
@@ -0,0 +1,39 @@
+use flexi_logger::writers::FileLogWriter;
+use flexi_logger::Logger;
+use log::*;
+
+#[test]
+fn test_writer_with_level_shutdown() {
+    // we use a timestamp in the directory name to allow repeated runs
+    let dir = chrono::Local::now()
+        .format("log_files/writer_with_level_shutdown/%Y-%m-%d_%H-%M-%S%.6f")
+        .to_string();
+    let flw = FileLogWriter::builder()
+        .directory(&dir)
+        .discriminant("Audit")
+        .flush_every(10)
+        .end_marker("=== END ===")
+        .try_build()
+        .unwrap();
+    let path = flw.current_filename();
+
+    let handle = Logger::with_str("info")
+        .do_not_log()
+        .add_writer_with_level("Audit", Box::new(flw), LevelFilter::Warn)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    warn!(target: "{Audit}", "This is an audited warning");
+    info!(target: "{Audit}", "This is an info message - you must not see it!");
+
+    // the buffered record and the end marker are written by the wrapped writer
+    handle.shutdown();
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(
+        content.lines().collect::<Vec<_>>(),
+        vec![
+            "WARN [test_writer_with_level_shutdown] This is an audited warning",
+            "=== END ===",
+        ]
+    );
+}